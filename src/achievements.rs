@@ -0,0 +1,69 @@
+// Achievements: feats a player pulls off during a run, earned once and kept
+// for good. Like unlocks they're saved on their own, so clearing the
+// leaderboard doesn't take them away. The bot and the attract demo never
+// earn any.
+
+use super::*;
+
+const BULLSEYE_PERFECT_CATCHES: u32 = 10;
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum Achievement {
+    Bullseye, // Perfect catches in one run
+}
+
+pub(crate) const ACHIEVEMENTS: [Achievement; 1] = [Achievement::Bullseye];
+
+impl Achievement {
+    pub(crate) fn id(self) -> &'static str {
+        match self {
+            Achievement::Bullseye => "bullseye",
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Achievement::Bullseye => "BULLSEYE",
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Achievements {
+    pub(crate) earned: Vec<Achievement>,
+}
+
+impl Achievements {
+    pub(crate) fn has(&self, achievement: Achievement) -> bool {
+        self.earned.contains(&achievement)
+    }
+}
+
+impl GameState {
+    // Awards anything a player's run so far has earned them
+    pub(crate) fn check_achievements(&mut self, player_index: usize) {
+        if self.is_bot(player_index) {
+            return;
+        }
+        let Some(slot) = self
+            .players
+            .iter()
+            .find(|slot| slot.player_index == player_index)
+        else {
+            return;
+        };
+        if slot.stats.perfect_catches >= BULLSEYE_PERFECT_CATCHES {
+            self.award(Achievement::Bullseye);
+        }
+    }
+
+    fn award(&mut self, achievement: Achievement) {
+        if self.achievements.has(achievement) {
+            return;
+        }
+        self.achievements.earned.push(achievement);
+        self.progress_changed();
+        self.ticker
+            .push(format!("ACHIEVEMENT: {}!", achievement.name()));
+    }
+}
@@ -0,0 +1,218 @@
+// The computer player's brain, shared by everything that needs one: the
+// versus bot, and the attract mode demo that plays itself on an idle menu.
+// It's an InputSource holding one player's d-pad, so whatever it drives
+// moves through the same bindings and commands as a person would, and runs
+// it plays replay and record like any other.
+//
+// Each tick it weighs three moves (left, stay, right) by where every object
+// on screen is going to land: deals pull it toward them, hazards about to
+// come down on it push it away, and both matter more the closer they are to
+// the floor. It acts on each decision a reaction time late.
+//
+// How good it is comes from a BotLevel: how late it reacts, how well it
+// reads where things will land, and how often it has a lapse and wanders
+// off for a moment. Its own rng decides the lapses, so the game's stays
+// untouched.
+
+use super::*;
+
+const LOOKAHEAD_TICKS: f64 = 8.0; // How far ahead of each move it judges where it'll be
+const DEAL_PULL: f64 = 1.0;
+const HAZARD_PUSH: f64 = 4.0;
+const HAZARD_MARGIN: f64 = 6.0; // Extra room it gives a hazard either side
+const LAPSE_TICKS: u32 = 20; // How long a mistake lasts
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum BotLevel {
+    Easy,
+    Medium,
+    Nightmare,
+}
+
+const BOT_LEVELS: [BotLevel; 3] = [BotLevel::Easy, BotLevel::Medium, BotLevel::Nightmare];
+
+// What a level comes down to
+struct BotSkill {
+    reaction_ticks: usize,
+    prediction: f64, // 0 judges objects where they are, 1 exactly where they'll land
+    mistake_rate: f64, // Chance each tick of starting a lapse
+}
+
+impl BotLevel {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            BotLevel::Easy => "Easy",
+            BotLevel::Medium => "Medium",
+            BotLevel::Nightmare => "Nightmare",
+        }
+    }
+
+    fn skill(&self) -> BotSkill {
+        match self {
+            BotLevel::Easy => BotSkill {
+                reaction_ticks: 24,
+                prediction: 0.4,
+                mistake_rate: 0.02,
+            },
+            BotLevel::Medium => BotSkill {
+                reaction_ticks: 12,
+                prediction: 0.8,
+                mistake_rate: 0.005,
+            },
+            BotLevel::Nightmare => BotSkill {
+                reaction_ticks: 3,
+                prediction: 1.0,
+                mistake_rate: 0.0,
+            },
+        }
+    }
+
+    pub(crate) fn next(&self) -> Self {
+        let i = BOT_LEVELS
+            .iter()
+            .position(|level| level == self)
+            .unwrap_or(0);
+        BOT_LEVELS[(i + 1) % BOT_LEVELS.len()]
+    }
+
+    pub(crate) fn previous(&self) -> Self {
+        let i = BOT_LEVELS
+            .iter()
+            .position(|level| level == self)
+            .unwrap_or(0);
+        BOT_LEVELS[(i + BOT_LEVELS.len() - 1) % BOT_LEVELS.len()]
+    }
+}
+
+pub(crate) struct AiController {
+    pub(crate) player_index: usize,
+    pub(crate) level: BotLevel,
+    skill: BotSkill,
+    decisions: VecDeque<Option<Command>>, // Made but not yet acted on, oldest first
+    lapse: Option<(Option<Command>, u32)>, // A wrong move and how many more ticks to hold it
+    held: Option<Command>,                // What poll reports until the next look
+    rng: StdRng,
+}
+
+impl AiController {
+    pub(crate) fn new(player_index: usize, level: BotLevel) -> Self {
+        AiController {
+            player_index,
+            level,
+            skill: level.skill(),
+            decisions: VecDeque::new(),
+            lapse: None,
+            held: None,
+            rng: StdRng::seed_from_u64(rand::random()),
+        }
+    }
+
+    // Forgets anything left over from a previous run
+    pub(crate) fn reset(&mut self) {
+        self.decisions.clear();
+        self.lapse = None;
+        self.held = None;
+    }
+
+    // Looks at the run once per tick and settles what to hold for it
+    pub(crate) fn observe(&mut self, state: &GameState) {
+        self.held = self.think(state);
+    }
+
+    fn think(&mut self, state: &GameState) -> Option<Command> {
+        if self.lapse.is_none() && self.rng.gen_bool(self.skill.mistake_rate) {
+            let command = match self.rng.gen_range(0..3) {
+                0 => None,
+                1 => Some(Command::MoveLeft(self.player_index)),
+                _ => Some(Command::MoveRight(self.player_index)),
+            };
+            self.lapse = Some((command, LAPSE_TICKS));
+        }
+        if let Some((command, remaining)) = &mut self.lapse {
+            let command = *command;
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.lapse = None;
+            }
+            return command;
+        }
+
+        self.decisions.push_back(self.decide(state));
+        if self.decisions.len() > self.skill.reaction_ticks {
+            self.decisions.pop_front().flatten()
+        } else {
+            None
+        }
+    }
+
+    fn decide(&self, state: &GameState) -> Option<Command> {
+        let slot = state
+            .players
+            .iter()
+            .find(|slot| slot.player_index == self.player_index && slot.health > 0)?;
+        let player = &slot.player;
+        let center = player.x + player.width / 2.0;
+        let step = state.tunables.player_speed * LOOKAHEAD_TICKS;
+        let half_width = player.width / 2.0;
+        let world = state.world;
+        let floor_y = world.floor_y();
+        let utility = |x: f64| -> f64 {
+            state
+                .objects
+                .iter()
+                .map(|obj| {
+                    let landing = obj.landing_x(state.wind, floor_y).unwrap_or(obj.x);
+                    let judged = obj.x + (landing - obj.x) * self.skill.prediction;
+                    let landing = judged + obj.width / 2.0;
+                    let distance = (landing - x).abs();
+                    let urgency = ((obj.y + obj.height) / floor_y).clamp(0.0, 1.0);
+                    match obj.obj_type {
+                        ObjectType::GoodDeal | ObjectType::EnergyDrink => {
+                            DEAL_PULL * urgency / (1.0 + distance / player.width)
+                        }
+                        _ => {
+                            let clearance = obj.width / 2.0 + half_width + HAZARD_MARGIN;
+                            let overlap = (1.0 - distance / clearance).max(0.0);
+                            -HAZARD_PUSH * urgency * overlap
+                        }
+                    }
+                })
+                .sum()
+        };
+        let clamp = |x: f64| x.clamp(half_width, world.width - half_width);
+        let moves = [
+            (None, center),
+            (
+                Some(Command::MoveLeft(self.player_index)),
+                clamp(center - step),
+            ),
+            (
+                Some(Command::MoveRight(self.player_index)),
+                clamp(center + step),
+            ),
+        ];
+        // Standing still wins ties
+        let mut best = (None, utility(center));
+        for (command, x) in moves.into_iter().skip(1) {
+            let value = utility(x);
+            if value > best.1 {
+                best = (command, value);
+            }
+        }
+        best.0
+    }
+}
+
+impl InputSource for AiController {
+    fn poll(&self) -> InputSnapshot {
+        let mut snapshot = InputSnapshot::default();
+        match self.held {
+            Some(Command::MoveLeft(0)) => snapshot.player1_left = true,
+            Some(Command::MoveRight(0)) => snapshot.player1_right = true,
+            Some(Command::MoveLeft(_)) => snapshot.player2_left = true,
+            Some(Command::MoveRight(_)) => snapshot.player2_right = true,
+            _ => {}
+        }
+        snapshot
+    }
+}
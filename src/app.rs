@@ -0,0 +1,467 @@
+// The running game and everything a frame touches, owned in one place. The
+// requestAnimationFrame trampoline in run() owns the App outright, so nothing
+// else can hold a borrow of it mid-frame. Work that finishes asynchronously
+// (the cabinet controller, IndexedDB) posts a message to the inbox instead,
+// and the next frame picks it up before reading input.
+
+use super::*;
+
+pub(crate) enum AppMessage {
+    ControllerAcquired(ClassicController),
+    StoreOpened(Rc<dyn KeyValueStore>),
+    Resized,
+    #[cfg(feature = "netplay")]
+    Netplay(netplay::NetMessage),
+    #[cfg(feature = "netplay")]
+    NetplayClosed,
+    #[cfg(feature = "cabinet-sync")]
+    CabinetChampions(Vec<cabinet::CabinetChampion>),
+}
+
+pub(crate) struct App {
+    state: GameState,
+    inputs: InputSources,
+    commands: CommandMapper,
+    scheduler: FixedStep,
+    resolution: ResolutionGovernor,
+    resync: ResyncFade,
+    renderer: Box<dyn Renderer>,
+    crt: CrtPass,
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    viewport: Rc<Cell<Viewport>>,
+    touch: TouchControls,
+    preloads: Vec<Preload>,
+    inbox: Rc<RefCell<Vec<AppMessage>>>,
+    run_saver: RunSaver,
+    last_frame_ms: Option<f64>,
+    #[cfg(feature = "netplay")]
+    netplay: Option<netplay::Netplay>,
+    #[cfg(feature = "cabinet-sync")]
+    cabinet: Option<cabinet::CabinetSync>,
+    #[cfg(feature = "cabinet-sync")]
+    cabinet_board: Option<Preload>, // Whether the first board has come in
+    #[cfg(feature = "debug-console")]
+    console: Rc<RefCell<console::Console>>,
+    #[cfg(feature = "editor")]
+    editor_commands: Rc<RefCell<Vec<editor::EditorCommand>>>,
+    #[cfg(feature = "debug-overlay")]
+    debug_views: Rc<debug::DebugViews>,
+    #[cfg(feature = "quick-spawn")]
+    quick_spawns: Rc<RefCell<Vec<quick_spawn::QuickSpawnCommand>>>,
+    #[cfg(feature = "tuning-panel")]
+    tuning_panel: Rc<RefCell<tuning::TuningPanel>>,
+}
+
+impl App {
+    pub(crate) fn new(canvas: &HtmlCanvasElement) -> Result<App, JsValue> {
+        let context = canvas
+            .get_context("2d")?
+            .unwrap()
+            .dyn_into::<CanvasRenderingContext2d>()?;
+
+        // The console listens first so it can keep typed keys away from the game
+        #[cfg(feature = "debug-console")]
+        let console = console::setup_listeners()?;
+
+        let config = GameConfig::from_page();
+        let viewport = Rc::new(Cell::new(Viewport::default()));
+        let resolution = ResolutionGovernor::default();
+        fit_canvas(canvas, &viewport, config.world, resolution.scale());
+
+        let mut inputs = InputSources::default();
+        inputs.register(Keyboard::listen()?);
+        inputs.register(Gamepads);
+        let touch = TouchControls::listen(canvas, viewport.clone())?;
+        inputs.register(touch.clone());
+
+        let inbox = Rc::new(RefCell::new(Vec::new()));
+        listen_for_resize(&inbox)?;
+
+        // localStorage where it's allowed. Otherwise play from memory until
+        // IndexedDB opens, then carry on with whatever was saved there.
+        let store: Rc<dyn KeyValueStore> = match LocalStorage::open() {
+            Some(storage) => Rc::new(storage),
+            None => {
+                let inbox = inbox.clone();
+                log(
+                    LogLevel::Info,
+                    "storage",
+                    "localStorage is off, trying IndexedDB",
+                );
+                spawn_local(async move {
+                    match IndexedDbStore::open().await {
+                        Ok(store) => inbox
+                            .borrow_mut()
+                            .push(AppMessage::StoreOpened(Rc::new(store))),
+                        Err(err) => log(
+                            LogLevel::Warn,
+                            "storage",
+                            &format!("IndexedDB unavailable, nothing will be saved: {err:?}"),
+                        ),
+                    }
+                });
+                Rc::new(MemoryStore::default())
+            }
+        };
+
+        // Acquire controller asynchronously
+        let controller_inbox = inbox.clone();
+        spawn_local(async move {
+            match ClassicController::acquire().await {
+                Ok(controller) => controller_inbox
+                    .borrow_mut()
+                    .push(AppMessage::ControllerAcquired(controller)),
+                Err(err) => log(
+                    LogLevel::Warn,
+                    "controller",
+                    &format!("no cabinet controls, keyboard and gamepads only: {err:?}"),
+                ),
+            }
+        });
+
+        // Everything the menu should wait for loads behind the loading screen
+        let renderer = CanvasRenderer::new(context.clone());
+        let mut preloads = Vec::new();
+        if let Some(atlas) = &config.atlas {
+            preloads.push(renderer.load_atlas(atlas.clone()));
+        }
+        if let Some(font) = &config.font {
+            preloads.push(renderer.load_font(font.clone()));
+        }
+        #[cfg(feature = "cabinet-sync")]
+        let cabinet = cabinet::CabinetSync::from_page(&inbox)?;
+        #[cfg(feature = "cabinet-sync")]
+        let cabinet_board = cabinet.as_ref().map(|_| {
+            let board = Rc::new(Cell::new(LoadStatus::Loading));
+            preloads.push(board.clone());
+            board
+        });
+        let mut state = GameState::new(config, store);
+        state.show_splash();
+        state.start_loading(preloads.len());
+
+        Ok(App {
+            state,
+            inputs,
+            commands: CommandMapper::default(),
+            scheduler: FixedStep::default(),
+            resolution,
+            resync: ResyncFade::default(),
+            renderer: Box::new(renderer),
+            crt: CrtPass::new()?,
+            canvas: canvas.clone(),
+            context,
+            #[cfg(feature = "netplay")]
+            netplay: netplay::Netplay::from_page(&inbox)?,
+            #[cfg(feature = "cabinet-sync")]
+            cabinet,
+            #[cfg(feature = "cabinet-sync")]
+            cabinet_board,
+            inbox,
+            run_saver: RunSaver::listen()?,
+            last_frame_ms: None,
+            #[cfg(feature = "debug-console")]
+            console,
+            #[cfg(feature = "editor")]
+            editor_commands: editor::setup_listeners(canvas, viewport.clone())?,
+            #[cfg(feature = "debug-overlay")]
+            debug_views: debug::setup_listeners()?,
+            #[cfg(feature = "quick-spawn")]
+            quick_spawns: quick_spawn::setup_listeners()?,
+            #[cfg(feature = "tuning-panel")]
+            tuning_panel: tuning::setup_listeners()?,
+            viewport,
+            touch,
+            preloads,
+        })
+    }
+
+    fn frame(&mut self, now_ms: f64) {
+        note_frame(&self.state);
+        // Real time for menu timers; a stall counts as no time at all
+        let elapsed_ms = self
+            .last_frame_ms
+            .replace(now_ms)
+            .map_or(0.0, |last_ms| now_ms - last_ms);
+        let elapsed_ms = if (0.0..STALL_GAP_MS).contains(&elapsed_ms) {
+            elapsed_ms
+        } else {
+            0.0
+        };
+        if self.resolution.update(now_ms) {
+            fit_canvas(
+                &self.canvas,
+                &self.viewport,
+                self.state.world,
+                self.resolution.scale(),
+            );
+        }
+        let messages = std::mem::take(&mut *self.inbox.borrow_mut());
+        for message in messages {
+            match message {
+                AppMessage::ControllerAcquired(controller) => self.inputs.register(controller),
+                AppMessage::StoreOpened(store) => self.state.set_store(store),
+                AppMessage::Resized => {
+                    fit_canvas(
+                        &self.canvas,
+                        &self.viewport,
+                        self.state.world,
+                        self.resolution.scale(),
+                    );
+                    self.state.keep_players_in_view();
+                }
+                #[cfg(feature = "netplay")]
+                AppMessage::Netplay(message) => {
+                    if let Some(netplay) = &mut self.netplay {
+                        netplay.handle(&mut self.state, message);
+                    }
+                }
+                #[cfg(feature = "netplay")]
+                AppMessage::NetplayClosed => {
+                    if let Some(netplay) = &mut self.netplay {
+                        netplay.disconnected(&mut self.state);
+                    }
+                }
+                #[cfg(feature = "cabinet-sync")]
+                AppMessage::CabinetChampions(champions) => {
+                    self.state.cabinet.champions = champions;
+                    if let Some(board) = &self.cabinet_board {
+                        board.set(LoadStatus::Ready);
+                    }
+                }
+            }
+        }
+        self.state.update_loading(&self.preloads);
+        self.state.update_splash();
+        self.state.update_credits();
+        self.state.update_menu_backdrop();
+
+        let inputs = self.inputs.poll();
+        // Any button ends the demo, and isn't taken as anything else
+        if self.state.in_demo() && inputs.any() {
+            self.state.back_to_menu();
+        }
+        self.state.update_operator(&inputs);
+        self.state.update_emotes(&inputs);
+        let commands = self.commands.map(&self.state.phase, &inputs);
+        self.handle_commands(&commands, elapsed_ms);
+        #[cfg(feature = "cabinet-sync")]
+        if let Some(cabinet) = &self.cabinet {
+            cabinet.publish_pending(&mut self.state);
+        }
+
+        #[cfg(feature = "debug-console")]
+        console::run_submitted(&self.console, &mut self.state);
+        #[cfg(feature = "quick-spawn")]
+        quick_spawn::run_queued(&self.quick_spawns, &mut self.state);
+        #[cfg(feature = "tuning-panel")]
+        tuning::run_queued(&self.tuning_panel, &mut self.state);
+
+        // Update game state
+        #[cfg(feature = "debug-overlay")]
+        let ticks = self
+            .debug_views
+            .ticks(&mut self.scheduler, now_ms, self.state.time_scale());
+        #[cfg(not(feature = "debug-overlay"))]
+        let ticks = self.scheduler.advance(now_ms, self.state.time_scale());
+        if self.scheduler.take_stall() && matches!(self.state.phase, GamePhase::Playing) {
+            self.resync.start();
+        }
+        for _ in 0..ticks {
+            let commands = self.state.with_bot(&commands);
+            #[cfg(feature = "netplay")]
+            if let Some(netplay) = &mut self.netplay
+                && netplay.tick(&mut self.state, &commands)
+            {
+                continue;
+            }
+
+            self.state.tick(&commands);
+        }
+        // An online run can't be picked back up alone
+        #[cfg(feature = "netplay")]
+        let online = self.netplay.is_some();
+        #[cfg(not(feature = "netplay"))]
+        let online = false;
+        if !online {
+            self.run_saver.refresh(&self.state);
+        }
+
+        // Draw, in logical units
+        #[cfg(feature = "profiling")]
+        let _span = profile::span("draw");
+        self.state.set_blend(self.scheduler.blend());
+        let view = self.viewport.get();
+        view.begin_frame(&self.context);
+        let ctx = &*self.renderer;
+        ctx.begin_frame();
+        ctx.set_pixel_snap(self.state.settings.pixel_snap);
+        draw(ctx, &self.state);
+        self.resync.draw(ctx, self.state.world);
+        self.touch.draw(ctx, self.state.world);
+        #[cfg(feature = "debug-overlay")]
+        self.debug_views.draw(ctx, &self.state);
+        #[cfg(feature = "debug-console")]
+        console::draw(ctx, &self.console.borrow(), self.state.world);
+        #[cfg(feature = "tuning-panel")]
+        tuning::draw(ctx, &self.tuning_panel.borrow(), &self.state);
+        view.end_frame(&self.context);
+        if self.state.settings.crt_effect {
+            self.crt.apply(&self.context, view);
+        }
+    }
+
+    // Menu, name entry and editor input. Gameplay movement is applied per
+    // simulation tick instead.
+    fn handle_commands(&mut self, commands: &[Command], elapsed_ms: f64) {
+        let state = &mut self.state;
+        let issued = |command| commands.contains(&command);
+        let mut start = None;
+        let mut chosen = None;
+        state.tick_attract(commands);
+
+        // Editor keys and clicks queue up regardless; only the editor uses them
+        #[cfg(feature = "editor")]
+        let mut editor_commands = std::mem::take(&mut *self.editor_commands.borrow_mut());
+
+        match &mut state.phase {
+            GamePhase::ModeSelect { selection, .. } => {
+                move_selection(
+                    selection,
+                    MENU_ITEMS.len(),
+                    menu_prev(commands),
+                    menu_next(commands),
+                );
+                let highlighted = MENU_ITEMS[*selection];
+                let cheat_entered = state.check_cheat_codes(commands);
+                state.hold_for_operator(commands, elapsed_ms);
+
+                // System buttons instantly choose + start
+                if issued(Command::StartTwoPlayer) {
+                    chosen = Some(MenuItem::Play(PlayerMode::Two));
+                } else if issued(Command::StartOnePlayer) {
+                    chosen = Some(MenuItem::Play(PlayerMode::Single));
+                } else if issued(Command::Confirm) && !cheat_entered {
+                    // A activates the currently highlighted option
+                    chosen = Some(highlighted);
+                }
+            }
+            GamePhase::Settings { .. } => state.handle_settings(commands),
+            GamePhase::Mutators { .. } => state.handle_mutators(commands),
+            GamePhase::TournamentSetup { .. } => state.handle_tournament_setup(commands),
+            GamePhase::Series => state.handle_series(commands),
+            GamePhase::Ready { .. } => state.handle_ready(commands),
+            GamePhase::Crashed(_) => state.handle_crashed(commands),
+            GamePhase::Splash { .. } => state.handle_splash(commands),
+            GamePhase::Credits { .. } => state.handle_credits(commands),
+            GamePhase::HowToPlay { .. } => chosen = state.handle_how_to_play(commands),
+            GamePhase::ResumePrompt { .. } => {
+                if let Err(err) = state.handle_resume_prompt(commands) {
+                    log(LogLevel::Warn, "resume", &err);
+                }
+            }
+            GamePhase::GameOver { tab } => {
+                if menu_prev(commands) {
+                    *tab = tab.previous();
+                } else if menu_next(commands) {
+                    *tab = tab.next();
+                }
+
+                if issued(Command::StartTwoPlayer) {
+                    start = Some(PlayerMode::Two);
+                } else if issued(Command::StartOnePlayer) {
+                    start = Some(PlayerMode::Single);
+                } else if issued(Command::Confirm) {
+                    let tab = *tab;
+                    state.open_leaderboard(tab);
+                }
+            }
+            GamePhase::Leaderboard { .. } => state.handle_leaderboard(commands),
+            GamePhase::Operator(_) => state.handle_operator(commands),
+            GamePhase::NewGamePlus { .. } => state.handle_new_game_plus(commands),
+            GamePhase::Playing | GamePhase::Loading { .. } => {}
+            GamePhase::NameEntry { .. } => state.handle_name_entry(commands),
+            #[cfg(feature = "editor")]
+            GamePhase::Editor(_) => {
+                if issued(Command::Confirm) {
+                    editor_commands.push(editor::EditorCommand::PlaceAtCursor);
+                }
+                if issued(Command::StartOnePlayer) {
+                    editor_commands.push(editor::EditorCommand::CycleObject);
+                }
+                if issued(Command::StartTwoPlayer) {
+                    editor_commands.push(editor::EditorCommand::TogglePreview);
+                }
+                state.handle_editor(commands, editor_commands);
+            }
+        }
+
+        if let Some(item) = chosen {
+            self.choose(item);
+        }
+        if let Some(mode) = start {
+            self.start_game(mode);
+        }
+    }
+
+    fn choose(&mut self, item: MenuItem) {
+        // The first game on a machine goes by way of how to play
+        if item.starts_game() && !self.state.seen_how_to_play {
+            self.state.open_how_to_play(Some(item));
+            return;
+        }
+        let state = &mut self.state;
+        match item {
+            MenuItem::Play(mode) => self.start_game(mode),
+            MenuItem::VersusBot => state.start_bot_game(),
+            MenuItem::Alternate => state.start_alternating(),
+            MenuItem::Tournament => state.open_tournament(),
+            MenuItem::Mutators => state.open_mutators(),
+            MenuItem::Settings => state.open_settings(),
+            MenuItem::HowToPlay => state.open_how_to_play(None),
+            MenuItem::Credits => state.open_credits(),
+            #[cfg(feature = "editor")]
+            MenuItem::Editor => state.open_editor(),
+        }
+    }
+
+    pub(crate) fn show_crash(&mut self, report: CrashReport) {
+        self.state.phase = GamePhase::Crashed(Box::new(report));
+    }
+
+    fn start_game(&mut self, mode: PlayerMode) {
+        // With a relay connected, two players means playing the other cabinet
+        #[cfg(feature = "netplay")]
+        if let (PlayerMode::Two, Some(netplay)) = (mode, &mut self.netplay) {
+            netplay.host(&mut self.state);
+            return;
+        }
+        match mode {
+            PlayerMode::Two => self.state.open_ready(),
+            PlayerMode::Single | PlayerMode::Team => self.state.start_new_game(mode),
+        }
+    }
+}
+
+type FrameCallback = Closure<dyn FnMut(f64)>;
+
+// Hands the App to requestAnimationFrame for good. The callback has to be
+// able to reschedule itself, which is the only reason it lives behind an
+// Rc<RefCell<..>>; the App itself is moved into it.
+pub(crate) fn run(mut app: App) {
+    let callback: Rc<RefCell<Option<FrameCallback>>> = Rc::new(RefCell::new(None));
+    let reschedule = callback.clone();
+    *callback.borrow_mut() = Some(Closure::new(move |now_ms: f64| {
+        app.frame(now_ms);
+        request_animation_frame(reschedule.borrow().as_ref().unwrap());
+    }));
+    request_animation_frame(callback.borrow().as_ref().unwrap());
+}
+
+fn request_animation_frame(f: &FrameCallback) {
+    web_sys::window()
+        .unwrap()
+        .request_animation_frame(f.as_ref().unchecked_ref())
+        .unwrap();
+}
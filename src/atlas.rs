@@ -0,0 +1,107 @@
+// Sprite atlas: object skins and their animation frames packed into one
+// image, with a JSON map of where each frame sits in it, so a whole set of
+// skins is two fetches and one image decode. The page config points at both:
+//
+//   "atlas": { "image": "skins.png", "frames": "skins.json" }
+//
+// The frame map is the usual packer "hash" format, {"frames": {"name":
+// {"frame": {"x", "y", "w", "h"}}}}, and an object's sprite names the frames
+// it uses. Objects keep drawing as their shapes until the atlas has loaded,
+// and for good if it doesn't.
+
+use super::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{HtmlImageElement, Response};
+
+const ANIMATION_FRAME_TICKS: u32 = 8; // Frames each animation frame shows for
+
+#[derive(Clone, Deserialize)]
+pub(crate) struct AtlasSource {
+    image: String,
+    frames: String,
+}
+
+#[derive(Copy, Clone, Deserialize)]
+struct AtlasFrame {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+#[derive(Deserialize)]
+struct FrameEntry {
+    frame: AtlasFrame,
+}
+
+#[derive(Deserialize)]
+struct FrameMap {
+    frames: HashMap<String, FrameEntry>,
+}
+
+pub(crate) struct SpriteAtlas {
+    image: HtmlImageElement,
+    frames: HashMap<String, AtlasFrame>,
+}
+
+impl SpriteAtlas {
+    pub(crate) async fn load(source: &AtlasSource) -> Result<Self, JsValue> {
+        let window = web_sys::window().unwrap();
+        let response: Response = JsFuture::from(window.fetch_with_str(&source.frames))
+            .await?
+            .dyn_into()?;
+        if !response.ok() {
+            return Err(JsValue::from_str(&format!(
+                "{} returned {}",
+                source.frames,
+                response.status()
+            )));
+        }
+        let text = JsFuture::from(response.text()?).await?;
+        let map: FrameMap = serde_json::from_str(&text.as_string().unwrap_or_default())
+            .map_err(|err| JsValue::from_str(&format!("{}: {err}", source.frames)))?;
+
+        let image = HtmlImageElement::new()?;
+        image.set_src(&source.image);
+        JsFuture::from(image.decode()).await?;
+
+        Ok(SpriteAtlas {
+            image,
+            frames: map
+                .frames
+                .into_iter()
+                .map(|(name, entry)| (name, entry.frame))
+                .collect(),
+        })
+    }
+
+    // Draws the sprite's frame for this point in its animation, if it has one
+    // the atlas knows about
+    pub(crate) fn draw(
+        &self,
+        ctx: &CanvasRenderingContext2d,
+        draw: &SpriteDraw,
+        ticks: u32,
+    ) -> bool {
+        let names = &draw.sprite.frames;
+        if names.is_empty() {
+            return false;
+        }
+        let name = &names[(ticks / ANIMATION_FRAME_TICKS) as usize % names.len()];
+        let Some(frame) = self.frames.get(name) else {
+            return false;
+        };
+        ctx.draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+            &self.image,
+            frame.x,
+            frame.y,
+            frame.w,
+            frame.h,
+            draw.x,
+            draw.y,
+            draw.w,
+            draw.h,
+        )
+        .is_ok()
+    }
+}
@@ -0,0 +1,88 @@
+// Behind the main menu: deals drifting down the screen, faded, so the menu
+// doesn't sit there dead between demos. They're drawn with the same sprites
+// as in a game but are only scenery; nothing collides, scores or touches the
+// run's rng, and there are never more than a handful of them.
+
+use super::*;
+
+const MAX_TAGS: usize = 12;
+const SPAWN_CHANCE: f64 = 0.04; // A frame, while there's room for another
+const BACKDROP_ALPHA: f64 = 0.25;
+
+struct BackdropTag {
+    x: f64,
+    y: f64,
+    speed: f64, // Logical units a frame
+    sway: f64,  // Phase of its side-to-side drift
+}
+
+#[derive(Default)]
+pub(crate) struct MenuBackdrop {
+    tags: Vec<BackdropTag>,
+    started: bool,
+}
+
+impl MenuBackdrop {
+    fn spawn(&mut self, world: WorldConfig, y: f64) {
+        let mut rng = rand::thread_rng();
+        self.tags.push(BackdropTag {
+            x: rng.gen_range(0.0..world.width - OBJECT_WIDTH),
+            y,
+            speed: rng.gen_range(0.3..0.8),
+            sway: rng.gen_range(0.0..std::f64::consts::TAU),
+        });
+    }
+
+    fn update(&mut self, world: WorldConfig) {
+        // Opens with the screen half full rather than everything starting
+        // at the top at once
+        if !self.started {
+            self.started = true;
+            for i in 0..MAX_TAGS / 2 {
+                let y = world.height * i as f64 / (MAX_TAGS / 2) as f64;
+                self.spawn(world, y);
+            }
+        }
+
+        for tag in &mut self.tags {
+            tag.y += tag.speed;
+            tag.sway += 0.03;
+            tag.x += tag.sway.sin() * 0.2;
+        }
+        self.tags.retain(|tag| tag.y < world.height);
+        if self.tags.len() < MAX_TAGS && rand::thread_rng().gen_bool(SPAWN_CHANCE) {
+            self.spawn(world, -OBJECT_HEIGHT);
+        }
+    }
+
+    // Drawn as whichever object the catalog has for a deal
+    pub(crate) fn draw(&self, ctx: &dyn Renderer, objects: &ObjectCatalog) {
+        let Some(kind) = objects.first_of(ObjectType::GoodDeal) else {
+            return;
+        };
+        let def = objects.get(kind);
+        let sprites: Vec<SpriteDraw> = self
+            .tags
+            .iter()
+            .map(|tag| SpriteDraw {
+                sprite: &def.sprite,
+                x: tag.x,
+                y: tag.y,
+                w: def.width,
+                h: def.height,
+            })
+            .collect();
+        ctx.set_alpha(BACKDROP_ALPHA);
+        ctx.sprites(&sprites);
+        ctx.set_alpha(1.0);
+    }
+}
+
+impl GameState {
+    // Called once a frame
+    pub(crate) fn update_menu_backdrop(&mut self) {
+        if let GamePhase::ModeSelect { backdrop, .. } = &mut self.phase {
+            backdrop.update(self.world);
+        }
+    }
+}
@@ -0,0 +1,303 @@
+// What each kind of object does when it spawns, moves, is caught or lands.
+// Behaviors report world changes back as WorldEffects rather than touching
+// GameState directly.
+
+use super::*;
+
+const PERFECT_CATCH_WINDOW: f64 = 3.0; // How far off center a catch can be and still count as perfect
+
+// Side effects an object behavior asks the game to carry out once the
+// current pass over objects and players is finished.
+pub(crate) enum WorldEffect {
+    Spill {
+        center_x: f64,
+    },
+    Popup {
+        x: f64,
+        y: f64,
+        text: String,
+        color: &'static str,
+    },
+    Announce(String),
+    ScoreAllPlayers(i32), // Added to every living player, never dropping below zero
+    Event(GameEvent),     // Passed on to the tick's event queue
+}
+
+// Rule switches behaviors need to consult, copied out of GameState.
+#[derive(Copy, Clone)]
+pub(crate) struct RuleFlags {
+    pub(crate) bonus_round: bool,
+    pub(crate) no_combo_reset: bool,
+    pub(crate) dodge_mode: bool,
+}
+
+pub(crate) struct BehaviorContext<'a> {
+    pub(crate) rules: &'a ScoringRules,
+    pub(crate) flags: RuleFlags,
+    pub(crate) effects: Vec<WorldEffect>,
+}
+
+// Hooks that define what a kind of falling object does. Every ObjectType has
+// one registered in the BehaviorRegistry, and update/check_collisions/draw
+// only reach objects through these.
+pub(crate) trait ObjectBehavior {
+    // The object has just been released into play
+    fn on_spawn(&self, _obj: &mut FallingObject) {}
+
+    // Every frame, before the object moves
+    fn on_tick(&self, _obj: &mut FallingObject) {}
+
+    // A living player touched the object; it's removed afterwards. By
+    // default the catcher gets the definition's effect, if any, unless it's a
+    // bonus round. Behaviors that catch differently apply it themselves.
+    fn on_catch(
+        &self,
+        _obj: &FallingObject,
+        def: &ObjectDef,
+        player: &mut PlayerSlot,
+        game: &mut BehaviorContext,
+    ) {
+        if !game.flags.bonus_round {
+            apply_catch_effect(def, player);
+        }
+    }
+
+    // The object's bottom reached the floor line. It can't be caught from
+    // here on, so this is its final outcome. Return true to remove it there.
+    fn on_floor(&self, _obj: &FallingObject, _game: &mut BehaviorContext) -> bool {
+        false
+    }
+
+    // Queues what the object looks like; the whole queue is drawn together
+    fn draw<'a>(&self, sprites: &mut Vec<SpriteDraw<'a>>, obj: &FallingObject, def: &'a ObjectDef) {
+        sprites.push(SpriteDraw::of(obj, &def.sprite));
+    }
+}
+
+// Gives the catcher the definition's effect, if it has one
+fn apply_catch_effect(def: &ObjectDef, player: &mut PlayerSlot) {
+    if let Some(effect) = def.effect {
+        player.apply_effect(effect.kind, effect.duration);
+    }
+}
+
+struct GoodDealBehavior;
+
+impl ObjectBehavior for GoodDealBehavior {
+    fn on_catch(
+        &self,
+        obj: &FallingObject,
+        def: &ObjectDef,
+        player: &mut PlayerSlot,
+        game: &mut BehaviorContext,
+    ) {
+        let rules = game.rules;
+        player.combo += 1;
+        let multiplier = rules.combo_multiplier(player.combo);
+        let points = def.score.unwrap_or(rules.deal_points) * multiplier;
+        player.earn(points);
+        let text = if multiplier > 1 {
+            format!("+{points} x{multiplier}")
+        } else {
+            format!("+{points}")
+        };
+        game.effects.push(WorldEffect::Popup {
+            x: obj.x + obj.width / 2.0,
+            y: obj.y,
+            text,
+            color: "#7fff7f",
+        });
+        if rules.combo_breakpoints.contains(&player.combo) {
+            game.effects.push(WorldEffect::Announce(format!(
+                "P{} combo x{}!",
+                player.player_index + 1,
+                player.combo
+            )));
+        }
+        if game.flags.bonus_round {
+            player.bonus_catches += 1;
+        }
+        // A long enough run of clean catches wins back a heart
+        player.streak += 1;
+        if player.streak.is_multiple_of(rules.heal_streak) && player.health < player.max_health {
+            player.health += 1;
+            game.effects.push(WorldEffect::Announce(format!(
+                "P{} {} clean catches - +1 heart!",
+                player.player_index + 1,
+                player.streak
+            )));
+        }
+        // Dead center, give or take a few pixels
+        let center = player.player.x + player.player.width / 2.0;
+        let perfect = (obj.x + obj.width / 2.0 - center).abs() <= PERFECT_CATCH_WINDOW;
+        if perfect {
+            player.earn(rules.perfect_bonus);
+            game.effects.push(WorldEffect::Popup {
+                x: obj.x + obj.width / 2.0,
+                y: obj.y - 10.0,
+                text: format!("PERFECT +{}", rules.perfect_bonus),
+                color: "#ffd700",
+            });
+        }
+        game.effects.push(WorldEffect::Event(GameEvent::Caught {
+            player_index: player.player_index,
+            x: obj.x + obj.width / 2.0,
+            y: obj.y + obj.height / 2.0,
+            perfect,
+        }));
+        apply_catch_effect(def, player);
+    }
+
+    // Deals that hit the floor uncaught may cost points
+    fn on_floor(&self, obj: &FallingObject, game: &mut BehaviorContext) -> bool {
+        game.effects.push(WorldEffect::Event(GameEvent::DealMissed {
+            x: obj.x + obj.width / 2.0,
+        }));
+        if game.rules.miss_penalty != 0 {
+            game.effects
+                .push(WorldEffect::ScoreAllPlayers(-game.rules.miss_penalty));
+        }
+        false
+    }
+}
+
+struct BadItemBehavior;
+
+impl ObjectBehavior for BadItemBehavior {
+    fn on_catch(
+        &self,
+        obj: &FallingObject,
+        def: &ObjectDef,
+        player: &mut PlayerSlot,
+        game: &mut BehaviorContext,
+    ) {
+        // Stragglers from before a bonus round can't hurt you
+        if game.flags.bonus_round {
+            return;
+        }
+        if player.has_effect(EffectKind::Shield) {
+            player.remove_effect(EffectKind::Shield);
+            apply_catch_effect(def, player);
+            return;
+        }
+        if !game.flags.no_combo_reset {
+            player.combo = 0;
+        }
+        player.streak = 0;
+        player.health -= game.rules.hazard_damage;
+        if player.health < 0 {
+            player.health = 0;
+        }
+        game.effects.push(WorldEffect::Event(GameEvent::Hit {
+            player_index: player.player_index,
+            kind: obj.kind,
+            x: obj.x + obj.width / 2.0,
+            y: obj.y + obj.height / 2.0,
+        }));
+        apply_catch_effect(def, player);
+    }
+
+    // In dodge mode every hazard that makes it past everyone is worth a deal
+    fn on_floor(&self, obj: &FallingObject, game: &mut BehaviorContext) -> bool {
+        game.effects
+            .push(WorldEffect::Event(GameEvent::HazardAvoided));
+        if let Some(player_index) = obj.grazed_by {
+            game.effects.push(WorldEffect::Event(GameEvent::NearMiss {
+                player_index,
+                x: obj.x + obj.width / 2.0,
+            }));
+        }
+        if game.flags.dodge_mode {
+            game.effects
+                .push(WorldEffect::ScoreAllPlayers(game.rules.deal_points));
+        }
+        false
+    }
+}
+
+struct EnergyDrinkBehavior;
+
+impl ObjectBehavior for EnergyDrinkBehavior {
+    fn on_catch(
+        &self,
+        _obj: &FallingObject,
+        def: &ObjectDef,
+        player: &mut PlayerSlot,
+        game: &mut BehaviorContext,
+    ) {
+        player.earn(def.score.unwrap_or(game.rules.power_up_points));
+        apply_catch_effect(def, player);
+    }
+}
+
+// Reverses controls through its definition's effect, which the default catch
+// skips in a bonus round; otherwise it's plain
+struct PrankFlyerBehavior;
+
+impl ObjectBehavior for PrankFlyerBehavior {}
+
+struct SpilledSodaBehavior;
+
+impl ObjectBehavior for SpilledSodaBehavior {
+    // Bursts on the player and soaks the floor under them
+    fn on_catch(
+        &self,
+        obj: &FallingObject,
+        def: &ObjectDef,
+        player: &mut PlayerSlot,
+        game: &mut BehaviorContext,
+    ) {
+        // Like hazards, stragglers from before a bonus round are harmless
+        if game.flags.bonus_round {
+            return;
+        }
+        game.effects.push(WorldEffect::Spill {
+            center_x: obj.x + obj.width / 2.0,
+        });
+        apply_catch_effect(def, player);
+    }
+
+    // Bursts when it reaches the floor and leaves a sticky patch behind
+    fn on_floor(&self, obj: &FallingObject, game: &mut BehaviorContext) -> bool {
+        if !game.flags.bonus_round {
+            game.effects.push(WorldEffect::Spill {
+                center_x: obj.x + obj.width / 2.0,
+            });
+        }
+        true
+    }
+}
+
+// Maps each object type to its behavior. Forks can add a new ObjectType and
+// register a behavior for it without touching the game loop.
+pub(crate) struct BehaviorRegistry {
+    behaviors: Vec<(ObjectType, Box<dyn ObjectBehavior>)>,
+}
+
+impl BehaviorRegistry {
+    pub(crate) fn with_builtins() -> Self {
+        let mut registry = BehaviorRegistry {
+            behaviors: Vec::new(),
+        };
+        registry.register(ObjectType::GoodDeal, Box::new(GoodDealBehavior));
+        registry.register(ObjectType::BadItem, Box::new(BadItemBehavior));
+        registry.register(ObjectType::EnergyDrink, Box::new(EnergyDrinkBehavior));
+        registry.register(ObjectType::PrankFlyer, Box::new(PrankFlyerBehavior));
+        registry.register(ObjectType::SpilledSoda, Box::new(SpilledSodaBehavior));
+        registry
+    }
+
+    // Registering a type again replaces its behavior
+    fn register(&mut self, obj_type: ObjectType, behavior: Box<dyn ObjectBehavior>) {
+        self.behaviors.retain(|(existing, _)| *existing != obj_type);
+        self.behaviors.push((obj_type, behavior));
+    }
+
+    pub(crate) fn get(&self, obj_type: ObjectType) -> &dyn ObjectBehavior {
+        self.behaviors
+            .iter()
+            .find(|(existing, _)| *existing == obj_type)
+            .map(|(_, behavior)| behavior.as_ref())
+            .expect("every object type has a registered behavior")
+    }
+}
@@ -0,0 +1,8 @@
+// Native entry point for the headless balancing simulator in src/simulate.rs.
+
+fn main() {
+    if let Err(err) = black_friday::simulate::run(std::env::args().skip(1)) {
+        eprintln!("simulate: {err}");
+        std::process::exit(2);
+    }
+}
@@ -0,0 +1,83 @@
+// The computer opponent for solo versus: an AiController playing P2. The
+// d-pad it holds goes through the same bindings as a person's, so runs
+// against it replay and record like any other two player game.
+//
+// The same controller plays P1 in the attract mode demo, which starts by
+// itself once the menu has sat untouched for a while and ends the moment
+// anyone presses anything.
+
+use super::*;
+
+const ATTRACT_AFTER_FRAMES: u32 = 30 * 60; // Half a minute of an idle menu
+
+impl GameState {
+    // Solo versus: P1 against the bot as P2
+    pub(crate) fn start_bot_game(&mut self) {
+        self.open_ready();
+        let bot = AiController::new(1, self.bot_level);
+        self.handicaps[bot.player_index] = Handicap::None;
+        if let GamePhase::Ready { ready } = &mut self.phase {
+            ready[bot.player_index] = true;
+        }
+        self.bot = Some(bot);
+    }
+
+    pub(crate) fn is_bot(&self, player_index: usize) -> bool {
+        self.bot
+            .as_ref()
+            .is_some_and(|bot| bot.player_index == player_index)
+    }
+
+    // A tick's commands with the bot's player driven by the bot rather than
+    // whoever is holding that d-pad
+    pub(crate) fn with_bot(&mut self, commands: &[Command]) -> Vec<Command> {
+        let Some(mut bot) = self.bot.take() else {
+            return commands.to_vec();
+        };
+        // Decisions left over from the last run don't carry into the next
+        if !matches!(self.phase, GamePhase::Playing) {
+            bot.reset();
+        }
+        let mut commands: Vec<Command> = commands
+            .iter()
+            .copied()
+            .filter(|command| {
+                !matches!(
+                    command,
+                    Command::MoveLeft(i) | Command::MoveRight(i) | Command::Throw(i)
+                        if *i == bot.player_index
+                )
+            })
+            .collect();
+        if matches!(self.phase, GamePhase::Playing) && bot.player_index < self.mode.player_count() {
+            bot.observe(self);
+            commands.extend(held_commands(&bot.poll()));
+        }
+        self.bot = Some(bot);
+        commands
+    }
+
+    // Counts idle menu frames and starts the demo once there are enough
+    pub(crate) fn tick_attract(&mut self, commands: &[Command]) {
+        let GamePhase::ModeSelect { idle_frames, .. } = &mut self.phase else {
+            return;
+        };
+        if !commands.is_empty() {
+            *idle_frames = 0;
+            return;
+        }
+        *idle_frames += 1;
+        if *idle_frames >= ATTRACT_AFTER_FRAMES {
+            self.start_demo();
+        }
+    }
+
+    fn start_demo(&mut self) {
+        self.start_new_game(PlayerMode::Single);
+        self.bot = Some(AiController::new(0, BotLevel::Medium));
+    }
+
+    pub(crate) fn in_demo(&self) -> bool {
+        self.is_bot(0)
+    }
+}
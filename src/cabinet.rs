@@ -0,0 +1,247 @@
+// Shares high scores with the rest of the arcade, built with
+// `--features cabinet-sync`. Loading the page with
+// `?cabinet=https://cabinet.local/api` posts every named score to
+// `<endpoint>/scores` and reads the cross-game board back from
+// `<endpoint>/champions`. `?cabinet=shell` does the same over postMessage
+// with the RCade shell this page is framed in. Either way the board shows up
+// as the CABINET CHAMPIONS tab on the game over screen, and the service is
+// free to put our scores in the arcade's attract rotation. Builds with a
+// signing key attach a signature to every score (see signing.rs), and every
+// score carries the run's replay so the service can play it back.
+
+use super::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, MessageEvent, Request, RequestInit, Response, UrlSearchParams, Window};
+
+// How this game is named on the shared board
+pub(crate) const GAME_ID: &str = "black-friday";
+
+// One line of the cross-game board, best first as the service sends them
+#[derive(Clone, Deserialize)]
+pub(crate) struct CabinetChampion {
+    pub(crate) game: String,
+    pub(crate) name: String,
+    pub(crate) score: i64,
+}
+
+// The game's side of the board: what the service last sent, and scores named
+// since the last frame that haven't gone out yet.
+#[derive(Default)]
+pub(crate) struct CabinetBoard {
+    pub(crate) champions: Vec<CabinetChampion>,
+    pub(crate) unpublished: Vec<PendingScore>,
+}
+
+pub(crate) struct PendingScore {
+    pub(crate) entry: LeaderboardEntry,
+    pub(crate) seed: u64,       // Of the run it was scored in
+    pub(crate) input_hash: u64, // The whole run's, which decides every player's score
+    pub(crate) replay: ReplayBundle,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishedScore<'a> {
+    game: &'static str,
+    name: &'a str,
+    score: i32,
+    players: usize,
+    time_survived_ms: f64,
+    mutated: bool,
+    seed: String, // Decimal strings, as a JS number can't hold every u64
+    input_hash: String,
+    timestamp_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    replay: &'a ReplayBundle,
+}
+
+// What goes up to the shell
+#[derive(Serialize)]
+#[serde(tag = "type")]
+enum ShellRequest<'a> {
+    #[serde(rename = "rcade:score")]
+    Score(PublishedScore<'a>),
+    #[serde(rename = "rcade:champions-request")]
+    Champions,
+}
+
+// What comes back; anything else the shell posts is ignored
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum ShellReply {
+    #[serde(rename = "rcade:champions")]
+    Champions { champions: Vec<CabinetChampion> },
+}
+
+enum Service {
+    Endpoint(String), // Without a trailing slash
+    Shell(Window),    // The frame hosting this page
+}
+
+pub(crate) struct CabinetSync {
+    service: Service,
+    inbox: Rc<RefCell<Vec<AppMessage>>>,
+}
+
+impl CabinetSync {
+    // Picks the service named in the page URL, if there is one, and asks it
+    // for the current board. Boards that arrive are posted to `inbox`.
+    pub(crate) fn from_page(inbox: &Rc<RefCell<Vec<AppMessage>>>) -> Result<Option<Self>, JsValue> {
+        let window = web_sys::window().unwrap();
+        let search = window.location().search()?;
+        let Some(target) = UrlSearchParams::new_with_str(&search)?.get("cabinet") else {
+            return Ok(None);
+        };
+
+        let service = if target == "shell" {
+            let Some(shell) = window.parent()? else {
+                return Ok(None);
+            };
+            let replies = inbox.clone();
+            let from = JsValue::from(shell.clone());
+            let onmessage = Closure::wrap(Box::new(move |event: MessageEvent| {
+                if event.source().map(JsValue::from).as_ref() != Some(&from) {
+                    return;
+                }
+                let Ok(text) = js_sys::JSON::stringify(&event.data()) else {
+                    return;
+                };
+                if let Ok(ShellReply::Champions { champions }) =
+                    serde_json::from_str(&String::from(text))
+                {
+                    replies
+                        .borrow_mut()
+                        .push(AppMessage::CabinetChampions(champions));
+                }
+            }) as Box<dyn FnMut(MessageEvent)>);
+            window
+                .add_event_listener_with_callback("message", onmessage.as_ref().unchecked_ref())?;
+            onmessage.forget();
+            Service::Shell(shell)
+        } else {
+            Service::Endpoint(target.trim_end_matches('/').to_string())
+        };
+
+        let sync = CabinetSync {
+            service,
+            inbox: inbox.clone(),
+        };
+        sync.publish(&[]);
+        Ok(Some(sync))
+    }
+
+    // Sends off whatever was named since the last frame
+    pub(crate) fn publish_pending(&self, state: &mut GameState) {
+        let unpublished = std::mem::take(&mut state.cabinet.unpublished);
+        if !unpublished.is_empty() {
+            self.publish(&unpublished);
+        }
+    }
+
+    // Posts the scores, then asks for the board again so it includes them
+    fn publish(&self, scores: &[PendingScore]) {
+        let timestamp_ms = js_sys::Date::now() as u64;
+        let bodies: Vec<PublishedScore> = scores
+            .iter()
+            .map(|pending| {
+                let entry = &pending.entry;
+                let claim = signing::ScoreClaim {
+                    score: entry.score,
+                    seed: pending.seed,
+                    input_hash: pending.input_hash,
+                    timestamp_ms,
+                };
+                PublishedScore {
+                    game: GAME_ID,
+                    name: &entry.name,
+                    score: entry.score,
+                    players: entry.mode.player_count(),
+                    time_survived_ms: entry.time_survived_ms,
+                    mutated: entry.mutated,
+                    seed: pending.seed.to_string(),
+                    input_hash: pending.input_hash.to_string(),
+                    timestamp_ms,
+                    signature: signing::build_key().map(|key| signing::sign(&claim, key)),
+                    replay: &pending.replay,
+                }
+            })
+            .collect();
+
+        match &self.service {
+            Service::Shell(shell) => {
+                let requests = bodies
+                    .into_iter()
+                    .map(ShellRequest::Score)
+                    .chain([ShellRequest::Champions]);
+                for request in requests {
+                    let message = serde_json::to_string(&request)
+                        .map_err(|err| JsValue::from_str(&err.to_string()))
+                        .and_then(|text| js_sys::JSON::parse(&text))
+                        .and_then(|message| shell.post_message(&message, "*"));
+                    if let Err(err) = message {
+                        warn_cabinet(&err);
+                    }
+                }
+            }
+            Service::Endpoint(endpoint) => {
+                let endpoint = endpoint.clone();
+                let scores: Vec<String> = bodies
+                    .iter()
+                    .filter_map(|body| serde_json::to_string(body).ok())
+                    .collect();
+                let inbox = self.inbox.clone();
+                spawn_local(async move {
+                    let scores_url = format!("{endpoint}/scores");
+                    for score in &scores {
+                        if let Err(err) = fetch_text(&scores_url, Some(score)).await {
+                            warn_cabinet(&err);
+                        }
+                    }
+                    let champions = fetch_text(&format!("{endpoint}/champions"), None)
+                        .await
+                        .and_then(|text| {
+                            serde_json::from_str(&text)
+                                .map_err(|err| JsValue::from_str(&err.to_string()))
+                        });
+                    match champions {
+                        Ok(champions) => inbox
+                            .borrow_mut()
+                            .push(AppMessage::CabinetChampions(champions)),
+                        Err(err) => warn_cabinet(&err),
+                    }
+                });
+            }
+        }
+    }
+}
+
+// A GET, or a POST of `body` as JSON. Resolves to the response text.
+async fn fetch_text(url: &str, body: Option<&str>) -> Result<String, JsValue> {
+    let init = RequestInit::new();
+    if let Some(body) = body {
+        init.set_method("POST");
+        init.set_body(&JsValue::from_str(body));
+        let headers = Headers::new()?;
+        headers.set("Content-Type", "application/json")?;
+        init.set_headers(&headers);
+    }
+    let request = Request::new_with_str_and_init(url, &init)?;
+    let response: Response =
+        JsFuture::from(web_sys::window().unwrap().fetch_with_request(&request))
+            .await?
+            .dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "{url}: HTTP {}",
+            response.status()
+        )));
+    }
+    let text = JsFuture::from(response.text()?).await?;
+    Ok(text.as_string().unwrap_or_default())
+}
+
+// The game carries on without the board; the console says why
+fn warn_cabinet(err: &JsValue) {
+    log(LogLevel::Warn, "cabinet", &format!("{err:?}"));
+}
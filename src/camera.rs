@@ -0,0 +1,131 @@
+// A simple camera over the playfield: an offset and a zoom that the renderer
+// applies on top of the view transform, so the simulation never sees it.
+//
+// For now all it does is zoom-punch: a quick push in toward something worth
+// noticing that eases back out. Big catches get a small one, the doorbuster
+// opening a bigger one and the final death the biggest and slowest. With
+// reduce motion on none of them happen and the camera stays put.
+
+use super::*;
+
+#[derive(Copy, Clone)]
+pub(crate) struct ZoomPunch {
+    strength: f64, // Extra zoom at the peak; 0.1 is 10% closer
+    frames: u32,   // How long it lasts, in and back out
+}
+
+pub(crate) const CATCH_PUNCH: ZoomPunch = ZoomPunch {
+    strength: 0.04,
+    frames: 12,
+};
+pub(crate) const DOORBUSTER_PUNCH: ZoomPunch = ZoomPunch {
+    strength: 0.08,
+    frames: 30,
+};
+pub(crate) const DEATH_PUNCH: ZoomPunch = ZoomPunch {
+    strength: 0.15,
+    frames: 60,
+};
+
+const PUNCH_IN: f64 = 0.2; // Share of a punch spent pushing in; the rest eases out
+
+#[derive(Copy, Clone)]
+struct ActivePunch {
+    punch: ZoomPunch,
+    x: f64, // What it pushes in toward, in logical units
+    y: f64,
+    age: u32,
+}
+
+impl ActivePunch {
+    // How far into its zoom the punch is right now, 0 to 1
+    fn amount(&self) -> f64 {
+        let t = self.age as f64 / self.punch.frames as f64;
+        if t < PUNCH_IN {
+            t / PUNCH_IN
+        } else {
+            let out = (t - PUNCH_IN) / (1.0 - PUNCH_IN);
+            (1.0 - out).powi(2)
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct Camera {
+    pub(crate) offset_x: f64,
+    pub(crate) offset_y: f64,
+    pub(crate) zoom: f64,
+    focus: (f64, f64), // The point that stays put as the zoom changes
+    punch: Option<ActivePunch>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            offset_x: 0.0,
+            offset_y: 0.0,
+            zoom: 1.0,
+            focus: (0.0, 0.0),
+            punch: None,
+        }
+    }
+}
+
+impl Camera {
+    // Starts a punch toward (x, y), unless a stronger one is still going
+    fn punch(&mut self, punch: ZoomPunch, x: f64, y: f64) {
+        if self
+            .punch
+            .is_some_and(|active| active.punch.strength * active.amount() > punch.strength)
+        {
+            return;
+        }
+        self.punch = Some(ActivePunch {
+            punch,
+            x,
+            y,
+            age: 0,
+        });
+    }
+
+    fn update(&mut self) {
+        self.zoom = 1.0;
+        let Some(active) = &mut self.punch else {
+            return;
+        };
+        active.age += 1;
+        if active.age >= active.punch.frames {
+            self.punch = None;
+            return;
+        }
+        self.zoom = 1.0 + active.punch.strength * active.amount();
+        self.focus = (active.x, active.y);
+    }
+
+    // Where the renderer moves and scales logical units to: a point p is
+    // drawn at p * scale + (dx, dy)
+    pub(crate) fn transform(&self) -> (f64, f64, f64) {
+        let (fx, fy) = self.focus;
+        (
+            self.offset_x + fx * (1.0 - self.zoom),
+            self.offset_y + fy * (1.0 - self.zoom),
+            self.zoom,
+        )
+    }
+}
+
+impl GameState {
+    // Zooming in on a point inside the playfield never shows past its edges
+    pub(crate) fn zoom_punch(&mut self, punch: ZoomPunch, x: f64, y: f64) {
+        if self.settings.reduce_motion {
+            return;
+        }
+        let world = self.world;
+        self.camera
+            .punch(punch, x.clamp(0.0, world.width), y.clamp(0.0, world.height));
+    }
+
+    pub(crate) fn update_camera(&mut self) {
+        self.camera.update();
+    }
+}
@@ -0,0 +1,45 @@
+// Clutch moments: when a hit leaves a player one more away from going out,
+// the game drops to half speed for a few seconds with the playfield's color
+// drained out, then eases back. In 1P that's the run about to end.
+//
+// The slowdown goes through the same time scale as the debug speed control,
+// so it only changes how fast ticks come, never what a tick does; replays and
+// netplay peers see exactly the same run.
+
+use super::*;
+
+const CLUTCH_TICKS: u32 = 90; // Three seconds at half speed
+const CLUTCH_TIME_SCALE: f64 = 0.5;
+const CLUTCH_SATURATION: f64 = 0.25;
+const CLUTCH_FADE_TICKS: u32 = 20; // Color and speed come back over the last of it
+
+impl GameState {
+    pub(crate) fn one_hit_from_out(&self, health: i32) -> bool {
+        health > 0 && health <= self.config.scoring.hazard_damage
+    }
+
+    pub(crate) fn start_clutch(&mut self) {
+        self.clutch_remaining = CLUTCH_TICKS;
+    }
+
+    pub(crate) fn update_clutch(&mut self) {
+        self.clutch_remaining = self.clutch_remaining.saturating_sub(1);
+    }
+
+    // 1 for the whole clutch moment, falling to 0 as it fades out
+    fn clutch_amount(&self) -> f64 {
+        if !matches!(self.phase, GamePhase::Playing) {
+            return 0.0;
+        }
+        (self.clutch_remaining as f64 / CLUTCH_FADE_TICKS as f64).min(1.0)
+    }
+
+    // How fast the simulation should run against real time
+    pub(crate) fn time_scale(&self) -> f64 {
+        1.0 - (1.0 - CLUTCH_TIME_SCALE) * self.clutch_amount()
+    }
+
+    pub(crate) fn clutch_saturation(&self) -> f64 {
+        1.0 - (1.0 - CLUTCH_SATURATION) * self.clutch_amount()
+    }
+}
@@ -0,0 +1,207 @@
+// What the player means rather than which button they're holding. Each phase
+// has its own binding table that turns the frame's InputSnapshot into
+// Commands, so menus, name entry, the editor and gameplay never look at
+// individual buttons and rebinding only means editing a table here.
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Command {
+    MoveLeft(usize), // By player index
+    MoveRight(usize),
+    CursorUp,
+    CursorDown,
+    CursorLeft,
+    CursorRight,
+    Confirm,
+    Ready(usize),   // Each player's A on the ready screen
+    StartOnePlayer, // The cabinet's system buttons
+    StartTwoPlayer,
+    ServiceHeld,  // 1P and 2P together, for the operator menu
+    Throw(usize), // Each player's A in play, for throwing a kept hazard in versus
+}
+
+type Binding = (fn(&InputSnapshot) -> bool, Command);
+
+// Either d-pad drives the menus. P1's up and down stay separate from left and
+// right because cheat codes tell them apart.
+const MENU_BINDINGS: &[Binding] = &[
+    (|i| i.player1_up, Command::CursorUp),
+    (|i| i.player1_down, Command::CursorDown),
+    (|i| i.player1_left || i.player2_left, Command::CursorLeft),
+    (|i| i.player1_right || i.player2_right, Command::CursorRight),
+    (|i| i.player1_a || i.player2_a, Command::Confirm),
+];
+
+const SYSTEM_BINDINGS: &[Binding] = &[
+    (|i| i.system_one_player, Command::StartOnePlayer),
+    (|i| i.system_two_player, Command::StartTwoPlayer),
+];
+
+const SERVICE_BINDINGS: &[Binding] = &[(
+    |i| i.system_one_player && i.system_two_player,
+    Command::ServiceHeld,
+)];
+
+// Whoever finished first types first, on P1's controls
+const NAME_ENTRY_BINDINGS: &[Binding] = &[
+    (|i| i.player1_up, Command::CursorUp),
+    (|i| i.player1_down, Command::CursorDown),
+    (|i| i.player1_left, Command::CursorLeft),
+    (|i| i.player1_right, Command::CursorRight),
+    (|i| i.player1_a, Command::Confirm),
+];
+
+// Each player picks a handicap with their own d-pad; P1's up and down set
+// the bot's level when playing against it
+const READY_BINDINGS: &[Binding] = &[
+    (|i| i.player1_up, Command::CursorUp),
+    (|i| i.player1_down, Command::CursorDown),
+    (|i| i.player1_left, Command::MoveLeft(0)),
+    (|i| i.player1_right, Command::MoveRight(0)),
+    (|i| i.player2_left, Command::MoveLeft(1)),
+    (|i| i.player2_right, Command::MoveRight(1)),
+    (|i| i.player1_a, Command::Ready(0)),
+    (|i| i.player2_a, Command::Ready(1)),
+];
+
+// Held every tick rather than pressed
+const PLAY_BINDINGS: &[Binding] = &[
+    (|i| i.player1_left, Command::MoveLeft(0)),
+    (|i| i.player1_right, Command::MoveRight(0)),
+    (|i| i.player2_left, Command::MoveLeft(1)),
+    (|i| i.player2_right, Command::MoveRight(1)),
+    (|i| i.player1_a, Command::Throw(0)),
+    (|i| i.player2_a, Command::Throw(1)),
+];
+
+// The editor cursor keeps moving while a direction is held. P2's d-pad scrubs
+// time as well as P1's up and down.
+#[cfg(feature = "editor")]
+const EDITOR_HELD_BINDINGS: &[Binding] = &[
+    (|i| i.player1_left, Command::CursorLeft),
+    (|i| i.player1_right, Command::CursorRight),
+    (|i| i.player1_up || i.player2_left, Command::CursorUp),
+    (|i| i.player1_down || i.player2_right, Command::CursorDown),
+];
+
+#[cfg(feature = "editor")]
+const EDITOR_PRESSED_BINDINGS: &[Binding] = &[
+    (|i| i.player1_a || i.player2_a, Command::Confirm),
+    (|i| i.system_one_player, Command::StartOnePlayer),
+    (|i| i.system_two_player, Command::StartTwoPlayer),
+];
+
+// Remembers last frame's buttons so bindings that act on a press only fire
+// once per press.
+#[derive(Default)]
+pub(crate) struct CommandMapper {
+    last: InputSnapshot,
+    start_armed: bool,   // A system button went down on the main menu
+    start_chorded: bool, // Both system buttons have been down together since
+}
+
+impl CommandMapper {
+    pub(crate) fn map(&mut self, phase: &GamePhase, inputs: &InputSnapshot) -> Vec<Command> {
+        let mut commands = Vec::new();
+        match phase {
+            GamePhase::Loading { .. } => {}
+            GamePhase::Playing => self.held(inputs, PLAY_BINDINGS, &mut commands),
+            GamePhase::NameEntry { .. } => {
+                self.pressed(inputs, NAME_ENTRY_BINDINGS, &mut commands);
+            }
+            GamePhase::Ready { .. } => self.pressed(inputs, READY_BINDINGS, &mut commands),
+            #[cfg(feature = "editor")]
+            GamePhase::Editor(_) => {
+                self.held(inputs, EDITOR_HELD_BINDINGS, &mut commands);
+                self.pressed(inputs, EDITOR_PRESSED_BINDINGS, &mut commands);
+            }
+            GamePhase::ModeSelect { .. } => {
+                self.pressed(inputs, MENU_BINDINGS, &mut commands);
+                self.start_on_release(inputs, &mut commands);
+            }
+            GamePhase::Operator(_) => {
+                self.pressed(inputs, MENU_BINDINGS, &mut commands);
+                self.pressed(inputs, SERVICE_BINDINGS, &mut commands);
+            }
+            GamePhase::GameOver { .. }
+            | GamePhase::Leaderboard { .. }
+            | GamePhase::Settings { .. }
+            | GamePhase::Mutators { .. }
+            | GamePhase::TournamentSetup { .. }
+            | GamePhase::Series
+            | GamePhase::ResumePrompt { .. }
+            | GamePhase::Splash { .. }
+            | GamePhase::Credits { .. }
+            | GamePhase::HowToPlay { .. }
+            | GamePhase::NewGamePlus { .. }
+            | GamePhase::Crashed(_) => {
+                self.pressed(inputs, MENU_BINDINGS, &mut commands);
+                self.pressed(inputs, SYSTEM_BINDINGS, &mut commands);
+            }
+        }
+        self.last = *inputs;
+        commands
+    }
+
+    fn held(&self, inputs: &InputSnapshot, bindings: &[Binding], commands: &mut Vec<Command>) {
+        for (button, command) in bindings {
+            if button(inputs) {
+                commands.push(*command);
+            }
+        }
+    }
+
+    fn pressed(&self, inputs: &InputSnapshot, bindings: &[Binding], commands: &mut Vec<Command>) {
+        for (button, command) in bindings {
+            if button(inputs) && !button(&self.last) {
+                commands.push(*command);
+            }
+        }
+    }
+
+    // On the main menu the system buttons start a game when let go rather
+    // than when pressed, so both can be held together for the operator menu
+    // without starting one. Only presses made on the menu count, so a button
+    // still held from the screen before doesn't start a game on release.
+    fn start_on_release(&mut self, inputs: &InputSnapshot, commands: &mut Vec<Command>) {
+        let mut pressed = Vec::new();
+        self.pressed(inputs, SYSTEM_BINDINGS, &mut pressed);
+        if !pressed.is_empty() {
+            self.start_armed = true;
+        }
+        self.held(inputs, SERVICE_BINDINGS, commands);
+        if commands.contains(&Command::ServiceHeld) {
+            self.start_chorded = true;
+        }
+        if self.start_armed && !self.start_chorded {
+            for (button, command) in SYSTEM_BINDINGS {
+                if !button(inputs) && button(&self.last) {
+                    commands.push(*command);
+                }
+            }
+        }
+        if !inputs.system_one_player && !inputs.system_two_player {
+            self.start_armed = false;
+            self.start_chorded = false;
+        }
+    }
+}
+
+// What a d-pad held in `inputs` does in play, for sources that are driven
+// tick by tick rather than polled once a frame
+pub(crate) fn held_commands(inputs: &InputSnapshot) -> Vec<Command> {
+    let mut commands = Vec::new();
+    CommandMapper::default().held(inputs, PLAY_BINDINGS, &mut commands);
+    commands
+}
+
+// Menus are single columns, so left steps back like up and right forward
+// like down.
+pub(crate) fn menu_prev(commands: &[Command]) -> bool {
+    commands.contains(&Command::CursorUp) || commands.contains(&Command::CursorLeft)
+}
+
+pub(crate) fn menu_next(commands: &[Command]) -> bool {
+    commands.contains(&Command::CursorDown) || commands.contains(&Command::CursorRight)
+}
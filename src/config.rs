@@ -0,0 +1,647 @@
+// Data-driven content: object definitions, wave scripts, spawn tables and
+// scoring rules. Built-ins come from assets/; the host page can override them.
+
+use super::*;
+use serde::de::DeserializeOwned;
+
+// The built-in behavior an object kind uses. Kinds themselves are defined
+// in assets/objects.json and name one of these.
+#[derive(Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ObjectType {
+    GoodDeal,    // Catch these for points
+    BadItem,     // Dodge these or lose health
+    EnergyDrink, // Power-up: temporary speed boost
+    PrankFlyer,  // Trick hazard: temporarily reverses controls
+    SpilledSoda, // Floor hazard: leaves a sticky patch where it lands
+}
+
+#[derive(Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum EffectKind {
+    SpeedBoost,
+    ReversedControls,
+    Shield, // Absorbs the next hazard, then breaks
+}
+
+#[derive(Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SpriteShape {
+    Box,
+    Can,
+    Flyer,
+    Cup,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Sprite {
+    pub(crate) shape: SpriteShape,
+    pub(crate) color: String,
+    #[serde(default)]
+    pub(crate) glyph: String,
+    #[serde(default)]
+    pub(crate) glyph_color: String,
+    // Sprite atlas frames drawn in place of the shape, cycling if there are
+    // several, once the atlas has loaded
+    #[serde(default)]
+    pub(crate) frames: Vec<String>,
+}
+
+#[derive(Copy, Clone, Deserialize)]
+pub(crate) struct EffectDef {
+    pub(crate) kind: EffectKind,
+    pub(crate) duration: u32, // Frames
+}
+
+// One kind of falling object as described in assets/objects.json.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ObjectDef {
+    pub(crate) id: String,
+    pub(crate) behavior: ObjectType,
+    pub(crate) sprite: Sprite,
+    #[serde(default = "default_object_width")]
+    pub(crate) width: f64,
+    #[serde(default = "default_object_height")]
+    pub(crate) height: f64,
+    #[serde(default = "default_speed_modifier")]
+    pub(crate) speed: f64, // Multiplies every velocity, so a lob keeps its arc
+    pub(crate) score: Option<i32>, // Points for a catch; falls back to the scoring rules
+    pub(crate) effect: Option<EffectDef>, // Applied to whoever catches it
+    #[serde(default)]
+    spawn_weight: f64, // Random spawn weight in waves whose spawn table doesn't list it
+}
+
+fn default_object_width() -> f64 {
+    OBJECT_WIDTH
+}
+
+fn default_object_height() -> f64 {
+    OBJECT_HEIGHT
+}
+
+fn default_speed_modifier() -> f64 {
+    1.0
+}
+
+const BUILTIN_OBJECTS: &str = include_str!("../assets/objects.json");
+
+pub(crate) struct ObjectCatalog {
+    pub(crate) defs: Vec<ObjectDef>,
+}
+
+impl ObjectCatalog {
+    fn builtin() -> Self {
+        let defs = serde_json::from_str(BUILTIN_OBJECTS).expect("assets/objects.json parses");
+        ObjectCatalog::from_defs(defs, WorldConfig::default())
+            .expect("assets/objects.json is valid")
+    }
+
+    // Objects also have to fit across the world at their biggest, with the big
+    // pieces setting on, or nowhere is left to put them
+    fn from_defs(defs: Vec<ObjectDef>, world: WorldConfig) -> Result<Self, String> {
+        if defs.is_empty() {
+            return Err("no object definitions".to_string());
+        }
+        for (i, def) in defs.iter().enumerate() {
+            if defs[..i].iter().any(|other| other.id == def.id) {
+                return Err(format!("duplicate object id \"{}\"", def.id));
+            }
+            if def.width <= 0.0 || def.height <= 0.0 || def.speed <= 0.0 {
+                return Err(format!(
+                    "object \"{}\" needs a positive size and speed",
+                    def.id
+                ));
+            }
+            if def.width * BIG_PIECES_SCALE > world.width {
+                return Err(format!(
+                    "object \"{}\" is wider than the {} wide world at big pieces size",
+                    def.id, world.width
+                ));
+            }
+        }
+        Ok(ObjectCatalog { defs })
+    }
+
+    pub(crate) fn get(&self, kind: usize) -> &ObjectDef {
+        &self.defs[kind]
+    }
+
+    pub(crate) fn find(&self, id: &str) -> Option<usize> {
+        self.defs.iter().position(|def| def.id == id)
+    }
+
+    // First kind using the given behavior, for set pieces that need e.g. a deal
+    pub(crate) fn first_of(&self, behavior: ObjectType) -> Option<usize> {
+        self.defs.iter().position(|def| def.behavior == behavior)
+    }
+
+    // Weighted random pick; `weight` is given each kind and its definition
+    pub(crate) fn pick(
+        &self,
+        rng: &mut impl Rng,
+        weight: impl Fn(usize, &ObjectDef) -> f64,
+    ) -> Option<usize> {
+        let weights: Vec<f64> = self
+            .defs
+            .iter()
+            .enumerate()
+            .map(|(kind, def)| weight(kind, def).max(0.0))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut roll = rng.gen_range(0.0..total);
+        for (kind, w) in weights.iter().enumerate() {
+            if roll < *w {
+                return Some(kind);
+            }
+            roll -= w;
+        }
+        Some(weights.len() - 1)
+    }
+}
+
+// Relative odds of each object kind for random spawns, from `from_wave`
+// until the next table takes over.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpawnTable {
+    from_wave: u32,
+    weights: HashMap<String, f64>, // Keyed by ObjectDef id
+    #[serde(skip)]
+    resolved: Vec<f64>, // Weight per kind, filled in when the tables are loaded
+}
+
+const BUILTIN_SPAWN_TABLES: &str = include_str!("../assets/spawn_tables.json");
+
+pub(crate) struct SpawnTables {
+    tables: Vec<SpawnTable>,
+}
+
+impl SpawnTables {
+    fn builtin_source() -> Vec<SpawnTable> {
+        serde_json::from_str(BUILTIN_SPAWN_TABLES).expect("assets/spawn_tables.json parses")
+    }
+
+    // Sorts the tables by wave and turns ids into per-kind weights. Kinds a
+    // table leaves out fall back to their definition's spawn weight.
+    fn from_tables(mut tables: Vec<SpawnTable>, objects: &ObjectCatalog) -> Result<Self, String> {
+        tables.sort_by_key(|table| table.from_wave);
+        for table in &mut tables {
+            if let Some(id) = table.weights.keys().find(|id| objects.find(id).is_none()) {
+                return Err(format!(
+                    "wave {} spawn table uses unknown object \"{}\"",
+                    table.from_wave, id
+                ));
+            }
+            table.resolved = objects
+                .defs
+                .iter()
+                .map(|def| *table.weights.get(&def.id).unwrap_or(&def.spawn_weight))
+                .collect();
+        }
+        Ok(SpawnTables { tables })
+    }
+
+    pub(crate) fn weight(&self, wave: u32, kind: usize, def: &ObjectDef) -> f64 {
+        self.tables
+            .iter()
+            .rev()
+            .find(|table| table.from_wave <= wave)
+            .map_or(def.spawn_weight, |table| table.resolved[kind])
+    }
+}
+
+// A designed set piece: timed drops that play out during one wave.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WaveScript {
+    pub(crate) wave: u32,
+    #[serde(default)]
+    pub(crate) name: String, // Announced on the ticker when the script starts
+    #[serde(default = "default_random_spawns")]
+    pub(crate) random_spawns: bool, // Keep the random spawner going alongside the script
+    pub(crate) spawns: Vec<ScriptedSpawn>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct ScriptedSpawn {
+    pub(crate) frame: u32, // Frames after the wave starts
+    pub(crate) x: f64,
+    pub(crate) object: String, // ObjectDef id
+    #[serde(skip)]
+    pub(crate) kind: usize, // Resolved from `object` when the scripts are loaded
+}
+
+fn default_random_spawns() -> bool {
+    true
+}
+
+const BUILTIN_WAVES: &str = include_str!("../assets/waves.json");
+
+pub(crate) struct WaveScripts {
+    pub(crate) scripts: Vec<WaveScript>,
+}
+
+impl WaveScripts {
+    fn builtin_source() -> Vec<WaveScript> {
+        serde_json::from_str(BUILTIN_WAVES).expect("assets/waves.json parses")
+    }
+
+    // Sorts each script by time and resolves object ids against the catalog
+    fn from_scripts(mut scripts: Vec<WaveScript>, objects: &ObjectCatalog) -> Result<Self, String> {
+        for script in &mut scripts {
+            script.spawns.sort_by_key(|spawn| spawn.frame);
+            for spawn in &mut script.spawns {
+                spawn.kind = objects.find(&spawn.object).ok_or_else(|| {
+                    format!(
+                        "wave {} script uses unknown object \"{}\"",
+                        script.wave, spawn.object
+                    )
+                })?;
+            }
+        }
+        Ok(WaveScripts { scripts })
+    }
+
+    pub(crate) fn for_wave(&self, wave: u32) -> Option<usize> {
+        self.scripts.iter().position(|script| script.wave == wave)
+    }
+}
+
+// Point values and penalties. Defaults match the original hardcoded rules;
+// a deployment can override any of them through GameConfig.
+pub(crate) struct ScoringRules {
+    pub(crate) deal_points: i32,
+    pub(crate) power_up_points: i32,
+    pub(crate) hazard_damage: i32,
+    pub(crate) combo_breakpoints: Vec<u32>, // Combo counts at which the multiplier goes up by one
+    pub(crate) miss_penalty: i32, // Points lost by each living player when a deal hits the floor
+    pub(crate) miss_rule: Option<bool>, // Whether drops cost combo and points; unset follows the difficulty
+    pub(crate) misses_allowed: u32, // Drops in a row the miss rule lets go before charging for them
+    pub(crate) miss_streak_penalty: i32, // What the miss rule charges for each drop past that
+    pub(crate) survival_points: i32,
+    pub(crate) perfect_bonus: i32, // Extra points for a deal caught dead center
+    pub(crate) heal_streak: u32,   // Catches without taking damage that win back a heart
+    pub(crate) new_game_plus_score: i32, // A solo run reaching this is offered NEW GAME+; 0 never
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        ScoringRules {
+            deal_points: 10,
+            power_up_points: 0,
+            hazard_damage: 1,
+            combo_breakpoints: vec![5, 10, 15],
+            miss_penalty: 0,
+            miss_rule: None,
+            misses_allowed: 3,
+            miss_streak_penalty: 5,
+            survival_points: 5,
+            perfect_bonus: 5,
+            heal_streak: 25,
+            new_game_plus_score: 1000,
+        }
+    }
+}
+
+impl ScoringRules {
+    fn apply_overrides(&mut self, overrides: &serde_json::Value) {
+        let number = |key: &str| overrides.get(key).and_then(|v| v.as_f64());
+        if let Some(v) = number("dealPoints") {
+            self.deal_points = v as i32;
+        }
+        if let Some(v) = number("powerUpPoints") {
+            self.power_up_points = v as i32;
+        }
+        if let Some(v) = number("hazardDamage") {
+            self.hazard_damage = v as i32;
+        }
+        if let Some(v) = number("missPenalty") {
+            self.miss_penalty = v as i32;
+        }
+        if let Some(v) = overrides.get("missRule").and_then(|v| v.as_bool()) {
+            self.miss_rule = Some(v);
+        }
+        if let Some(v) = number("missesAllowed") {
+            self.misses_allowed = v as u32;
+        }
+        if let Some(v) = number("missStreakPenalty") {
+            self.miss_streak_penalty = v as i32;
+        }
+        if let Some(v) = number("survivalPoints") {
+            self.survival_points = v as i32;
+        }
+        if let Some(v) = number("perfectBonus") {
+            self.perfect_bonus = v as i32;
+        }
+        if let Some(v) = number("healStreak") {
+            self.heal_streak = v as u32;
+        }
+        if let Some(v) = number("newGamePlusScore") {
+            self.new_game_plus_score = v as i32;
+        }
+        if let Some(breakpoints) = overrides.get("comboBreakpoints").and_then(|v| v.as_array()) {
+            let mut parsed: Vec<u32> = breakpoints
+                .iter()
+                .filter_map(|v| v.as_f64())
+                .map(|v| v as u32)
+                .collect();
+            parsed.sort_unstable();
+            self.combo_breakpoints = parsed;
+        }
+    }
+
+    pub(crate) fn combo_multiplier(&self, combo: u32) -> i32 {
+        1 + self
+            .combo_breakpoints
+            .iter()
+            .filter(|&&breakpoint| combo >= breakpoint)
+            .count() as i32
+    }
+}
+
+// The playfield's size in logical units. Spawning, movement and the screens
+// all work from this rather than fixed numbers, so a cabinet or embed can
+// pick its own when the game mounts.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorldConfig {
+    pub(crate) width: f64,
+    pub(crate) height: f64,
+}
+
+const FLOOR_MARGIN: f64 = 20.0; // Space below the floor line
+const MIN_WORLD_SIZE: f64 = 160.0; // Smaller and the menus stop fitting
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Layout::Landscape.world()
+    }
+}
+
+impl WorldConfig {
+    // Players stand on this line; floor hazards sit on it too.
+    pub(crate) fn floor_y(self) -> f64 {
+        self.height - FLOOR_MARGIN
+    }
+
+    fn check(self) -> Result<Self, String> {
+        if self.width < MIN_WORLD_SIZE || self.height < MIN_WORLD_SIZE {
+            return Err(format!(
+                "{}x{} is smaller than {MIN_WORLD_SIZE}x{MIN_WORLD_SIZE}",
+                self.width, self.height
+            ));
+        }
+        Ok(self)
+    }
+}
+
+// Which way up the cabinet's screen is mounted, for pages that don't give
+// the world's size outright. A portrait screen gets a narrower, taller
+// playfield.
+#[derive(Copy, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Layout {
+    #[default]
+    Landscape,
+    Portrait,
+}
+
+impl Layout {
+    pub(crate) fn world(self) -> WorldConfig {
+        let (width, height) = match self {
+            Layout::Landscape => (330.0, 250.0),
+            Layout::Portrait => (250.0, 330.0),
+        };
+        WorldConfig { width, height }
+    }
+}
+
+// How forgiving a deployment plays. Each preset sets how long a run opens
+// with deals only, how fast things fall and speed up after that, and whether
+// dropped deals are punished.
+#[derive(Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+pub(crate) struct DifficultyPreset {
+    pub(crate) grace_ticks: u32, // Deals only, falling slowly, at the start of a run
+    pub(crate) fall_speed: f64,  // Multiplies every object's speed
+    pub(crate) ramp: f64,        // Multiplies how much harder each wave gets
+    pub(crate) miss_rule: bool, // Dropped deals cost combo and points unless the page says otherwise
+}
+
+impl Difficulty {
+    pub(crate) fn preset(self) -> DifficultyPreset {
+        match self {
+            Difficulty::Easy => DifficultyPreset {
+                grace_ticks: 8 * 60,
+                fall_speed: 0.85,
+                ramp: 0.75,
+                miss_rule: false,
+            },
+            Difficulty::Normal => DifficultyPreset {
+                grace_ticks: 5 * 60,
+                fall_speed: 1.0,
+                ramp: 1.0,
+                miss_rule: false,
+            },
+            Difficulty::Hard => DifficultyPreset {
+                grace_ticks: 2 * 60,
+                fall_speed: 1.15,
+                ramp: 1.25,
+                miss_rule: true,
+            },
+        }
+    }
+}
+
+// The opening stretch of a run, paced on its own: fall speed and spawn rate
+// start at a fraction of the main curve's and climb to it over `seconds`.
+// The main curve runs underneath untouched, so a gentler start doesn't
+// flatten the late game.
+#[derive(Copy, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct WarmUp {
+    pub(crate) seconds: f64,
+    pub(crate) fall_speed: f64, // Where fall speed starts, as a share of the main curve's
+    pub(crate) spawn_rate: f64, // Likewise for how often things spawn
+}
+
+impl Default for WarmUp {
+    fn default() -> Self {
+        WarmUp {
+            seconds: 30.0,
+            fall_speed: 0.75,
+            spawn_rate: 0.6,
+        }
+    }
+}
+
+impl WarmUp {
+    // `start` on the first tick, rising in a straight line to 1 once it's over
+    pub(crate) fn scale(&self, start: f64, elapsed_ticks: u32) -> f64 {
+        let duration = self.seconds * 60.0;
+        if duration <= 0.0 {
+            return 1.0;
+        }
+        let t = (elapsed_ticks as f64 / duration).min(1.0);
+        start + (1.0 - start) * t
+    }
+}
+
+const DEFAULT_LEADERBOARD_SIZE: usize = 100;
+
+// Per-deployment tuning, read from an optional
+// <script id="game-config" type="application/json"> block in the host page.
+pub(crate) struct GameConfig {
+    pub(crate) world: WorldConfig,
+    pub(crate) difficulty: Difficulty,
+    pub(crate) warm_up: WarmUp,
+    pub(crate) scoring: ScoringRules,
+    pub(crate) objects: ObjectCatalog,
+    pub(crate) waves: WaveScripts,
+    pub(crate) spawn_tables: SpawnTables,
+    pub(crate) share_url: Option<String>, // Where the game over QR code links to
+    pub(crate) atlas: Option<AtlasSource>,
+    pub(crate) font: Option<FontSource>,
+    pub(crate) leaderboard_size: usize, // Entries kept on each board
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        let objects = ObjectCatalog::builtin();
+        let waves = WaveScripts::from_scripts(WaveScripts::builtin_source(), &objects)
+            .expect("assets/waves.json is valid");
+        let spawn_tables = SpawnTables::from_tables(SpawnTables::builtin_source(), &objects)
+            .expect("assets/spawn_tables.json is valid");
+        GameConfig {
+            world: WorldConfig::default(),
+            difficulty: Difficulty::default(),
+            warm_up: WarmUp::default(),
+            scoring: ScoringRules::default(),
+            objects,
+            waves,
+            spawn_tables,
+            share_url: None,
+            atlas: None,
+            font: None,
+            leaderboard_size: DEFAULT_LEADERBOARD_SIZE,
+        }
+    }
+}
+
+fn warn_config(message: &str) {
+    log(LogLevel::Warn, "config", message);
+}
+
+// One section of the config blob read with serde. None when it's missing, and
+// when it doesn't parse, which is reported; the other sections still apply.
+fn config_section<T: DeserializeOwned>(parsed: &serde_json::Value, key: &str) -> Option<T> {
+    let value = parsed.get(key).filter(|value| !value.is_null())?;
+    serde_json::from_value(value.clone())
+        .map_err(|err| warn_config(&format!("{key} ignored: {err}")))
+        .ok()
+}
+
+impl GameConfig {
+    // The page's `<script id="game-config">` blob, if there is a page with one.
+    // Scores are shared as links back to the page itself unless it says
+    // otherwise.
+    pub(crate) fn from_page() -> Self {
+        let window = web_sys::window();
+        let mut config: GameConfig = window
+            .as_ref()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id("game-config"))
+            .and_then(|element| element.text_content())
+            .map(|text| GameConfig::from_json(&text))
+            .unwrap_or_default();
+        if config.share_url.is_none() {
+            let location = window.map(|window| window.location());
+            config.share_url = location
+                .and_then(|location| Some(location.origin().ok()? + &location.pathname().ok()?));
+        }
+        config
+    }
+
+    // Built-in defaults with whatever a config blob overrides. Bad sections
+    // are reported and skipped rather than failing the whole load.
+    pub(crate) fn from_json(text: &str) -> Self {
+        let mut config = GameConfig::default();
+        let parsed = serde_json::from_str::<serde_json::Value>(text).unwrap_or_else(|err| {
+            warn_config(&format!("config ignored: {err}"));
+            serde_json::Value::Null
+        });
+        if let Some(scoring) = parsed.get("scoring").filter(|v| v.is_object()) {
+            config.scoring.apply_overrides(scoring);
+        }
+
+        // Log levels aren't part of the config, they just come in the same blob
+        let levels: HashMap<String, LogLevel> = config_section(&parsed, "log").unwrap_or_default();
+        for (module, level) in levels {
+            set_log_level(&module, level);
+        }
+        config.share_url = config_section(&parsed, "shareUrl");
+        config.atlas = config_section(&parsed, "atlas");
+        config.font = config_section(&parsed, "font");
+        match config_section(&parsed, "leaderboardSize") {
+            Some(0) => warn_config("leaderboardSize ignored: must be at least 1"),
+            Some(size) => config.leaderboard_size = size,
+            None => {}
+        }
+        config.difficulty = config_section(&parsed, "difficulty").unwrap_or_default();
+        config.warm_up = config_section(&parsed, "warmUp").unwrap_or_default();
+        // An explicit "world" size wins over the "layout" preset
+        config.world = config_section::<Layout>(&parsed, "layout")
+            .unwrap_or_default()
+            .world();
+        if let Some(world) = config_section::<WorldConfig>(&parsed, "world") {
+            match world.check() {
+                Ok(world) => config.world = world,
+                Err(err) => warn_config(&format!("world ignored: {err}")),
+            }
+        }
+
+        // A full "objects" list replaces the built-in definitions
+        if let Some(defs) = config_section(&parsed, "objects") {
+            match ObjectCatalog::from_defs(defs, config.world) {
+                Ok(objects) => config.objects = objects,
+                Err(err) => warn_config(&format!("objects ignored: {err}")),
+            }
+        }
+
+        // Likewise "waves"; scripts are checked against whichever objects won
+        let scripts = config_section(&parsed, "waves").unwrap_or_else(WaveScripts::builtin_source);
+        match WaveScripts::from_scripts(scripts, &config.objects) {
+            Ok(waves) => config.waves = waves,
+            Err(err) => {
+                warn_config(&format!("waves ignored: {err}"));
+                config.waves = WaveScripts {
+                    scripts: Vec::new(),
+                };
+            }
+        }
+
+        // And "spawnTables"; without any, objects spawn at their own weights
+        let tables =
+            config_section(&parsed, "spawnTables").unwrap_or_else(SpawnTables::builtin_source);
+        match SpawnTables::from_tables(tables, &config.objects) {
+            Ok(spawn_tables) => config.spawn_tables = spawn_tables,
+            Err(err) => {
+                warn_config(&format!("spawn tables ignored: {err}"));
+                config.spawn_tables = SpawnTables { tables: Vec::new() };
+            }
+        }
+        config
+    }
+}
@@ -0,0 +1,301 @@
+// Developer command console, built with `--features debug-console`.
+// Backquote opens it; each line is run against the live GameState:
+//
+//   spawn bad 150         drop an object (id or good/bad/energy/prank/soda) at x
+//   set difficulty 2.5    also: wave, wind, score p1 500, health p2 5
+//   give shield p1        also: speed, reverse; optional duration in seconds
+//   phase gameover        also: menu, playing, settings, mutators
+//   clear                 remove every falling object
+//   log                   the latest log messages; log level storage debug
+//                         sets one module's level, or "default" for the rest
+//   store mock            swap saving over to an empty mock store; then
+//                         store fail reads/writes/both/none, and store writes
+//                         lists what was saved
+//   help
+
+use super::*;
+
+const MAX_INPUT: usize = 40;
+const MAX_LINES: usize = 5;
+const DEFAULT_EFFECT_SECONDS: u32 = 10;
+
+#[derive(Default)]
+pub(crate) struct Console {
+    open: bool,
+    input: String,
+    lines: Vec<String>,          // Recent commands and their output, oldest first
+    submitted: VecDeque<String>, // Entered but not yet run
+    mock_store: Option<Rc<MockStore>>, // Set once "store mock" has swapped it in
+}
+
+impl Console {
+    fn print(&mut self, line: String) {
+        self.lines.push(line);
+        if self.lines.len() > MAX_LINES {
+            self.lines.remove(0);
+        }
+    }
+}
+
+pub(crate) fn setup_listeners() -> Result<Rc<RefCell<Console>>, JsValue> {
+    let console = Rc::new(RefCell::new(Console::default()));
+    let window = web_sys::window().unwrap();
+
+    let state = console.clone();
+    let keydown = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        let mut console = state.borrow_mut();
+        let code = event.code();
+        if !console.open {
+            if code == "Backquote" {
+                console.open = true;
+                event.prevent_default();
+                event.stop_immediate_propagation();
+            }
+            return;
+        }
+
+        match code.as_str() {
+            "Backquote" | "Escape" => console.open = false,
+            "Enter" => {
+                let line = std::mem::take(&mut console.input);
+                if !line.trim().is_empty() {
+                    console.submitted.push_back(line);
+                }
+            }
+            "Backspace" => {
+                console.input.pop();
+            }
+            _ => {
+                let key = event.key();
+                if key.chars().count() == 1 && console.input.len() < MAX_INPUT {
+                    console.input.push_str(&key);
+                }
+            }
+        }
+        // While open, nothing typed should reach the game
+        event.prevent_default();
+        event.stop_immediate_propagation();
+    }) as Box<dyn FnMut(_)>);
+    window.add_event_listener_with_callback("keydown", keydown.as_ref().unchecked_ref())?;
+    keydown.forget();
+
+    Ok(console)
+}
+
+pub(crate) fn run_submitted(console: &RefCell<Console>, state: &mut GameState) {
+    let mut console = console.borrow_mut();
+    while let Some(line) = console.submitted.pop_front() {
+        console.print(format!("> {line}"));
+        let output = run(state, &mut console.mock_store, &line)
+            .unwrap_or_else(|err| format!("error: {err}"));
+        for line in output.lines() {
+            console.print(line.to_string());
+        }
+    }
+}
+
+fn run(
+    state: &mut GameState,
+    mock_store: &mut Option<Rc<MockStore>>,
+    line: &str,
+) -> Result<String, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["help"] => Ok("spawn, set, give, phase, clear, log, store".to_string()),
+        ["log"] => {
+            let entries: Vec<String> = recent_log(MAX_LINES - 1)
+                .iter()
+                .map(|entry| entry.to_string())
+                .collect();
+            if entries.is_empty() {
+                return Ok("nothing logged".to_string());
+            }
+            Ok(entries.join("\n"))
+        }
+        ["log", "level", module, level] => {
+            let level =
+                LogLevel::parse(level).ok_or_else(|| format!("unknown log level {level}"))?;
+            set_log_level(module, level);
+            Ok(format!("{module} logs at {}", level.name()))
+        }
+        ["clear"] => {
+            state.objects.clear();
+            state.pending_spawns.clear();
+            Ok("cleared".to_string())
+        }
+        ["store", "mock"] => {
+            let store = Rc::new(MockStore::default());
+            state.set_store(store.clone());
+            *mock_store = Some(store);
+            Ok("saving to a mock store".to_string())
+        }
+        ["store", "fail", what] => {
+            let store = mock_store
+                .as_ref()
+                .ok_or("no mock store; store mock first")?;
+            let (reads, writes) = match *what {
+                "reads" => (true, false),
+                "writes" => (false, true),
+                "both" => (true, true),
+                "none" => (false, false),
+                _ => return Err("usage: store fail reads|writes|both|none".to_string()),
+            };
+            store.fail_reads.set(reads);
+            store.fail_writes.set(writes);
+            Ok(format!("failing reads: {reads}, writes: {writes}"))
+        }
+        ["store", "writes"] => {
+            let store = mock_store
+                .as_ref()
+                .ok_or("no mock store; store mock first")?;
+            let writes = store.writes();
+            if writes.is_empty() {
+                return Ok("nothing written".to_string());
+            }
+            let shown = writes.len().saturating_sub(MAX_LINES - 1);
+            Ok(writes[shown..]
+                .iter()
+                .map(|write| {
+                    let key = write.key.trim_start_matches("black_friday_");
+                    let failed = if write.failed { " FAILED" } else { "" };
+                    format!("{key} {}b{failed}", write.bytes)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }
+
+        ["spawn", object, rest @ ..] => {
+            let kind = find_object(&state.config.objects, object)?;
+            let def = state.config.objects.get(kind);
+            let width = state.world.width;
+            let x = match rest {
+                [] => (width - def.width) / 2.0,
+                [x] => parse::<f64>(x)?,
+                _ => return Err("usage: spawn <object> [x]".to_string()),
+            };
+            let x = x.clamp(0.0, width - def.width);
+            let mut object = FallingObject::dropped(x, kind, def);
+            state.behaviors.get(object.obj_type).on_spawn(&mut object);
+            state.objects.push(object);
+            Ok(format!("spawned {} at {x:.0}", def.id))
+        }
+        ["set", "difficulty", value] => {
+            state.difficulty_multiplier = parse(value)?;
+            Ok(format!("difficulty {:.2}", state.difficulty_multiplier))
+        }
+        ["set", "wave", value] => {
+            state.wave = parse::<u32>(value)?.max(1);
+            state.wave_timer = 0;
+            Ok(format!("wave {}", state.wave))
+        }
+        ["set", "wind", value] => {
+            state.wind = parse(value)?;
+            state.wind_target = state.wind;
+            Ok(format!("wind {:.2}", state.wind))
+        }
+        ["set", "score", player, value] => {
+            let value = parse(value)?;
+            find_player(state, player)?.score = value;
+            Ok(format!("{player} score {value}"))
+        }
+        ["set", "health", player, value] => {
+            let value = parse::<i32>(value)?.max(1);
+            find_player(state, player)?.health = value;
+            Ok(format!("{player} health {value}"))
+        }
+        ["give", effect, player, rest @ ..] => {
+            let kind = match *effect {
+                "speed" => EffectKind::SpeedBoost,
+                "reverse" => EffectKind::ReversedControls,
+                "shield" => EffectKind::Shield,
+                _ => return Err(format!("unknown effect {effect}")),
+            };
+            let seconds = match rest {
+                [] => DEFAULT_EFFECT_SECONDS,
+                [seconds] => parse(seconds)?,
+                _ => return Err("usage: give <effect> <player> [seconds]".to_string()),
+            };
+            find_player(state, player)?.apply_effect(kind, seconds.saturating_mul(60));
+            Ok(format!("{player} has {effect} for {seconds}s"))
+        }
+        ["phase", phase] => {
+            match *phase {
+                "menu" => state.back_to_menu(),
+                "playing" => state.start_new_game(state.mode),
+                "settings" => state.open_settings(),
+                "mutators" => state.open_mutators(),
+                // Ends the run the normal way, through name entry
+                "gameover" if matches!(state.phase, GamePhase::Playing) => {
+                    for slot in &mut state.players {
+                        slot.health = 0;
+                    }
+                }
+                "gameover" => return Err("no run in progress".to_string()),
+                _ => return Err(format!("unknown phase {phase}")),
+            }
+            Ok(format!("phase {phase}"))
+        }
+        _ => Err(format!("unknown command: {line}")),
+    }
+}
+
+fn parse<T: std::str::FromStr>(word: &str) -> Result<T, String> {
+    word.parse().map_err(|_| format!("bad number {word}"))
+}
+
+// Object ids from the catalog, plus short names for the built-in behaviors
+fn find_object(objects: &ObjectCatalog, name: &str) -> Result<usize, String> {
+    if let Some(kind) = objects.find(name) {
+        return Ok(kind);
+    }
+    let behavior = match name {
+        "good" => ObjectType::GoodDeal,
+        "bad" => ObjectType::BadItem,
+        "energy" => ObjectType::EnergyDrink,
+        "prank" => ObjectType::PrankFlyer,
+        "soda" => ObjectType::SpilledSoda,
+        _ => return Err(format!("unknown object {name}")),
+    };
+    objects
+        .first_of(behavior)
+        .ok_or_else(|| format!("no object uses {name}"))
+}
+
+fn find_player<'a>(state: &'a mut GameState, name: &str) -> Result<&'a mut PlayerSlot, String> {
+    let index = match name {
+        "p1" | "1" => 0,
+        "p2" | "2" => 1,
+        _ => return Err(format!("unknown player {name}")),
+    };
+    state
+        .players
+        .iter_mut()
+        .find(|slot| slot.player_index == index)
+        .ok_or_else(|| format!("{name} isn't playing"))
+}
+
+pub(crate) fn draw(ctx: &dyn Renderer, console: &Console, world: WorldConfig) {
+    if !console.open {
+        return;
+    }
+    let height = 16.0 + (MAX_LINES as f64 + 1.0) * 10.0;
+    ctx.rect(0.0, 0.0, world.width, height, "rgba(0, 0, 0, 0.85)");
+    ctx.rect(0.0, height, world.width, 1.0, "#0f0");
+
+    for (i, line) in console.lines.iter().enumerate() {
+        let color = if line.starts_with("error") {
+            "#ff6666"
+        } else {
+            "#ccc"
+        };
+        ctx.text(line, 5.0, 12.0 + i as f64 * 10.0, 8.0, color, Align::Left);
+    }
+    ctx.text(
+        &format!("] {}_", console.input),
+        5.0,
+        12.0 + MAX_LINES as f64 * 10.0 + 4.0,
+        8.0,
+        "#0f0",
+        Align::Left,
+    );
+}
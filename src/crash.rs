@@ -0,0 +1,164 @@
+// Crash recovery. A panic in the wasm build kills the frame loop and would
+// leave the cabinet on a frozen canvas; instead the panic hook logs it, saves
+// a short report of what the game was doing, and starts a fresh App on the
+// same canvas showing a crash screen. Pressing 1P from there goes back to the
+// main menu.
+//
+// The hook can't reach the App, so each frame leaves a note of the counts the
+// report wants, as of the last frame that started. A game that keeps crashing
+// before anyone gets past the crash screen stops restarting after a few goes.
+
+use super::*;
+
+const MAX_RESTARTS: u32 = 3;
+
+// What the game was doing when it went down
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CrashReport {
+    pub(crate) message: String,
+    pub(crate) phase: String,
+    pub(crate) frame: u32,
+    pub(crate) players: usize,
+    pub(crate) objects: usize,
+    pub(crate) pending_spawns: usize,
+    pub(crate) popups: usize,
+    pub(crate) particles: usize,
+}
+
+struct LastFrame {
+    store: Rc<dyn KeyValueStore>,
+    report: CrashReport,
+}
+
+thread_local! {
+    static LAST_FRAME: RefCell<Option<LastFrame>> = const { RefCell::new(None) };
+    static RESTARTS: Cell<u32> = const { Cell::new(0) };
+}
+
+impl GamePhase {
+    fn name(&self) -> &'static str {
+        match self {
+            GamePhase::ModeSelect { .. } => "menu",
+            GamePhase::Playing => "playing",
+            GamePhase::GameOver { .. } => "game over",
+            GamePhase::Leaderboard { .. } => "leaderboard",
+            GamePhase::NameEntry { .. } => "name entry",
+            GamePhase::Settings { .. } => "settings",
+            GamePhase::Mutators { .. } => "mutators",
+            GamePhase::TournamentSetup { .. } => "tournament setup",
+            GamePhase::Series => "series",
+            GamePhase::Ready { .. } => "ready",
+            GamePhase::ResumePrompt { .. } => "resume prompt",
+            GamePhase::Crashed(_) => "crashed",
+            GamePhase::Loading { .. } => "loading",
+            GamePhase::Splash { .. } => "splash",
+            GamePhase::Credits { .. } => "credits",
+            GamePhase::HowToPlay { .. } => "how to play",
+            GamePhase::Operator(_) => "operator",
+            GamePhase::NewGamePlus { .. } => "new game plus",
+            #[cfg(feature = "editor")]
+            GamePhase::Editor(_) => "editor",
+        }
+    }
+}
+
+// Called at the top of every frame
+pub(crate) fn note_frame(state: &GameState) {
+    let report = CrashReport {
+        message: String::new(),
+        phase: state.phase.name().to_string(),
+        frame: state.frame_count,
+        players: state.players.len(),
+        objects: state.objects.len(),
+        pending_spawns: state.pending_spawns.len(),
+        popups: state.popups.len() + state.whooshes.len(),
+        particles: state.wind_particles.len(),
+    };
+    LAST_FRAME.with(|last| {
+        *last.borrow_mut() = Some(LastFrame {
+            store: state.store.clone(),
+            report,
+        });
+    });
+}
+
+pub(crate) fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let message = info.to_string();
+        log(LogLevel::Error, "panic", &message);
+
+        let mut report = CrashReport::default();
+        LAST_FRAME.with(|last| {
+            // The panic may have come from inside note_frame itself
+            if let Ok(last) = last.try_borrow()
+                && let Some(last) = &*last
+            {
+                report = last.report.clone();
+                report.message = message;
+                report.save(&*last.store);
+            }
+        });
+
+        let restarts = RESTARTS.get() + 1;
+        RESTARTS.set(restarts);
+        if restarts <= MAX_RESTARTS {
+            restart_after_panic(report);
+        }
+    }));
+}
+
+// The panicking frame still has to unwind out of wasm first, so the new App
+// is built from a timeout rather than from inside the hook
+fn restart_after_panic(report: CrashReport) {
+    let restart = Closure::once(move || {
+        let window = web_sys::window().unwrap();
+        let canvas = window
+            .document()
+            .and_then(|document| document.get_element_by_id("game"))
+            .and_then(|canvas| canvas.dyn_into::<HtmlCanvasElement>().ok());
+        match canvas.map(|canvas| App::new(&canvas)) {
+            Some(Ok(mut app)) => {
+                app.show_crash(report);
+                app::run(app);
+            }
+            // Nothing to build on; a reload is the best there is
+            _ => {
+                let _ = window.location().reload();
+            }
+        }
+    });
+    let _ = web_sys::window()
+        .unwrap()
+        .set_timeout_with_callback(restart.as_ref().unchecked_ref());
+    restart.forget();
+}
+
+impl GameState {
+    pub(crate) fn handle_crashed(&mut self, commands: &[Command]) {
+        if commands.contains(&Command::StartOnePlayer) {
+            RESTARTS.set(0);
+            self.back_to_menu();
+        }
+    }
+}
+
+pub(crate) fn draw_crashed(ctx: &dyn Renderer, world: WorldConfig, report: &CrashReport) {
+    let center = world.width / 2.0;
+    ctx.text("GAME CRASHED", center, 60.0, 18.0, "#ff4444", Align::Center);
+    let message: String = report.message.chars().take(60).collect();
+    ctx.text(&message, center, 90.0, 8.0, "#aaa", Align::Center);
+    let detail = format!(
+        "{} - frame {} - {} objects",
+        report.phase, report.frame, report.objects
+    );
+    ctx.text(&detail, center, 105.0, 8.0, "#888", Align::Center);
+    ctx.text(
+        "PRESS 1P TO RESTART",
+        center,
+        world.height - 40.0,
+        10.0,
+        "#fff",
+        Align::Center,
+    );
+}
@@ -0,0 +1,80 @@
+// Credits, from the main menu: who made the game and what it's built on,
+// rolling up the screen. It goes back to the menu by itself once the last
+// line has rolled off, or straight away on any button.
+
+use super::*;
+
+const SCROLL_SPEED: f64 = 0.5; // Logical units a frame
+
+const HEADING: (f64, &str) = (8.0, "#888");
+const NAME: (f64, &str) = (10.0, "#fff");
+
+const CREDITS: &[(&str, (f64, &str))] = &[
+    ("BLACK FRIDAY", (18.0, "#ffd700")),
+    ("", NAME),
+    ("A GAME BY", HEADING),
+    ("Andrew", NAME),
+    ("", NAME),
+    ("MADE FOR", HEADING),
+    ("RCade", NAME),
+    ("", NAME),
+    ("BUILT WITH", HEADING),
+    ("Rust and WebAssembly", NAME),
+    ("wasm-bindgen, js-sys, web-sys", NAME),
+    ("rand", NAME),
+    ("serde, serde_json", NAME),
+    ("qrcodegen", NAME),
+    ("hmac-sha256", NAME),
+    ("rcade-plugin-input-classic", NAME),
+    ("", NAME),
+    ("ART", HEADING),
+    ("Drawn in code", NAME),
+    ("", NAME),
+    ("AUDIO", HEADING),
+    ("None yet", NAME),
+    ("", NAME),
+    ("THANKS FOR PLAYING", (12.0, "#7fff7f")),
+];
+
+fn credit_lines() -> Vec<TextLine<'static>> {
+    CREDITS
+        .iter()
+        .map(|&(text, (size, color))| TextLine { text, size, color })
+        .collect()
+}
+
+impl GameState {
+    pub(crate) fn open_credits(&mut self) {
+        self.phase = GamePhase::Credits { frames: 0 };
+    }
+
+    pub(crate) fn handle_credits(&mut self, commands: &[Command]) {
+        if !commands.is_empty() {
+            self.phase = GamePhase::main_menu();
+        }
+    }
+
+    // Called once a frame
+    pub(crate) fn update_credits(&mut self) {
+        let GamePhase::Credits { frames } = &mut self.phase else {
+            return;
+        };
+        *frames += 1;
+        // Rolled in from below the screen and all the way off the top
+        let travelled = *frames as f64 * SCROLL_SPEED;
+        if travelled > self.world.height + column_height(&credit_lines()) {
+            self.phase = GamePhase::main_menu();
+        }
+    }
+}
+
+pub(crate) fn draw_credits(ctx: &dyn Renderer, world: WorldConfig, frames: u32) {
+    let top = world.height - frames as f64 * SCROLL_SPEED;
+    draw_text_column(
+        ctx,
+        &credit_lines(),
+        world.width / 2.0,
+        top,
+        (0.0, world.height),
+    );
+}
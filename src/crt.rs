@@ -0,0 +1,159 @@
+// The CRT look, an optional pass over each finished frame: the playfield
+// bulges a little like the glass of an old tube, dark scanlines run across it
+// and the corners fall off into shadow. There's no WebGL backend to do this
+// in a shader, so it's done with canvas copies: the frame goes out to an
+// offscreen buffer and comes back in thin strips, each a little shorter the
+// further it is from the middle, once across and once down. It only ever
+// touches finished pixels, so nothing drawn before it knows it's there.
+
+use super::*;
+
+const CURVATURE: f64 = 0.04; // Share of the playfield the outermost strips lose
+const STRIP_PIXELS: f64 = 4.0; // Canvas pixels per strip
+const SCANLINE_COLOR: &str = "rgba(0, 0, 0, 0.25)";
+const MIN_SCANLINE_PITCH: f64 = 2.0; // Canvas pixels from one scanline to the next, at least
+const VIGNETTE_COLOR: &str = "rgba(0, 0, 0, 0.5)"; // What the corners fade to
+
+// A box on the canvas, in canvas pixels
+#[derive(Clone, Copy)]
+struct Area {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+pub(crate) struct CrtPass {
+    buffer: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+}
+
+impl CrtPass {
+    pub(crate) fn new() -> Result<Self, JsValue> {
+        let buffer = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .create_element("canvas")?
+            .dyn_into::<HtmlCanvasElement>()?;
+        let context = buffer
+            .get_context("2d")?
+            .unwrap()
+            .dyn_into::<CanvasRenderingContext2d>()?;
+        Ok(CrtPass { buffer, context })
+    }
+
+    // Called once the frame is drawn and the viewport has put the context back
+    pub(crate) fn apply(&self, target: &CanvasRenderingContext2d, view: Viewport) {
+        let Some(canvas) = target.canvas() else {
+            return;
+        };
+        let (x, y, width, height) = view.playfield_pixels();
+        let area = Area {
+            x,
+            y,
+            width,
+            height,
+        };
+        // Copying from an empty canvas throws
+        if area.width < 1.0 || area.height < 1.0 {
+            return;
+        }
+        self.bulge(target, &canvas, area, true);
+        self.bulge(target, &canvas, area, false);
+        draw_scanlines(target, area, view.pixels_per_unit());
+        draw_vignette(target, area);
+    }
+
+    // Takes a copy of the playfield, then puts it back a strip at a time,
+    // rows when `across` and columns otherwise, pinching each toward the
+    // middle by how far it is from the center
+    fn bulge(
+        &self,
+        target: &CanvasRenderingContext2d,
+        canvas: &HtmlCanvasElement,
+        area: Area,
+        across: bool,
+    ) {
+        let Area {
+            x,
+            y,
+            width,
+            height,
+        } = area;
+        let (buffer_width, buffer_height) = (width.ceil() as u32, height.ceil() as u32);
+        if self.buffer.width() != buffer_width || self.buffer.height() != buffer_height {
+            self.buffer.set_width(buffer_width);
+            self.buffer.set_height(buffer_height);
+        }
+        self.context
+            .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                canvas, x, y, width, height, 0.0, 0.0, width, height,
+            )
+            .unwrap();
+        target.set_fill_style_str("#000");
+        target.fill_rect(x, y, width, height);
+
+        let (length, breadth) = if across {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let mut at = 0.0;
+        while at < length {
+            let strip = STRIP_PIXELS.min(length - at);
+            let from_center = (at + strip / 2.0) / length * 2.0 - 1.0; // -1 to 1
+            let size = breadth * (1.0 - CURVATURE * from_center * from_center);
+            let inset = (breadth - size) / 2.0;
+            let (sx, sy, sw, sh, dx, dy, dw, dh) = if across {
+                (0.0, at, width, strip, x + inset, y + at, size, strip)
+            } else {
+                (at, 0.0, strip, height, x + at, y + inset, strip, size)
+            };
+            target
+                .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    &self.buffer,
+                    sx,
+                    sy,
+                    sw,
+                    sh,
+                    dx,
+                    dy,
+                    dw,
+                    dh,
+                )
+                .unwrap();
+            at += strip;
+        }
+    }
+}
+
+// A dark line under every row of logical pixels, or every other canvas pixel
+// when they're too small to tell apart
+fn draw_scanlines(target: &CanvasRenderingContext2d, area: Area, pixels_per_unit: f64) {
+    let pitch = pixels_per_unit.max(MIN_SCANLINE_PITCH);
+    target.begin_path();
+    let mut line_y = area.y + pitch / 2.0;
+    while line_y < area.y + area.height {
+        target.rect(area.x, line_y.floor(), area.width, (pitch / 2.0).floor());
+        line_y += pitch;
+    }
+    target.set_fill_style_str(SCANLINE_COLOR);
+    target.fill();
+}
+
+// Clear in the middle, darkening toward the corners
+fn draw_vignette(target: &CanvasRenderingContext2d, area: Area) {
+    let center_x = area.x + area.width / 2.0;
+    let center_y = area.y + area.height / 2.0;
+    let corner = area.width.hypot(area.height) / 2.0;
+    let Ok(gradient) =
+        target.create_radial_gradient(center_x, center_y, corner * 0.5, center_x, center_y, corner)
+    else {
+        return;
+    };
+    gradient.add_color_stop(0.0, "rgba(0, 0, 0, 0)").unwrap();
+    gradient.add_color_stop(1.0, VIGNETTE_COLOR).unwrap();
+    target.set_fill_style_canvas_gradient(&gradient);
+    target.fill_rect(area.x, area.y, area.width, area.height);
+}
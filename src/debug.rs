@@ -0,0 +1,193 @@
+// Developer views, built with `--features debug-overlay`: F3 toggles a
+// readout of the numbers behind the difficulty curve, F4 a heatmap of where
+// catches and hits have happened this session.
+//
+// Time controls live here too: F6 cycles the simulation speed, F7 pauses and
+// F8 advances a paused game by a single tick.
+
+use super::*;
+use std::cell::Cell;
+
+const TIME_SCALES: [f64; 4] = [1.0, 0.5, 0.25, 2.0];
+
+#[derive(Default)]
+pub(crate) struct DebugViews {
+    overlay: Cell<bool>,
+    heatmap: Cell<bool>,
+    time_scale: Cell<usize>, // Index into TIME_SCALES
+    paused: Cell<bool>,
+    pending_steps: Cell<u32>, // F8 presses not yet run
+}
+
+impl DebugViews {
+    pub(crate) fn draw(&self, ctx: &dyn Renderer, state: &GameState) {
+        if self.heatmap.get() {
+            draw_heatmap(ctx, &state.heatmap, state.world);
+        }
+        if self.overlay.get() {
+            draw_overlay(ctx, state);
+        }
+        self.draw_time_control(ctx, state.world);
+    }
+
+    // How many ticks to simulate this frame, honoring speed and pause on top
+    // of the game's own `time_scale`
+    pub(crate) fn ticks(&self, scheduler: &mut FixedStep, now_ms: f64, time_scale: f64) -> u32 {
+        if self.paused.get() {
+            // Keep the scheduler's clock current so unpausing doesn't jump
+            scheduler.advance(now_ms, 0.0);
+            return self.pending_steps.replace(0);
+        }
+        scheduler.advance(now_ms, TIME_SCALES[self.time_scale.get()] * time_scale)
+    }
+
+    fn draw_time_control(&self, ctx: &dyn Renderer, world: WorldConfig) {
+        let scale = TIME_SCALES[self.time_scale.get()];
+        let label = if self.paused.get() {
+            "PAUSED  F8: step".to_string()
+        } else if scale != 1.0 {
+            format!("{scale}x speed")
+        } else {
+            return;
+        };
+        ctx.text(
+            &label,
+            world.width - 4.0,
+            world.height - 4.0,
+            8.0,
+            "#ff0",
+            Align::Right,
+        );
+    }
+}
+
+pub(crate) fn setup_listeners() -> Result<Rc<DebugViews>, JsValue> {
+    let views = Rc::new(DebugViews::default());
+    let window = web_sys::window().unwrap();
+
+    let toggles = views.clone();
+    let keydown = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        match event.code().as_str() {
+            "F3" => toggles.overlay.set(!toggles.overlay.get()),
+            "F4" => toggles.heatmap.set(!toggles.heatmap.get()),
+            "F6" => {
+                let next = (toggles.time_scale.get() + 1) % TIME_SCALES.len();
+                toggles.time_scale.set(next);
+            }
+            "F7" => toggles.paused.set(!toggles.paused.get()),
+            "F8" => {
+                // Stepping from a running game pauses it first
+                if toggles.paused.replace(true) {
+                    toggles.pending_steps.set(toggles.pending_steps.get() + 1);
+                }
+            }
+            _ => return,
+        }
+        event.prevent_default();
+    }) as Box<dyn FnMut(_)>);
+    window.add_event_listener_with_callback("keydown", keydown.as_ref().unchecked_ref())?;
+    keydown.forget();
+
+    Ok(views)
+}
+
+// Average time per profiled span, once there are any
+#[cfg(feature = "profiling")]
+fn timings_line() -> Option<String> {
+    let averages = profile::averages();
+    if averages.is_empty() {
+        return None;
+    }
+    let timings: Vec<String> = averages
+        .iter()
+        .map(|(name, ms)| format!("{name} {ms:.2}"))
+        .collect();
+    Some(format!("{} ms", timings.join("  ")))
+}
+
+#[cfg(not(feature = "profiling"))]
+fn timings_line() -> Option<String> {
+    None
+}
+
+// Catches in green and hits in red, each scaled against its own busiest cell
+fn draw_heatmap(ctx: &dyn Renderer, heatmap: &Heatmap, world: WorldConfig) {
+    for (cells, color) in [(&heatmap.catches, "#00ff00"), (&heatmap.hits, "#ff0000")] {
+        let busiest = cells.iter().copied().max().unwrap_or(0);
+        if busiest == 0 {
+            continue;
+        }
+        for (i, &count) in cells.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            ctx.set_alpha(0.1 + 0.5 * count as f64 / busiest as f64);
+            ctx.rect(
+                (i % heatmap.columns) as f64 * HEATMAP_CELL,
+                (i / heatmap.columns) as f64 * HEATMAP_CELL,
+                HEATMAP_CELL,
+                HEATMAP_CELL,
+                color,
+            );
+        }
+    }
+    ctx.set_alpha(1.0);
+
+    let catches: u32 = heatmap.catches.iter().sum();
+    let hits: u32 = heatmap.hits.iter().sum();
+    ctx.text(
+        &format!("heatmap: {catches} catches, {hits} hits"),
+        4.0,
+        world.height - 14.0,
+        8.0,
+        "#fff",
+        Align::Left,
+    );
+}
+
+fn draw_overlay(ctx: &dyn Renderer, state: &GameState) {
+    let performance = &state.performance;
+    let accuracy = performance.accuracy().map_or("--".to_string(), |accuracy| {
+        format!("{:.0}%", accuracy * 100.0)
+    });
+    let dda = if state.settings.adaptive_difficulty {
+        format!("{:+.2}", state.dda_level)
+    } else {
+        "off".to_string()
+    };
+    let warm_up = &state.config.warm_up;
+    let lines: Vec<String> = [
+        format!("wave {}  t {}", state.wave, state.wave_timer),
+        format!("difficulty {:.2}", state.difficulty_multiplier),
+        format!(
+            "warm-up fall x{:.2}  spawn x{:.2}  grace {:.2}",
+            warm_up.scale(warm_up.fall_speed, state.frame_count),
+            warm_up.scale(warm_up.spawn_rate, state.frame_count),
+            state.grace()
+        ),
+        format!(
+            "objects {}  queued {}",
+            state.objects.len(),
+            state.pending_spawns.len()
+        ),
+        format!("dda {dda}  skill {:+.2}", performance.skill()),
+        format!(
+            "spawn x{:.2}  deals x{:.2}",
+            state.dda_spawn_rate(),
+            state.dda_deal_odds()
+        ),
+        format!(
+            "accuracy {accuracy}  hits {}",
+            performance.count(PerfEvent::HitTaken)
+        ),
+    ]
+    .into_iter()
+    .chain(timings_line())
+    .collect();
+
+    let height = 8.0 + lines.len() as f64 * 10.0;
+    ctx.rect(4.0, 30.0, 180.0, height, "rgba(0, 0, 0, 0.7)");
+    for (i, line) in lines.iter().enumerate() {
+        ctx.text(line, 8.0, 40.0 + i as f64 * 10.0, 8.0, "#0f0", Align::Left);
+    }
+}
@@ -0,0 +1,62 @@
+// Objects on their way out: whatever's caught, bursts on the floor or sinks
+// off the bottom of the screen is moved to a list of dying objects rather
+// than dropped, and shrinks and fades there for a few frames, so nothing
+// blinks out of existence between one frame and the next. Dying objects are
+// only drawn; nothing collides with them or lands them again.
+
+use super::*;
+
+const DESPAWN_FRAMES: u32 = 10;
+const DESPAWN_SHRINK: f64 = 0.6; // Share of its size an object has lost by the last frame
+
+#[derive(Clone)]
+pub(crate) struct DyingObject {
+    object: FallingObject,
+    age: u32,
+}
+
+impl DyingObject {
+    fn progress(&self) -> f64 {
+        self.age as f64 / DESPAWN_FRAMES as f64
+    }
+
+    // The object as it's drawn this frame, shrunk about its center
+    fn shrunk(&self) -> FallingObject {
+        let scale = 1.0 - DESPAWN_SHRINK * self.progress();
+        let mut object = self.object.clone();
+        object.width *= scale;
+        object.height *= scale;
+        object.x += (self.object.width - object.width) / 2.0;
+        object.y += (self.object.height - object.height) / 2.0;
+        object
+    }
+}
+
+impl GameState {
+    pub(crate) fn despawn(&mut self, object: FallingObject) {
+        self.dying.push(DyingObject { object, age: 0 });
+    }
+
+    pub(crate) fn update_despawns(&mut self) {
+        for dying in &mut self.dying {
+            dying.age += 1;
+        }
+        self.dying.retain(|dying| dying.age < DESPAWN_FRAMES);
+    }
+}
+
+// Each one drawn as its behavior draws it, smaller and fainter every frame
+pub(crate) fn draw_despawns(ctx: &dyn Renderer, state: &GameState) {
+    for dying in &state.dying {
+        let object = dying.shrunk();
+        let def = state.config.objects.get(object.kind);
+        let mut sprites = Vec::new();
+        state
+            .behaviors
+            .get(object.obj_type)
+            .draw(&mut sprites, &object, def);
+        ctx.set_alpha(1.0 - dying.progress());
+        ctx.sprites(&sprites);
+    }
+    ctx.set_alpha(1.0);
+}
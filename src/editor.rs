@@ -0,0 +1,398 @@
+// Wave script editor, built with `--features editor`.
+//
+// The aisle doubles as a timeline: x is where an object drops, and the
+// height on screen is when it drops, from the start of the wave at the top
+// to the end of the wave at the floor.
+
+use super::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::MouseEvent;
+
+const TIMELINE_TOP: f64 = 24.0;
+const FRAME_STEP: u32 = 5;
+const X_STEP: f64 = 5.0;
+const STATUS_LIFETIME: u32 = 120;
+
+pub(crate) enum EditorCommand {
+    PlaceAtCursor,
+    PlaceAt { x: f64, y: f64 }, // Canvas coordinates of a mouse click
+    CycleObject,
+    TogglePreview,
+    ToggleRandomSpawns,
+    ChangeWave(i32),
+    Export,
+    Exit,
+}
+
+// Plays the script on its own so the designer can watch it fall.
+struct Preview {
+    frame: u32,
+    objects: Vec<FallingObject>,
+}
+
+pub(crate) struct Editor {
+    script: WaveScript,
+    cursor_x: f64,
+    cursor_frame: u32,
+    object: usize, // Kind placed by the next click
+    preview: Option<Preview>,
+    status: Option<(String, u32)>,
+}
+
+fn frame_to_y(frame: u32, world: WorldConfig) -> f64 {
+    let floor_y = world.floor_y();
+    TIMELINE_TOP + frame as f64 / WAVE_LENGTH_FRAMES as f64 * (floor_y - TIMELINE_TOP)
+}
+
+fn y_to_frame(y: f64, world: WorldConfig) -> u32 {
+    let t = ((y - TIMELINE_TOP) / (world.floor_y() - TIMELINE_TOP)).clamp(0.0, 1.0);
+    snap_frame((t * WAVE_LENGTH_FRAMES as f64) as u32)
+}
+
+fn snap_frame(frame: u32) -> u32 {
+    (frame / FRAME_STEP * FRAME_STEP).min(WAVE_LENGTH_FRAMES - FRAME_STEP)
+}
+
+fn snap_x(x: f64, width: f64, world: WorldConfig) -> f64 {
+    ((x / X_STEP).round() * X_STEP).clamp(0.0, world.width - width)
+}
+
+// Editor keys go through their own queue so the shared keyboard state stays
+// limited to what the cabinet has.
+pub(crate) fn setup_listeners(
+    canvas: &HtmlCanvasElement,
+    viewport: Rc<Cell<Viewport>>,
+) -> Result<Rc<RefCell<Vec<EditorCommand>>>, JsValue> {
+    let commands = Rc::new(RefCell::new(Vec::new()));
+    let window = web_sys::window().unwrap();
+
+    {
+        let commands = commands.clone();
+        let keydown = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+            let command = match event.code().as_str() {
+                "KeyO" => EditorCommand::CycleObject,
+                "KeyP" => EditorCommand::TogglePreview,
+                "KeyR" => EditorCommand::ToggleRandomSpawns,
+                "BracketLeft" => EditorCommand::ChangeWave(-1),
+                "BracketRight" => EditorCommand::ChangeWave(1),
+                "KeyE" => EditorCommand::Export,
+                "Escape" => EditorCommand::Exit,
+                _ => return,
+            };
+            commands.borrow_mut().push(command);
+        }) as Box<dyn FnMut(_)>);
+        window.add_event_listener_with_callback("keydown", keydown.as_ref().unchecked_ref())?;
+        keydown.forget();
+    }
+
+    {
+        let commands = commands.clone();
+        let mousedown = Closure::wrap(Box::new(move |event: MouseEvent| {
+            let (x, y) = viewport
+                .get()
+                .logical_point(event.offset_x() as f64, event.offset_y() as f64);
+            commands.borrow_mut().push(EditorCommand::PlaceAt { x, y });
+        }) as Box<dyn FnMut(_)>);
+        canvas.add_event_listener_with_callback("mousedown", mousedown.as_ref().unchecked_ref())?;
+        mousedown.forget();
+    }
+
+    Ok(commands)
+}
+
+impl GameState {
+    pub(crate) fn open_editor(&mut self) {
+        self.phase = GamePhase::Editor(Box::new(Editor {
+            script: self.script_for_wave(1),
+            cursor_x: self.world.width / 2.0,
+            cursor_frame: 0,
+            object: 0,
+            preview: None,
+            status: None,
+        }));
+    }
+
+    fn script_for_wave(&self, wave: u32) -> WaveScript {
+        match self.config.waves.for_wave(wave) {
+            Some(index) => self.config.waves.scripts[index].clone(),
+            None => WaveScript {
+                wave,
+                name: String::new(),
+                random_spawns: true,
+                spawns: Vec::new(),
+            },
+        }
+    }
+
+    // Writes the script being edited back so the next run plays it
+    fn store_edited_script(&mut self) {
+        let GamePhase::Editor(editor) = &self.phase else {
+            return;
+        };
+        let script = editor.script.clone();
+        let scripts = &mut self.config.waves.scripts;
+        scripts.retain(|existing| existing.wave != script.wave);
+        if !script.spawns.is_empty() {
+            scripts.push(script);
+            scripts.sort_by_key(|script| script.wave);
+        }
+    }
+
+    pub(crate) fn handle_editor(&mut self, cursor: &[Command], commands: Vec<EditorCommand>) {
+        // Left/right moves along the aisle; up/down scrubs time
+        if let GamePhase::Editor(editor) = &mut self.phase {
+            let def = self.config.objects.get(editor.object);
+            let world = self.world;
+            for command in cursor {
+                match command {
+                    Command::CursorLeft => {
+                        editor.cursor_x = snap_x(editor.cursor_x - X_STEP, def.width, world);
+                    }
+                    Command::CursorRight => {
+                        editor.cursor_x = snap_x(editor.cursor_x + X_STEP, def.width, world);
+                    }
+                    Command::CursorUp => {
+                        editor.cursor_frame =
+                            snap_frame(editor.cursor_frame.saturating_sub(FRAME_STEP));
+                    }
+                    Command::CursorDown => {
+                        editor.cursor_frame = snap_frame(editor.cursor_frame + FRAME_STEP);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for command in commands {
+            self.apply_editor_command(command);
+        }
+
+        if let GamePhase::Editor(editor) = &mut self.phase {
+            if let Some((_, remaining)) = &mut editor.status {
+                *remaining = remaining.saturating_sub(1);
+                if *remaining == 0 {
+                    editor.status = None;
+                }
+            }
+            let difficulty =
+                1.0 + self.tunables.difficulty_per_wave * (editor.script.wave - 1) as f64;
+            let time_scale = difficulty * self.tunables.fall_speed;
+            if let Some(preview) = &mut editor.preview {
+                preview.frame += 1;
+                for spawn in &editor.script.spawns {
+                    if spawn.frame + SPAWN_TELEGRAPH_FRAMES == preview.frame {
+                        let def = self.config.objects.get(spawn.kind);
+                        let x = spawn.x.clamp(0.0, self.world.width - def.width);
+                        preview
+                            .objects
+                            .push(FallingObject::dropped(x, spawn.kind, def));
+                    }
+                }
+                for obj in &mut preview.objects {
+                    obj.step(time_scale);
+                }
+                let world = self.world;
+                preview.objects.retain(|obj| !obj.is_off_screen(world));
+                if preview.frame > WAVE_LENGTH_FRAMES + SPAWN_TELEGRAPH_FRAMES
+                    && preview.objects.is_empty()
+                {
+                    editor.preview = None;
+                }
+            }
+        }
+    }
+
+    fn apply_editor_command(&mut self, command: EditorCommand) {
+        match command {
+            EditorCommand::Exit => {
+                self.store_edited_script();
+                self.back_to_menu();
+            }
+            EditorCommand::ChangeWave(delta) => {
+                self.store_edited_script();
+                let GamePhase::Editor(editor) = &self.phase else {
+                    return;
+                };
+                let wave = (editor.script.wave as i32 + delta).max(1) as u32;
+                let script = self.script_for_wave(wave);
+                if let GamePhase::Editor(editor) = &mut self.phase {
+                    editor.script = script;
+                    editor.preview = None;
+                }
+            }
+            EditorCommand::Export => {
+                self.store_edited_script();
+                self.export_wave_scripts();
+            }
+            command => {
+                if let GamePhase::Editor(editor) = &mut self.phase {
+                    edit_script(editor, &self.config, command);
+                }
+            }
+        }
+    }
+
+    // Copies every wave script as JSON, ready to paste into assets/waves.json
+    fn export_wave_scripts(&mut self) {
+        let json = serde_json::to_string_pretty(&self.config.waves.scripts)
+            .expect("wave scripts serialize");
+        let clipboard = web_sys::window().unwrap().navigator().clipboard();
+        let write = clipboard.write_text(&json);
+        spawn_local(async move {
+            if JsFuture::from(write).await.is_err() {
+                log(LogLevel::Warn, "editor", "clipboard write failed");
+            }
+        });
+        if let GamePhase::Editor(editor) = &mut self.phase {
+            editor.status = Some((
+                format!("Copied {} wave scripts", self.config.waves.scripts.len()),
+                STATUS_LIFETIME,
+            ));
+        }
+    }
+}
+
+// Commands that only touch the editor itself
+fn edit_script(editor: &mut Editor, config: &GameConfig, command: EditorCommand) {
+    let objects = &config.objects;
+    match command {
+        EditorCommand::PlaceAtCursor => {
+            let (x, frame) = (editor.cursor_x, editor.cursor_frame);
+            toggle_spawn(editor, objects, x, frame);
+        }
+        EditorCommand::PlaceAt { x, y } => {
+            let def = objects.get(editor.object);
+            editor.cursor_x = snap_x(x - def.width / 2.0, def.width, config.world);
+            editor.cursor_frame = y_to_frame(y, config.world);
+            let (x, frame) = (editor.cursor_x, editor.cursor_frame);
+            toggle_spawn(editor, objects, x, frame);
+        }
+        EditorCommand::CycleObject => {
+            editor.object = (editor.object + 1) % objects.defs.len();
+        }
+        EditorCommand::TogglePreview => {
+            editor.preview = match editor.preview {
+                Some(_) => None,
+                None => Some(Preview {
+                    frame: 0,
+                    objects: Vec::new(),
+                }),
+            };
+        }
+        EditorCommand::ToggleRandomSpawns => {
+            editor.script.random_spawns = !editor.script.random_spawns;
+        }
+        EditorCommand::ChangeWave(_) | EditorCommand::Export | EditorCommand::Exit => {}
+    }
+}
+
+// Removes a spawn sitting under the cursor, or places the selected object there
+fn toggle_spawn(editor: &mut Editor, objects: &ObjectCatalog, x: f64, frame: u32) {
+    let spawns = &mut editor.script.spawns;
+    if let Some(index) = spawns
+        .iter()
+        .position(|spawn| spawn.frame == frame && (spawn.x - x).abs() < X_STEP * 2.0)
+    {
+        spawns.remove(index);
+        return;
+    }
+    let def = objects.get(editor.object);
+    spawns.push(ScriptedSpawn {
+        frame,
+        x,
+        object: def.id.clone(),
+        kind: editor.object,
+    });
+    spawns.sort_by_key(|spawn| spawn.frame);
+}
+
+pub(crate) fn draw(ctx: &dyn Renderer, state: &GameState) {
+    let GamePhase::Editor(editor) = &state.phase else {
+        return;
+    };
+    let objects = &state.config.objects;
+    let world = state.world;
+
+    // One guide line per second of the wave
+    for second in 0..=WAVE_LENGTH_FRAMES / 60 {
+        let y = frame_to_y(second * 60, world);
+        ctx.rect(0.0, y, world.width, 1.0, "#2a2a2a");
+        ctx.text(
+            &format!("{second}s"),
+            2.0,
+            y - 1.0,
+            8.0,
+            "#666",
+            Align::Left,
+        );
+    }
+    ctx.rect(0.0, world.floor_y(), world.width, 1.0, "#444");
+
+    // Placed spawns, dimmed while the preview plays over them
+    ctx.set_alpha(if editor.preview.is_some() { 0.3 } else { 1.0 });
+    let markers: Vec<_> = editor
+        .script
+        .spawns
+        .iter()
+        .map(|spawn| {
+            let def = objects.get(spawn.kind);
+            let mut marker = FallingObject::dropped(spawn.x, spawn.kind, def);
+            marker.y = frame_to_y(spawn.frame, world) - def.height / 2.0;
+            SpriteDraw::of(&marker, &def.sprite)
+        })
+        .collect();
+    ctx.sprites(&markers);
+    ctx.set_alpha(1.0);
+
+    if let Some(preview) = &editor.preview {
+        let y = frame_to_y(preview.frame.min(WAVE_LENGTH_FRAMES), world);
+        ctx.rect(0.0, y, world.width, 1.0, "#ffd700");
+        let sprites: Vec<_> = preview
+            .objects
+            .iter()
+            .map(|obj| SpriteDraw::of(obj, &objects.get(obj.kind).sprite))
+            .collect();
+        ctx.sprites(&sprites);
+    } else {
+        // Ghost of the selected object under the cursor
+        let def = objects.get(editor.object);
+        let mut ghost = FallingObject::dropped(editor.cursor_x, editor.object, def);
+        ghost.y = frame_to_y(editor.cursor_frame, world) - def.height / 2.0;
+        ctx.set_alpha(0.5);
+        ctx.sprites(&[SpriteDraw::of(&ghost, &def.sprite)]);
+        ctx.set_alpha(1.0);
+        ctx.stroke_rect(ghost.x, ghost.y, def.width, def.height, "#fff", 1.0);
+    }
+
+    let random = if editor.script.random_spawns {
+        "on"
+    } else {
+        "off"
+    };
+    ctx.text(
+        &format!(
+            "EDITOR wave {}  {}  {} spawns  random {}",
+            editor.script.wave,
+            objects.get(editor.object).id,
+            editor.script.spawns.len(),
+            random
+        ),
+        5.0,
+        12.0,
+        10.0,
+        "#fff",
+        Align::Left,
+    );
+
+    if let Some((message, _)) = &editor.status {
+        ctx.text(message, 5.0, 240.0, 8.0, "#7fff7f", Align::Left);
+    } else {
+        let help = [
+            "A/click: place  1P/O: object  2P/P: preview",
+            "[ ]: wave  R: random  E: export  Esc: exit",
+        ];
+        for (i, line) in help.iter().enumerate() {
+            ctx.text(line, 5.0, 240.0 + i as f64 * 9.0, 8.0, "#aaa", Align::Left);
+        }
+    }
+}
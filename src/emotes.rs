@@ -0,0 +1,135 @@
+// Emotes for the players at a shared cabinet: hold A, then push a direction,
+// and a speech bubble pops up over your player for a second. P1's stick has
+// four to pick from, P2's left and right two. They're only for show: read
+// straight off the buttons each frame, never part of a run's inputs, so they
+// don't reach replays or the other side of an online game. A setting turns
+// them off for tournament runs.
+//
+// A is also the versus throw, but that goes on a short tap; by the time a
+// hold can pick an emote it's too long to throw.
+
+use super::*;
+
+const EMOTE_FRAMES: u32 = 60;
+const BUBBLE_SIZE: f64 = 20.0;
+
+// By direction: left, right, up, down
+const EMOTES: [&str; 4] = ["\u{1f61c}", "\u{1f60e}", "\u{1f44d}", "\u{1f631}"];
+
+struct Emote {
+    player_index: usize,
+    symbol: &'static str,
+    age: u32,
+}
+
+#[derive(Default)]
+pub(crate) struct EmoteWheel {
+    last: InputSnapshot,
+    shown: Vec<Emote>,
+}
+
+// A player's A, then their directions in EMOTES order
+fn emote_buttons(inputs: &InputSnapshot, player_index: usize) -> (bool, [bool; 4]) {
+    if player_index == 0 {
+        (
+            inputs.player1_a,
+            [
+                inputs.player1_left,
+                inputs.player1_right,
+                inputs.player1_up,
+                inputs.player1_down,
+            ],
+        )
+    } else {
+        (
+            inputs.player2_a,
+            [inputs.player2_left, inputs.player2_right, false, false],
+        )
+    }
+}
+
+impl GameState {
+    fn emotes_allowed(&self) -> bool {
+        matches!(self.phase, GamePhase::Playing)
+            && !self.in_demo()
+            && (self.tournament.is_none() || self.settings.tournament_emotes)
+    }
+
+    // Called once a frame with the raw buttons
+    pub(crate) fn update_emotes(&mut self, inputs: &InputSnapshot) {
+        let allowed = self.emotes_allowed();
+        let bot_index = self.bot.as_ref().map(|bot| bot.player_index);
+        // Held past a tap, going by the ticks the run has counted, so a
+        // press is never both a throw and an emote
+        let holding: Vec<usize> = self
+            .players
+            .iter()
+            .filter(|slot| slot.health > 0 && Some(slot.player_index) != bot_index)
+            .filter(|slot| slot.a_held > THROW_TAP_TICKS)
+            .map(|slot| slot.player_index)
+            .collect();
+        let wheel = &mut self.emotes;
+        for emote in &mut wheel.shown {
+            emote.age += 1;
+        }
+        wheel
+            .shown
+            .retain(|emote| allowed && emote.age < EMOTE_FRAMES);
+
+        for player_index in 0..2 {
+            let (a, directions) = emote_buttons(inputs, player_index);
+            let (_, before) = emote_buttons(&wheel.last, player_index);
+            if !allowed || !a || !holding.contains(&player_index) {
+                continue;
+            }
+            let picked = (0..EMOTES.len()).find(|&i| directions[i] && !before[i]);
+            if let Some(i) = picked {
+                wheel
+                    .shown
+                    .retain(|emote| emote.player_index != player_index);
+                wheel.shown.push(Emote {
+                    player_index,
+                    symbol: EMOTES[i],
+                    age: 0,
+                });
+            }
+        }
+        wheel.last = *inputs;
+    }
+}
+
+// A bubble over each emoting player, following them as they move
+pub(crate) fn draw_emotes(ctx: &dyn Renderer, state: &GameState) {
+    for emote in &state.emotes.shown {
+        let Some(slot) = state
+            .players
+            .iter()
+            .find(|slot| slot.player_index == emote.player_index)
+        else {
+            continue;
+        };
+        let player = &state.drawn_player(slot);
+        let x = player.x + player.width + 2.0;
+        let y = player.y - BUBBLE_SIZE - 8.0;
+        let fade = EMOTE_FRAMES - emote.age;
+        ctx.set_alpha((fade as f64 / 10.0).min(1.0));
+        ctx.rect(x, y, BUBBLE_SIZE, BUBBLE_SIZE, "#fff");
+        ctx.polygon(
+            &[
+                (x + 3.0, y + BUBBLE_SIZE),
+                (x + 9.0, y + BUBBLE_SIZE),
+                (x, y + BUBBLE_SIZE + 6.0),
+            ],
+            "#fff",
+        );
+        ctx.text(
+            emote.symbol,
+            x + BUBBLE_SIZE / 2.0,
+            y + BUBBLE_SIZE - 5.0,
+            13.0,
+            "#000",
+            Align::Center,
+        );
+    }
+    ctx.set_alpha(1.0);
+}
@@ -0,0 +1,162 @@
+// Things that happened during a tick. Gameplay code only queues them; the
+// systems that care (stats, difficulty tracking, the heatmap, the ticker,
+// the end of the run) pick them up in dispatch_events once the tick is done.
+
+use super::*;
+
+pub(crate) enum GameEvent {
+    // A deal landed in a player's hands
+    Caught {
+        player_index: usize,
+        x: f64,
+        y: f64,
+        perfect: bool, // Dead center
+    },
+    // A hazard got through to a player
+    Hit {
+        player_index: usize,
+        kind: usize, // What hit them
+        x: f64,
+        y: f64,
+    },
+    // A deal reached the floor, where it can't be caught any more; charged to
+    // the nearest living player
+    DealMissed {
+        x: f64,
+    },
+    // A hazard reached the floor; credited to every living player
+    HazardAvoided,
+    // A hazard landed after brushing past a player
+    NearMiss {
+        player_index: usize,
+        x: f64,
+    },
+    // Out of health; the player leaves play once this is handled
+    PlayerDied {
+        player_index: usize,
+    },
+    WaveStarted {
+        wave: u32,
+    },
+    // Every player is out
+    GameOver,
+}
+
+impl GameState {
+    pub(crate) fn dispatch_events(&mut self) {
+        while let Some(event) = self.events.pop_front() {
+            match event {
+                GameEvent::Caught {
+                    player_index,
+                    x,
+                    y,
+                    perfect,
+                } => {
+                    let mut combo = 0;
+                    if let Some(slot) = self.player_mut(player_index) {
+                        slot.stats.deals_caught += 1;
+                        slot.misses_in_a_row = 0;
+                        if perfect {
+                            slot.stats.perfect_catches += 1;
+                        }
+                        combo = slot.combo;
+                    }
+                    if perfect {
+                        self.check_achievements(player_index);
+                    }
+                    // Catches that push the multiplier up are the big ones
+                    if self.config.scoring.combo_breakpoints.contains(&combo) {
+                        self.zoom_punch(CATCH_PUNCH, x, y);
+                    }
+                    self.performance
+                        .record(self.frame_count, PerfEvent::DealCaught);
+                    self.heatmap.record(PerfEvent::DealCaught, x, y);
+                }
+                GameEvent::Hit {
+                    player_index,
+                    kind,
+                    x,
+                    y,
+                } => {
+                    self.keep_hazard(player_index, kind);
+                    let mut health = 0;
+                    if let Some(slot) = self.player_mut(player_index) {
+                        slot.stats.hits_taken += 1;
+                        health = slot.health;
+                    }
+                    if self.one_hit_from_out(health) {
+                        self.start_clutch();
+                    }
+                    self.performance
+                        .record(self.frame_count, PerfEvent::HitTaken);
+                    self.heatmap.record(PerfEvent::HitTaken, x, y);
+                }
+                GameEvent::DealMissed { x } => {
+                    self.performance
+                        .record(self.frame_count, PerfEvent::DealMissed);
+                    let nearest = self
+                        .players
+                        .iter_mut()
+                        .filter(|slot| slot.health > 0)
+                        .min_by(|a, b| {
+                            let distance = |slot: &PlayerSlot| {
+                                (slot.player.x + slot.player.width / 2.0 - x).abs()
+                            };
+                            distance(a).total_cmp(&distance(b))
+                        });
+                    if let Some(slot) = nearest {
+                        slot.stats.deals_missed += 1;
+                        let player_index = slot.player_index;
+                        self.punish_miss(player_index, x);
+                    }
+                }
+                GameEvent::HazardAvoided => {
+                    for slot in self.players.iter_mut().filter(|slot| slot.health > 0) {
+                        slot.stats.hits_avoided += 1;
+                    }
+                }
+                GameEvent::NearMiss { player_index, x } => {
+                    self.reward_near_miss(player_index, x);
+                }
+                GameEvent::PlayerDied { player_index } => {
+                    self.record_personal_best(player_index);
+                    let time_survived_ms = self.run_time_ms;
+                    let last_standing = self.players.iter().all(|slot| slot.health <= 0);
+                    if let Some(slot) = self.player_mut(player_index) {
+                        let result = RunResult {
+                            player_index,
+                            score: slot.score,
+                            time_survived_ms,
+                            stats: slot.stats,
+                            handicap: slot.handicap,
+                        };
+                        let player = &slot.player;
+                        let (x, y) = (player.x + player.width / 2.0, player.y);
+                        self.final_scores.push(result);
+                        if last_standing {
+                            self.zoom_punch(DEATH_PUNCH, x, y);
+                        }
+                    }
+                    self.players
+                        .retain(|slot| slot.player_index != player_index);
+                }
+                GameEvent::WaveStarted { wave } => {
+                    // A bonus round announces itself
+                    if matches!(self.playing_phase, PlayingPhase::Normal) {
+                        self.ticker
+                            .push(format!("Wave {wave} - prices are dropping faster!"));
+                    }
+                }
+                GameEvent::GameOver => self.start_name_entry(),
+            }
+        }
+    }
+
+    // Dead players are kept until the end of the tick, so events about them
+    // still find them.
+    pub(crate) fn player_mut(&mut self, player_index: usize) -> Option<&mut PlayerSlot> {
+        self.players
+            .iter_mut()
+            .find(|slot| slot.player_index == player_index)
+    }
+}
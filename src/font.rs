@@ -0,0 +1,47 @@
+// An arcade font for all the game's text in place of the browser's
+// monospace, when the page config names one:
+//
+//   "font": { "family": "Press Start 2P", "url": "fonts/press-start.woff2" }
+//
+// It's loaded through the FontFace API, and the canvas renderer holds text
+// back until it's ready, rather than drawing it in monospace for a moment
+// and then jumping. A font that fails to load, or takes too long, leaves the
+// text in monospace.
+
+use super::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::FontFace;
+
+#[derive(Clone, Deserialize)]
+pub(crate) struct FontSource {
+    pub(crate) family: String,
+    url: String,
+}
+
+impl FontSource {
+    // Loads the font and adds it to the page, reporting how that went in
+    // `status`. A failed font leaves text in monospace.
+    pub(crate) fn load(self, status: Preload) {
+        spawn_local(async move {
+            match self.fetch().await {
+                Ok(()) => status.set(LoadStatus::Ready),
+                Err(err) => {
+                    log(
+                        LogLevel::Warn,
+                        "font",
+                        &format!("{} unavailable, using monospace: {err:?}", self.family),
+                    );
+                    status.set(LoadStatus::Failed);
+                }
+            }
+        });
+    }
+
+    async fn fetch(&self) -> Result<(), JsValue> {
+        let font = FontFace::new_with_str(&self.family, &format!("url({})", self.url))?;
+        JsFuture::from(font.load()?).await?;
+        let document = web_sys::window().unwrap().document().unwrap();
+        document.fonts().add(&font)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,1949 @@
+// The game core: simulation state, spawning, collisions and the fixed-step update.
+
+use super::*;
+
+pub(crate) const PLAYER_WIDTH: f64 = 30.0;
+pub(crate) const PLAYER_HEIGHT: f64 = 30.0;
+const PLAYER_SPEED: f64 = 3.0;
+const TINY_PLAYER_SCALE: f64 = 0.5;
+pub(crate) const BIG_PIECES_SCALE: f64 = 1.5; // Players and objects with the big pieces setting on
+const PRACTICE_HEALTH: i32 = 30; // Starting health with the practice unlock on
+pub(crate) const OBJECT_WIDTH: f64 = 20.0;
+pub(crate) const OBJECT_HEIGHT: f64 = 20.0;
+
+// Base falling speed for objects. This will be scaled by difficulty.
+const OBJECT_SPEED: f64 = 3.0;
+
+// Thrown objects: diagonal throws come in from the top corners, lobs arc up
+// from the side walls and fall under gravity. Both appear from this wave on.
+const THROWN_OBJECTS_FROM_WAVE: u32 = 3;
+const DIAGONAL_THROW_CHANCE: f64 = 0.15;
+const LOB_CHANCE: f64 = 0.1;
+const DIAGONAL_SPEED_X: f64 = 1.2;
+const LOB_SPEED_X: f64 = 1.6;
+const LOB_SPEED_Y: f64 = -2.5;
+const LOB_GRAVITY: f64 = 0.06;
+
+// Wind: from this wave on, a sideways gust picks a new strength every few
+// seconds and the actual wind eases toward it. Stronger in later waves.
+const WIND_FROM_WAVE: u32 = 4;
+const WIND_CHANGE_INTERVAL: u32 = 300;
+const WIND_MAX_BASE: f64 = 0.4;
+const WIND_MAX_PER_WAVE: f64 = 0.05;
+const WIND_MAX_CAP: f64 = 1.0;
+const WIND_EASING: f64 = 0.02;
+const WIND_PARTICLE_CAP: usize = 40;
+
+// Base spawn interval in "difficulty ticks". Real spawn rate speeds up as difficulty rises,
+// but never past the floor, so it can't become *too* fast to be playable.
+const BASE_SPAWN_INTERVAL: f64 = 45.0;
+const MIN_SPAWN_INTERVAL: f64 = 10.0;
+
+// Grace period: a run opens with only deals, falling slowly, for as long as
+// the difficulty preset says, then hazards and full speed blend back in.
+const GRACE_FALL_SPEED: f64 = 0.6;
+const GRACE_BLEND_FRAMES: u32 = 120;
+
+// Objects are announced at the edge they'll enter from this many frames
+// before they actually appear.
+pub(crate) const SPAWN_TELEGRAPH_FRAMES: u32 = 30;
+
+// Floating text (score popups etc.) rises and fades over this many frames.
+pub(crate) const POPUP_LIFETIME: u32 = 40;
+
+// Bottom-of-screen news ticker. Messages scroll right to left one at a time;
+// older messages are dropped if too many pile up.
+const TICKER_SPEED: f64 = 1.2;
+const TICKER_CHAR_WIDTH: f64 = 5.0; // 8px monospace
+const TICKER_MAX_QUEUED: usize = 4;
+
+// Survival bonus: every 10 seconds of real (unpaused) play, each living
+// player earns a few points (see ScoringRules).
+pub(crate) const SURVIVAL_INTERVAL_MS: f64 = 10_000.0;
+
+// The simulation advances in fixed 60 Hz ticks whatever the display's refresh
+// rate. Frame gaps longer than the cap (tab switched away, debugger) are cut
+// short rather than caught up, and a stall, a gap far past it (a GC pause, the
+// tab hiccuping), only catches up a couple of ticks, so nothing on screen jumps.
+pub(crate) const TICK_MS: f64 = 1000.0 / 60.0;
+const MAX_FRAME_GAP_MS: f64 = 100.0;
+pub(crate) const STALL_GAP_MS: f64 = 250.0;
+const STALL_CATCH_UP_MS: f64 = TICK_MS * 2.0;
+
+// Each wave lasts ~10 seconds at 60 FPS and ends with a difficulty bump.
+const DIFFICULTY_PER_WAVE: f64 = 0.2;
+pub(crate) const WAVE_LENGTH_FRAMES: u32 = 600;
+
+// Adaptive difficulty (a setting) judges the last ~20 seconds of play and
+// nudges spawn rate and deal odds by up to these fractions either way. The
+// time-based ramp still applies on top.
+const DDA_WINDOW_FRAMES: u32 = 1200;
+const DDA_TARGET_ACCURACY: f64 = 0.6;
+const DDA_HIT_WEIGHT: f64 = 0.4; // Skill lost per recent hit
+const DDA_EASING: f64 = 0.01;
+const DDA_SPAWN_RATE_RANGE: f64 = 0.35;
+const DDA_DEAL_ODDS_RANGE: f64 = 0.4;
+
+// Every few waves the store runs a 15-second bonus round: only deals fall, in
+// dense patterns, and nothing can hurt you. Each catch grows a multiplier that
+// is applied to the round's haul when it ends.
+const BONUS_ROUND_EVERY_WAVES: u32 = 5;
+const BONUS_ROUND_DURATION: u32 = 900;
+const BONUS_SPAWN_INTERVAL: u32 = 12;
+const BONUS_POINTS_PER_CATCH: i32 = 10;
+const BONUS_MULTIPLIER_STEP: f64 = 0.1;
+const BONUS_ROUND_WARNING_FRAMES: u32 = 480;
+
+// Between waves spawning stops for a moment while the aisle just cleared is
+// tallied up: a bonus for every heart each player still has.
+const WAVE_CLEAR_FRAMES: u32 = 120;
+const WAVE_CLEAR_POINTS_PER_HEART: i32 = 10;
+
+// Energy drink: +60% movement speed while the effect lasts.
+const SPEED_BOOST_MULTIPLIER: f64 = 1.6;
+
+// Spilled soda leaves a patch on the floor that halves movement speed for ~8 seconds.
+const STICKY_PATCH_WIDTH: f64 = 40.0;
+const STICKY_PATCH_DURATION: u32 = 480;
+const STICKY_SLOW_FACTOR: f64 = 0.5;
+
+// "Lights out": once the game is hard enough, the store occasionally goes dark
+// except for a small circle around each player. A flicker warns it's coming.
+const BLACKOUT_MIN_DIFFICULTY: f64 = 2.0;
+const BLACKOUT_CHANCE_PER_FRAME: f64 = 1.0 / 1200.0;
+const BLACKOUT_WARNING_FRAMES: u32 = 60;
+const BLACKOUT_DURATION: u32 = 240;
+pub(crate) const BLACKOUT_LIGHT_RADIUS: f64 = 40.0;
+
+// How many past positions to keep for the motion trail while boosted.
+pub(crate) const TRAIL_LENGTH: usize = 6;
+
+// A status effect on a single player that wears off after a number of frames.
+#[derive(Clone)]
+struct TimedEffect {
+    kind: EffectKind,
+    remaining: u32,
+}
+
+// Progress through the current wave's script, if it has one.
+#[derive(Clone, Default)]
+struct ScriptPlayer {
+    wave: u32, // Wave the script was looked up for; 0 before the first lookup
+    script: Option<usize>,
+    next: usize, // Index of the next spawn to play
+}
+
+// Velocities are per frame at difficulty 1.0; the whole simulation of an
+// object is sped up by the difficulty multiplier.
+#[derive(Clone)]
+pub(crate) struct FallingObject {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    vx: f64,
+    vy: f64,
+    gravity: f64,
+    pub(crate) width: f64,
+    pub(crate) height: f64,
+    pub(crate) kind: usize, // Index into the ObjectCatalog
+    pub(crate) obj_type: ObjectType,
+    pub(crate) landed: bool, // Set once the object's bottom has reached the floor line
+    pub(crate) grazed_by: Option<usize>, // Player index of a near miss, paid out if it lands
+    pub(crate) lane: Option<usize>, // Player index whose spawn lane it dropped in
+    pub(crate) thrown_by: Option<usize>, // Player index of a versus throw, who it can't hit
+    pub(crate) before: (f64, f64), // Position as of the tick before, for drawing in between
+}
+
+impl FallingObject {
+    pub(crate) fn new(
+        kind: usize,
+        def: &ObjectDef,
+        x: f64,
+        y: f64,
+        vx: f64,
+        vy: f64,
+        gravity: f64,
+    ) -> Self {
+        FallingObject {
+            x,
+            y,
+            vx: vx * def.speed,
+            vy: vy * def.speed,
+            gravity: gravity * def.speed * def.speed,
+            width: def.width,
+            height: def.height,
+            kind,
+            obj_type: def.behavior,
+            landed: false,
+            grazed_by: None,
+            lane: None,
+            thrown_by: None,
+            before: (x, y),
+        }
+    }
+
+    // Straight down from the top edge
+    pub(crate) fn dropped(x: f64, kind: usize, def: &ObjectDef) -> Self {
+        FallingObject::new(kind, def, x, -def.height, 0.0, OBJECT_SPEED, 0.0)
+    }
+
+    // Thrown from one of the top corners toward the other side
+    fn diagonal(from_left: bool, kind: usize, def: &ObjectDef, world: WorldConfig) -> Self {
+        let (x, vx) = if from_left {
+            (0.0, DIAGONAL_SPEED_X)
+        } else {
+            (world.width - def.width, -DIAGONAL_SPEED_X)
+        };
+        FallingObject::new(kind, def, x, -def.height, vx, OBJECT_SPEED, 0.0)
+    }
+
+    // Lobbed upward from a side wall, arcing down into the aisle
+    fn lobbed(
+        from_left: bool,
+        height: f64,
+        kind: usize,
+        def: &ObjectDef,
+        world: WorldConfig,
+    ) -> Self {
+        let (x, vx) = if from_left {
+            (-def.width, LOB_SPEED_X)
+        } else {
+            (world.width, -LOB_SPEED_X)
+        };
+        FallingObject::new(kind, def, x, height, vx, LOB_SPEED_Y, LOB_GRAVITY)
+    }
+
+    pub(crate) fn step(&mut self, time_scale: f64) {
+        self.x += self.vx * time_scale;
+        self.y += self.vy * time_scale;
+        self.vy += self.gravity * time_scale;
+    }
+
+    // Where the object will touch the floor if nothing changes. Time scale
+    // doesn't affect the path, only how fast it's travelled.
+    pub(crate) fn landing_x(&self, wind: f64, floor_y: f64) -> Option<f64> {
+        let drop = floor_y - self.height - self.y;
+        if drop <= 0.0 {
+            return None;
+        }
+        let frames = if self.gravity == 0.0 {
+            if self.vy <= 0.0 {
+                return None;
+            }
+            drop / self.vy
+        } else {
+            (-self.vy + (self.vy * self.vy + 2.0 * self.gravity * drop).sqrt()) / self.gravity
+        };
+        Some(self.x + (self.vx + wind) * frames)
+    }
+
+    // Grows the object for the big pieces setting. One coming in from off
+    // screen stays off screen; one starting in the aisle grows about its
+    // middle and is kept inside the walls.
+    pub(crate) fn enlarge(&mut self, scale: f64, world: WorldConfig) {
+        let width = self.width * scale;
+        let height = self.height * scale;
+        if self.x + self.width <= 0.0 {
+            self.x -= width - self.width;
+        } else if self.x < world.width {
+            self.x = (self.x - (width - self.width) / 2.0).clamp(0.0, world.width - width);
+        }
+        if self.y + self.height <= 0.0 {
+            self.y -= height - self.height;
+        } else {
+            self.y -= (height - self.height) / 2.0;
+        }
+        self.width = width;
+        self.height = height;
+        self.before = (self.x, self.y);
+    }
+
+    pub(crate) fn is_off_screen(&self, world: WorldConfig) -> bool {
+        self.y >= world.height || self.x < -self.width * 2.0 || self.x > world.width + self.width
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum PerfEvent {
+    DealCaught,
+    DealMissed,
+    HitTaken,
+}
+
+// Where catches and hits happen, bucketed into a coarse grid over the
+// playfield. Only the debug overlay draws it.
+pub(crate) const HEATMAP_CELL: f64 = 10.0;
+
+pub(crate) struct Heatmap {
+    pub(crate) columns: usize,
+    rows: usize,
+    pub(crate) catches: Vec<u32>, // Row-major, `columns` wide
+    pub(crate) hits: Vec<u32>,
+}
+
+impl Heatmap {
+    fn new(world: WorldConfig) -> Self {
+        let columns = (world.width / HEATMAP_CELL) as usize;
+        let rows = (world.height / HEATMAP_CELL) as usize;
+        Heatmap {
+            columns,
+            rows,
+            catches: vec![0; columns * rows],
+            hits: vec![0; columns * rows],
+        }
+    }
+
+    pub(crate) fn record(&mut self, event: PerfEvent, x: f64, y: f64) {
+        let column = ((x / HEATMAP_CELL) as usize).min(self.columns - 1);
+        let row = ((y / HEATMAP_CELL) as usize).min(self.rows - 1);
+        let cells = match event {
+            PerfEvent::DealCaught => &mut self.catches,
+            PerfEvent::HitTaken => &mut self.hits,
+            PerfEvent::DealMissed => return,
+        };
+        cells[row * self.columns + column] += 1;
+    }
+}
+
+// Recent catches, misses and hits, used to judge how the players are doing.
+#[derive(Clone, Default)]
+pub(crate) struct PerformanceWindow {
+    events: VecDeque<(u32, PerfEvent)>, // Frame it happened on, oldest first
+}
+
+impl PerformanceWindow {
+    pub(crate) fn record(&mut self, frame: u32, event: PerfEvent) {
+        self.events.push_back((frame, event));
+    }
+
+    fn trim(&mut self, frame: u32) {
+        while let Some(&(at, _)) = self.events.front() {
+            if frame - at <= DDA_WINDOW_FRAMES {
+                break;
+            }
+            self.events.pop_front();
+        }
+    }
+
+    pub(crate) fn count(&self, event: PerfEvent) -> usize {
+        self.events.iter().filter(|(_, e)| *e == event).count()
+    }
+
+    pub(crate) fn accuracy(&self) -> Option<f64> {
+        let caught = self.count(PerfEvent::DealCaught);
+        let total = caught + self.count(PerfEvent::DealMissed);
+        (total > 0).then(|| caught as f64 / total as f64)
+    }
+
+    // -1 when the players are struggling, 1 when they're cruising
+    pub(crate) fn skill(&self) -> f64 {
+        let accuracy = self
+            .accuracy()
+            .map_or(0.0, |accuracy| (accuracy - DDA_TARGET_ACCURACY) * 2.5);
+        let damage = self.count(PerfEvent::HitTaken) as f64 * DDA_HIT_WEIGHT;
+        (accuracy - damage).clamp(-1.0, 1.0)
+    }
+}
+
+// An object that has been decided on but not yet released into play.
+#[derive(Clone)]
+pub(crate) struct PendingSpawn {
+    pub(crate) object: FallingObject,
+    delay: u32,
+}
+
+// A short-lived piece of text that drifts upward and fades out.
+#[derive(Clone)]
+pub(crate) struct TextPopup {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) text: String,
+    pub(crate) color: &'static str,
+    pub(crate) age: u32,
+}
+
+// Scrolling one-line announcer fed by gameplay events.
+#[derive(Clone, Default)]
+pub(crate) struct Ticker {
+    queue: VecDeque<String>,
+    pub(crate) current: Option<String>,
+    pub(crate) offset: f64, // How far the current message has scrolled in from the right edge
+}
+
+impl Ticker {
+    pub(crate) fn push(&mut self, message: String) {
+        if self.queue.len() >= TICKER_MAX_QUEUED {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(message);
+    }
+
+    // `screen_width` is how far a message crosses before it's gone
+    fn update(&mut self, screen_width: f64) {
+        if let Some(message) = &self.current {
+            self.offset += TICKER_SPEED;
+            let width = message.chars().count() as f64 * TICKER_CHAR_WIDTH;
+            if self.offset > screen_width + width {
+                self.current = None;
+            }
+        }
+        if self.current.is_none() {
+            self.current = self.queue.pop_front();
+            self.offset = 0.0;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.queue.clear();
+        self.current = None;
+        self.offset = 0.0;
+    }
+}
+
+// A temporary patch on the floor that affects players standing in it.
+#[derive(Clone)]
+pub(crate) struct FloorZone {
+    pub(crate) x: f64,
+    pub(crate) width: f64,
+    pub(crate) remaining: u32,
+}
+
+// A lights-out event: flickers for `warning` frames, then stays dark for `remaining`.
+#[derive(Clone)]
+pub(crate) struct Blackout {
+    pub(crate) warning: u32,
+    remaining: u32,
+}
+
+// Background dust blown about by the wind; purely visual.
+#[derive(Clone)]
+pub(crate) struct WindParticle {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) speed: f64, // Multiplier on the wind so particles drift at different rates
+}
+
+#[derive(Clone)]
+pub(crate) struct Player {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) width: f64,
+    pub(crate) height: f64,
+    pub(crate) before: (f64, f64), // Position as of the tick before, for drawing in between
+}
+
+impl Player {
+    pub(crate) fn overlaps(&self, obj: &FallingObject) -> bool {
+        self.x < obj.x + obj.width
+            && self.x + self.width > obj.x
+            && self.y < obj.y + obj.height
+            && self.y + self.height > obj.y
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct PlayerSlot {
+    pub(crate) player: Player,
+    pub(crate) score: i32,
+    pub(crate) health: i32,
+    pub(crate) max_health: i32, // What the run started with; healing stops here
+    pub(crate) player_index: usize, // Original player slot (0 for P1, 1 for P2)
+    effects: Vec<TimedEffect>,
+    pub(crate) trail: Vec<(f64, f64)>, // Recent positions, newest last
+    pub(crate) bonus_catches: u32,     // Deals caught during the current bonus round
+    pub(crate) combo: u32,             // Consecutive catches without taking a hit
+    pub(crate) streak: u32, // Like combo, but always broken by damage, whatever the mutators
+    pub(crate) entries_ahead: usize, // Leaderboard entries this run hasn't passed yet
+    pub(crate) pace: Vec<i32>, // Score at the end of each five seconds, for the personal best
+    pub(crate) stats: RunStats,
+    pub(crate) handicap: Handicap,
+    pub(crate) loop_multiplier: f64, // On points earned; above 1 in NEW GAME+
+    pub(crate) misses_in_a_row: u32, // Deals dropped nearest this player since their last catch
+    pub(crate) kept_hazard: Option<usize>, // Object kind of the hazard they can throw in versus
+    pub(crate) a_held: u32,          // Ticks A has been held, which tells a throw from an emote
+}
+
+// Per-player tallies shown live in the HUD and on the run summary.
+#[derive(Default, Copy, Clone)]
+pub(crate) struct RunStats {
+    pub(crate) deals_caught: u32,
+    pub(crate) deals_missed: u32, // Deals that reached the floor nearest this player
+    pub(crate) hits_taken: u32,
+    pub(crate) hits_avoided: u32, // Hazards that reached the floor while this player was alive
+    pub(crate) perfect_catches: u32, // Deals caught dead center
+}
+
+impl RunStats {
+    pub(crate) fn catch_rate(&self) -> Option<f64> {
+        let total = self.deals_caught + self.deals_missed;
+        (total > 0).then(|| self.deals_caught as f64 / total as f64)
+    }
+}
+
+impl PlayerSlot {
+    fn new(index: usize, total_players: usize, size_scale: f64, world: WorldConfig) -> Self {
+        let spacing = world.width / (total_players as f64 + 1.0);
+        let target_center = spacing * (index as f64 + 1.0);
+        let width = PLAYER_WIDTH * size_scale;
+        let height = PLAYER_HEIGHT * size_scale;
+        let x = target_center - width / 2.0;
+        let y = world.floor_y() - height;
+        PlayerSlot {
+            player: Player {
+                x,
+                y,
+                width,
+                height,
+                before: (x, y),
+            },
+            score: 0,
+            health: 3,
+            max_health: 3,
+            player_index: index,
+            effects: Vec::new(),
+            trail: Vec::new(),
+            bonus_catches: 0,
+            combo: 0,
+            streak: 0,
+            entries_ahead: 0,
+            pace: Vec::new(),
+            stats: RunStats::default(),
+            handicap: Handicap::None,
+            loop_multiplier: 1.0,
+            misses_in_a_row: 0,
+            kept_hazard: None,
+            a_held: 0,
+        }
+    }
+
+    pub(crate) fn bonus_multiplier(&self) -> f64 {
+        1.0 + self.bonus_catches as f64 * BONUS_MULTIPLIER_STEP
+    }
+
+    pub(crate) fn apply_effect(&mut self, kind: EffectKind, duration: u32) {
+        // Picking up the same effect again refreshes its timer rather than stacking
+        if let Some(effect) = self.effects.iter_mut().find(|e| e.kind == kind) {
+            effect.remaining = duration;
+        } else {
+            self.effects.push(TimedEffect {
+                kind,
+                remaining: duration,
+            });
+        }
+    }
+
+    pub(crate) fn has_effect(&self, kind: EffectKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+
+    pub(crate) fn remove_effect(&mut self, kind: EffectKind) {
+        self.effects.retain(|e| e.kind != kind);
+    }
+
+    fn tick_effects(&mut self) {
+        for effect in &mut self.effects {
+            effect.remaining = effect.remaining.saturating_sub(1);
+        }
+        self.effects.retain(|e| e.remaining > 0);
+
+        // Only leave a trail while boosted; let it shrink away afterwards
+        if self.has_effect(EffectKind::SpeedBoost) {
+            self.trail.push((self.player.x, self.player.y));
+            if self.trail.len() > TRAIL_LENGTH {
+                self.trail.remove(0);
+            }
+        } else if !self.trail.is_empty() {
+            self.trail.remove(0);
+        }
+    }
+
+    fn speed(&self, base: f64) -> f64 {
+        if self.has_effect(EffectKind::SpeedBoost) {
+            base * SPEED_BOOST_MULTIPLIER
+        } else {
+            base
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum PlayingPhase {
+    Normal,
+    BonusRound {
+        remaining: u32,
+    },
+    WaveClear {
+        remaining: u32,
+        bonus: [i32; 2], // Awarded to each player, by player index
+    },
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum PlayerMode {
+    Single,
+    Two,
+    Team, // Two players sharing one score
+}
+
+impl PlayerMode {
+    pub(crate) fn player_count(&self) -> usize {
+        match self {
+            PlayerMode::Single => 1,
+            PlayerMode::Two | PlayerMode::Team => 2,
+        }
+    }
+}
+
+// Each phase owns the state that only matters while it's showing, so leaving
+// a phase drops it and entering one starts it fresh.
+pub(crate) enum GamePhase {
+    ModeSelect {
+        selection: usize, // Index into MENU_ITEMS
+        cheats: CheatDetector,
+        unlock_flash: Option<(Unlock, u32)>, // Just-unlocked mode and frames left to show it
+        idle_frames: u32,                    // How long nobody's touched anything, for the demo
+        service_held_ms: f64,                // How long 1P and 2P have been held together
+        backdrop: MenuBackdrop,
+    },
+    Playing,
+    GameOver {
+        tab: LeaderboardCategory,
+    },
+    Leaderboard {
+        tab: LeaderboardCategory,
+        scroll: usize, // Index of the top row shown
+    },
+    NameEntry {
+        name: Vec<char>,
+        cursor: usize,         // Letter being changed, below NAME_LENGTH
+        queue: Vec<RunResult>, // Scores still waiting for a name, current first
+    },
+    Settings {
+        selection: usize, // Index into SETTINGS_ITEMS
+    },
+    Mutators {
+        selection: usize, // Index into mutator_items()
+    },
+    TournamentSetup {
+        selection: usize, // Index into TOURNAMENT_ITEMS
+        setup: TournamentSetup,
+    },
+    Series, // Between tournament runs; the standings are in GameState::tournament
+    Ready {
+        ready: [bool; 2], // By player index; the game starts once both are
+    },
+    ResumePrompt {
+        selection: usize, // Index into RESUME_ITEMS
+    },
+    Crashed(Box<CrashReport>), // Restarted after a panic
+    Loading {
+        loaded: usize, // Of `total` preloads, finished or failed
+        total: usize,
+        frames: u32, // Spent waiting so far
+    },
+    Splash {
+        frames: u32, // Shown so far
+    },
+    Credits {
+        frames: u32, // Scrolled so far
+    },
+    Operator(OperatorMenu),
+    NewGamePlus {
+        selection: usize, // Index into NEW_GAME_PLUS_ITEMS
+    },
+    HowToPlay {
+        page: usize,            // Index into the pages, controls first
+        then: Option<MenuItem>, // Carried on with after the last page
+    },
+    #[cfg(feature = "editor")]
+    Editor(Box<editor::Editor>),
+}
+
+// Optional rule twists chosen before a run. Any active mutator flags the
+// run's leaderboard entry so it isn't mistaken for a standard score.
+#[derive(Default, Copy, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct Mutators {
+    pub(crate) double_speed: bool,
+    pub(crate) no_combo_reset: bool,
+    pub(crate) tiny_players: bool,
+    pub(crate) dodge_mode: bool,
+    pub(crate) split_floor: bool,
+    // Only offered once unlocked with a cheat code
+    pub(crate) big_heads: bool,
+    pub(crate) rainbow: bool,
+    pub(crate) practice_lives: bool,
+}
+
+impl Mutators {
+    pub(crate) fn any(&self) -> bool {
+        self.double_speed
+            || self.no_combo_reset
+            || self.tiny_players
+            || self.dodge_mode
+            || self.split_floor
+            || self.big_heads
+            || self.rainbow
+            || self.practice_lives
+    }
+
+    pub(crate) fn label(&self, item: MutatorItem) -> String {
+        let on_off = |flag: bool| if flag { "ON" } else { "OFF" };
+        match item {
+            MutatorItem::DoubleSpeed => format!("2x speed: {}", on_off(self.double_speed)),
+            MutatorItem::NoComboReset => {
+                format!("No combo reset: {}", on_off(self.no_combo_reset))
+            }
+            MutatorItem::TinyPlayers => format!("Tiny players: {}", on_off(self.tiny_players)),
+            MutatorItem::DodgeMode => format!("Dodge mode: {}", on_off(self.dodge_mode)),
+            MutatorItem::SplitFloor => format!("Split floor (2P): {}", on_off(self.split_floor)),
+            MutatorItem::BigHeads => format!("Big heads: {}", on_off(self.big_heads)),
+            MutatorItem::Rainbow => format!("Rainbow: {}", on_off(self.rainbow)),
+            MutatorItem::PracticeLives => {
+                format!("30 lives practice: {}", on_off(self.practice_lives))
+            }
+            MutatorItem::Back => "Back".to_string(),
+        }
+    }
+}
+
+// Gameplay numbers the tuning panel can change while a game is running.
+// Defaults are the constants above.
+#[derive(Clone)]
+pub(crate) struct Tunables {
+    pub(crate) player_speed: f64,
+    pub(crate) player_scale: f64, // Player size, before the tiny players mutator
+    pub(crate) fall_speed: f64,   // Multiplies every object's speed
+    pub(crate) spawn_interval: f64,
+    pub(crate) min_spawn_interval: f64,
+    pub(crate) difficulty_per_wave: f64,
+    pub(crate) deal_weight: f64, // Multiplies deal odds from every spawn table
+}
+
+impl Default for Tunables {
+    fn default() -> Self {
+        Tunables {
+            player_speed: PLAYER_SPEED,
+            player_scale: 1.0,
+            fall_speed: 1.0,
+            spawn_interval: BASE_SPAWN_INTERVAL,
+            min_spawn_interval: MIN_SPAWN_INTERVAL,
+            difficulty_per_wave: DIFFICULTY_PER_WAVE,
+            deal_weight: 1.0,
+        }
+    }
+}
+
+// One player's outcome for the run summary and name entry.
+#[derive(Clone)]
+pub(crate) struct RunResult {
+    pub(crate) player_index: usize,
+    pub(crate) score: i32,
+    pub(crate) time_survived_ms: f64,
+    pub(crate) stats: RunStats,
+    pub(crate) handicap: Handicap,
+}
+
+pub(crate) struct GameState {
+    pub(crate) players: Vec<PlayerSlot>,
+    pub(crate) objects: Vec<FallingObject>,
+    pub(crate) pending_spawns: Vec<PendingSpawn>,
+    pub(crate) floor_zones: Vec<FloorZone>,
+    pub(crate) blackout: Option<Blackout>,
+    pub(crate) wind: f64, // Sideways drift per frame, positive blows right
+    pub(crate) wind_target: f64,
+    pub(crate) wind_particles: Vec<WindParticle>,
+    pub(crate) popups: Vec<TextPopup>,
+    pub(crate) whooshes: Vec<Whoosh>,
+    pub(crate) dying: Vec<DyingObject>, // Objects out of play, shrinking away
+    pub(crate) emotes: EmoteWheel,      // Cosmetic, so kept out of snapshots and the state hash
+    pub(crate) blend: f64,              // How far into the next tick to draw, set each frame
+    pub(crate) camera: Camera,
+    pub(crate) clutch_remaining: u32, // Ticks left of the current clutch moment, if any
+    pub(crate) pace_marker: Option<PaceMarker>,
+    pub(crate) hud_text: HudText,
+    pub(crate) ticker: Ticker,
+    pub(crate) frame_count: u32,
+    pub(crate) wave: u32,
+    pub(crate) wave_timer: u32,
+    pub(crate) playing_phase: PlayingPhase,
+    pub(crate) difficulty_multiplier: f64,
+    spawn_meter: f64,
+    pub(crate) performance: PerformanceWindow,
+    pub(crate) team_combo: u32, // The combo both players share in team mode
+    pub(crate) next_lane: usize, // Player index whose lane gets the next drop, with spawn lanes
+    pub(crate) dda_level: f64,  // Eased toward the players' recent skill, -1 to 1; 0 when off
+    pub(crate) heatmap: Heatmap, // Kept for the whole session, across runs
+    script_player: ScriptPlayer,
+    pub(crate) seed: u64,          // What the current run's rng was seeded with
+    pub(crate) input_log: Vec<u8>, // Held movement for every tick of this run so far
+    rng: StdRng,                   // Every random choice the simulation makes comes from here
+    pub(crate) world: WorldConfig, // The playfield's size, fixed once the game mounts
+    pub(crate) config: GameConfig,
+    pub(crate) tunables: Tunables,
+    pub(crate) behaviors: BehaviorRegistry,
+    pub(crate) mode: PlayerMode,
+    pub(crate) phase: GamePhase,
+    pub(crate) settings: Settings,
+    pub(crate) settings_after_run: Option<Settings>, // Put back once a resumed run is over
+    pub(crate) mutators: Mutators,
+    pub(crate) handicaps: [Handicap; 2], // Picked on the ready screen, by player index
+    pub(crate) new_game_plus: u32,       // Loops into NEW GAME+; 0 for an ordinary run
+    pub(crate) unlockables: Unlockables,
+    pub(crate) hold_saves: bool, // On a tick that may be rolled back
+    pub(crate) unsaved_progress: bool, // Held back by hold_saves
+    pub(crate) achievements: Achievements,
+    pub(crate) personal_best: PersonalBest,
+    pub(crate) seen_how_to_play: bool, // Shown before the first game on this machine
+    pub(crate) final_scores: Vec<RunResult>, // Results for dead players
+    pub(crate) tournament: Option<Tournament>, // The series being played, if any
+    pub(crate) bot: Option<AiController>, // Plays P2 in solo versus, or P1 in the demo
+    pub(crate) bot_level: BotLevel,    // Last picked on the ready screen
+    pub(crate) share_code: Option<ShareCode>, // QR code for the run's best named score
+    pub(crate) leaderboard: Vec<LeaderboardEntry>, // Sorted by score
+    pub(crate) session_best: Option<SessionBest>, // Not saved; starts over with the page
+    pub(crate) store: Rc<dyn KeyValueStore>, // Where the leaderboard, settings and unlocks are saved
+    pub(crate) events: VecDeque<GameEvent>,  // Queued during a tick, handled at its end
+    pub(crate) run_time_ms: f64,             // Time spent playing this run, in simulation ticks
+    survival_awards: u32,                    // Survival intervals already paid out
+    #[cfg(feature = "cabinet-sync")]
+    pub(crate) cabinet: cabinet::CabinetBoard,
+}
+
+impl GameState {
+    // Takes everything it would otherwise fetch from the browser, so a
+    // GameState can be built anywhere, page or not.
+    pub(crate) fn new(config: GameConfig, store: Rc<dyn KeyValueStore>) -> Self {
+        let mut state = GameState {
+            players: Vec::new(),
+            objects: Vec::new(),
+            pending_spawns: Vec::new(),
+            floor_zones: Vec::new(),
+            blackout: None,
+            wind: 0.0,
+            wind_target: 0.0,
+            wind_particles: Vec::new(),
+            popups: Vec::new(),
+            whooshes: Vec::new(),
+            dying: Vec::new(),
+            emotes: EmoteWheel::default(),
+            blend: 1.0,
+            camera: Camera::default(),
+            clutch_remaining: 0,
+            pace_marker: None,
+            hud_text: HudText::default(),
+            ticker: Ticker::default(),
+            frame_count: 0,
+            wave: 1,
+            wave_timer: 0,
+            playing_phase: PlayingPhase::Normal,
+            difficulty_multiplier: 1.0,
+            spawn_meter: 0.0,
+            performance: PerformanceWindow::default(),
+            team_combo: 0,
+            next_lane: 0,
+            dda_level: 0.0,
+            heatmap: Heatmap::new(config.world),
+            world: config.world,
+            script_player: ScriptPlayer::default(),
+            seed: 0,
+            input_log: Vec::new(),
+            rng: StdRng::seed_from_u64(0),
+            config,
+            tunables: Tunables::default(),
+            behaviors: BehaviorRegistry::with_builtins(),
+            mode: PlayerMode::Single,
+            phase: GamePhase::main_menu(),
+            settings: Settings::load(&*store),
+            settings_after_run: None,
+            mutators: Mutators::default(),
+            handicaps: [Handicap::None; 2],
+            new_game_plus: 0,
+            unlockables: Unlockables::load(&*store),
+            hold_saves: false,
+            unsaved_progress: false,
+            achievements: Achievements::load(&*store),
+            personal_best: PersonalBest::load(&*store),
+            seen_how_to_play: seen_how_to_play(&*store),
+            final_scores: Vec::new(),
+            tournament: None,
+            bot: None,
+            bot_level: BotLevel::Medium,
+            share_code: None,
+            leaderboard: Vec::new(),
+            session_best: None,
+            store,
+            events: VecDeque::new(),
+            run_time_ms: 0.0,
+            survival_awards: 0,
+            #[cfg(feature = "cabinet-sync")]
+            cabinet: cabinet::CabinetBoard::default(),
+        };
+        state.load_leaderboard();
+        state.offer_resume();
+        state
+    }
+
+    pub(crate) fn reset_runtime(&mut self) {
+        if let Some(settings) = self.settings_after_run.take() {
+            self.settings = settings;
+        }
+        self.objects.clear();
+        self.pending_spawns.clear();
+        self.floor_zones.clear();
+        self.blackout = None;
+        self.wind = 0.0;
+        self.wind_target = 0.0;
+        self.wind_particles.clear();
+        self.popups.clear();
+        self.whooshes.clear();
+        self.dying.clear();
+        self.camera = Camera::default();
+        self.clutch_remaining = 0;
+        self.pace_marker = None;
+        self.ticker.clear();
+        self.frame_count = 0;
+        self.wave = 1;
+        self.wave_timer = 0;
+        self.playing_phase = PlayingPhase::Normal;
+        self.difficulty_multiplier = 1.0;
+        self.spawn_meter = 0.0;
+        self.performance = PerformanceWindow::default();
+        self.team_combo = 0;
+        self.next_lane = 0;
+        self.dda_level = 0.0;
+        self.script_player = ScriptPlayer::default();
+        self.final_scores.clear();
+        self.share_code = None;
+        self.run_time_ms = 0.0;
+        self.survival_awards = 0;
+    }
+
+    pub(crate) fn start_new_game(&mut self, mode: PlayerMode) {
+        self.start_seeded_game(mode, rand::random());
+    }
+
+    // Runs started from the same seed, config, settings and mutators play out
+    // identically tick for tick when fed the same commands.
+    pub(crate) fn start_seeded_game(&mut self, mode: PlayerMode, seed: u64) {
+        self.reset_runtime();
+        self.seed = seed;
+        self.input_log.clear();
+        self.rng = StdRng::seed_from_u64(seed);
+        self.mode = mode;
+        // Only this run's scores count as the latest once it's over
+        for entry in &mut self.leaderboard {
+            entry.latest = false;
+        }
+        let size_scale = self.player_size_scale();
+        let world = self.world;
+        self.players = (0..mode.player_count())
+            .map(|idx| PlayerSlot::new(idx, mode.player_count(), size_scale, world))
+            .collect();
+        if self.mutators.practice_lives {
+            for slot in &mut self.players {
+                slot.health = PRACTICE_HEALTH;
+            }
+        }
+        self.apply_handicaps();
+        self.apply_new_game_plus();
+        for slot in &mut self.players {
+            slot.max_health = slot.health;
+        }
+        self.start_pace();
+        self.phase = GamePhase::Playing;
+    }
+
+    pub(crate) fn player_size_scale(&self) -> f64 {
+        let mutator_scale = if self.mutators.tiny_players {
+            TINY_PLAYER_SCALE
+        } else {
+            1.0
+        };
+        mutator_scale * self.tunables.player_scale * self.piece_scale()
+    }
+
+    pub(crate) fn piece_scale(&self) -> f64 {
+        if self.settings.big_pieces {
+            BIG_PIECES_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    // One fixed simulation step: held movement, the run clock, then the world.
+    // Returns the state hash after the step.
+    pub(crate) fn tick(&mut self, commands: &[Command]) -> u64 {
+        self.remember_positions();
+        if matches!(self.phase, GamePhase::Playing) {
+            // Two bits per player, left then right, then a bit each for A;
+            // one byte per tick
+            let mut held = 0u8;
+            let mut throwing = [false; 2];
+            for command in commands {
+                match *command {
+                    Command::MoveLeft(player_index) => {
+                        self.move_player(player_index, -1.0);
+                        held |= 1 << (player_index * 2);
+                    }
+                    Command::MoveRight(player_index) => {
+                        self.move_player(player_index, 1.0);
+                        held |= 2 << (player_index * 2);
+                    }
+                    Command::Throw(player_index) => {
+                        throwing[player_index] = true;
+                        held |= 16 << player_index;
+                    }
+                    _ => {}
+                }
+            }
+            self.update_throws(throwing);
+            self.input_log.push(held);
+        }
+        self.tick_clock(TICK_MS);
+        self.update_camera();
+        self.update();
+        self.dispatch_events();
+        self.sample_pace();
+        self.update_pace();
+        self.state_hash()
+    }
+
+    // Fingerprint of everything that decides how the rest of the run plays
+    // out. Two peers in lockstep compare these after each tick; the first
+    // mismatch is where they diverged. Visual-only state (popups, particles,
+    // the ticker) is left out.
+    pub(crate) fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::default();
+        let h = &mut hasher;
+        (
+            self.frame_count,
+            self.wave,
+            self.wave_timer,
+            self.survival_awards,
+            self.team_combo,
+            self.next_lane,
+        )
+            .hash(h);
+        std::mem::discriminant(&self.playing_phase).hash(h);
+        match self.playing_phase {
+            PlayingPhase::BonusRound { remaining } | PlayingPhase::WaveClear { remaining, .. } => {
+                remaining.hash(h)
+            }
+            PlayingPhase::Normal => {}
+        }
+        for value in [
+            self.difficulty_multiplier,
+            self.spawn_meter,
+            self.dda_level,
+            self.wind,
+            self.wind_target,
+            self.run_time_ms,
+        ] {
+            value.to_bits().hash(h);
+        }
+        self.blackout
+            .as_ref()
+            .map(|blackout| (blackout.warning, blackout.remaining))
+            .hash(h);
+        for slot in &self.players {
+            (slot.player_index, slot.score, slot.health, slot.combo).hash(h);
+            (slot.max_health, slot.streak, slot.kept_hazard, slot.a_held).hash(h);
+            (
+                slot.bonus_catches,
+                slot.stats.deals_caught,
+                slot.stats.hits_taken,
+            )
+                .hash(h);
+            for value in [slot.player.x, slot.player.y, slot.player.width] {
+                value.to_bits().hash(h);
+            }
+            for effect in &slot.effects {
+                std::mem::discriminant(&effect.kind).hash(h);
+                effect.remaining.hash(h);
+            }
+        }
+        let pending = self.pending_spawns.iter().map(|pending| &pending.object);
+        for obj in self.objects.iter().chain(pending) {
+            (obj.kind, obj.landed, obj.grazed_by).hash(h);
+            for value in [obj.x, obj.y, obj.vx, obj.vy, obj.gravity] {
+                value.to_bits().hash(h);
+            }
+        }
+        for pending in &self.pending_spawns {
+            pending.delay.hash(h);
+        }
+        for zone in &self.floor_zones {
+            (zone.x.to_bits(), zone.remaining).hash(h);
+        }
+        (self.script_player.script, self.script_player.next).hash(h);
+        // Peek at the rng's next output without disturbing it
+        self.rng.clone().next_u64().hash(h);
+        hasher.finish()
+    }
+
+    #[cfg(feature = "netplay")]
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            players: self.players.clone(),
+            objects: self.objects.clone(),
+            pending_spawns: self.pending_spawns.clone(),
+            floor_zones: self.floor_zones.clone(),
+            blackout: self.blackout.clone(),
+            wind: self.wind,
+            wind_target: self.wind_target,
+            wind_particles: self.wind_particles.clone(),
+            popups: self.popups.clone(),
+            whooshes: self.whooshes.clone(),
+            dying: self.dying.clone(),
+            camera: self.camera,
+            clutch_remaining: self.clutch_remaining,
+            pace_marker: self.pace_marker.clone(),
+            ticker: self.ticker.clone(),
+            frame_count: self.frame_count,
+            wave: self.wave,
+            wave_timer: self.wave_timer,
+            playing_phase: self.playing_phase,
+            difficulty_multiplier: self.difficulty_multiplier,
+            spawn_meter: self.spawn_meter,
+            performance: self.performance.clone(),
+            team_combo: self.team_combo,
+            next_lane: self.next_lane,
+            dda_level: self.dda_level,
+            script_player: self.script_player.clone(),
+            rng: self.rng.clone(),
+            input_log_len: self.input_log.len(),
+            final_scores: self.final_scores.clone(),
+            run_time_ms: self.run_time_ms,
+            survival_awards: self.survival_awards,
+            achievements: self.achievements.clone(),
+            personal_best: self.personal_best.clone(),
+            unsaved_progress: self.unsaved_progress,
+        }
+    }
+
+    // Puts the run back exactly as it was when the snapshot was taken,
+    // including back into play if it has ended since
+    #[cfg(feature = "netplay")]
+    pub(crate) fn restore(&mut self, snapshot: Snapshot) {
+        self.players = snapshot.players;
+        self.objects = snapshot.objects;
+        self.pending_spawns = snapshot.pending_spawns;
+        self.floor_zones = snapshot.floor_zones;
+        self.blackout = snapshot.blackout;
+        self.wind = snapshot.wind;
+        self.wind_target = snapshot.wind_target;
+        self.wind_particles = snapshot.wind_particles;
+        self.popups = snapshot.popups;
+        self.whooshes = snapshot.whooshes;
+        self.dying = snapshot.dying;
+        self.camera = snapshot.camera;
+        self.clutch_remaining = snapshot.clutch_remaining;
+        self.pace_marker = snapshot.pace_marker;
+        self.ticker = snapshot.ticker;
+        self.frame_count = snapshot.frame_count;
+        self.wave = snapshot.wave;
+        self.wave_timer = snapshot.wave_timer;
+        self.playing_phase = snapshot.playing_phase;
+        self.difficulty_multiplier = snapshot.difficulty_multiplier;
+        self.spawn_meter = snapshot.spawn_meter;
+        self.performance = snapshot.performance;
+        self.team_combo = snapshot.team_combo;
+        self.next_lane = snapshot.next_lane;
+        self.dda_level = snapshot.dda_level;
+        self.script_player = snapshot.script_player;
+        self.rng = snapshot.rng;
+        self.input_log.truncate(snapshot.input_log_len);
+        self.final_scores = snapshot.final_scores;
+        self.run_time_ms = snapshot.run_time_ms;
+        self.survival_awards = snapshot.survival_awards;
+        self.achievements = snapshot.achievements;
+        self.personal_best = snapshot.personal_best;
+        self.unsaved_progress = snapshot.unsaved_progress;
+        self.phase = GamePhase::Playing;
+    }
+
+    // Advances the run timer. Only time spent in the Playing phase counts,
+    // so menus and name entry don't inflate it.
+    fn tick_clock(&mut self, elapsed: f64) {
+        if !matches!(self.phase, GamePhase::Playing) {
+            return;
+        }
+
+        self.run_time_ms += elapsed;
+        let intervals = (self.run_time_ms / SURVIVAL_INTERVAL_MS) as u32;
+        while self.survival_awards < intervals {
+            self.survival_awards += 1;
+            for player_slot in &mut self.players {
+                player_slot.earn(self.config.scoring.survival_points);
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        if !matches!(self.phase, GamePhase::Playing) {
+            return;
+        }
+        #[cfg(feature = "profiling")]
+        let _span = profile::span("update");
+
+        self.frame_count += 1;
+        self.update_clutch();
+
+        match self.playing_phase {
+            PlayingPhase::Normal => {
+                // Increase difficulty over time.
+                //
+                // We ramp up relatively quickly: every ~10 seconds at 60 FPS, we get a
+                // noticeable bump in speed and spawn rate.
+                self.wave_timer += 1;
+                if self.wave_timer >= WAVE_LENGTH_FRAMES {
+                    self.start_wave_clear();
+                } else {
+                    if self.wave.is_multiple_of(BONUS_ROUND_EVERY_WAVES)
+                        && self.wave_timer == WAVE_LENGTH_FRAMES - BONUS_ROUND_WARNING_FRAMES
+                    {
+                        self.ticker.push(format!(
+                            "DOORBUSTER BONUS in {}s!",
+                            (BONUS_ROUND_WARNING_FRAMES + WAVE_CLEAR_FRAMES) / 60
+                        ));
+                    }
+                    if self.run_wave_script() {
+                        self.spawn_normal();
+                    }
+                }
+            }
+            PlayingPhase::WaveClear { remaining, bonus } => {
+                if remaining <= 1 {
+                    self.playing_phase = PlayingPhase::Normal;
+                    self.next_wave();
+                } else {
+                    self.playing_phase = PlayingPhase::WaveClear {
+                        remaining: remaining - 1,
+                        bonus,
+                    };
+                }
+            }
+            PlayingPhase::BonusRound { remaining } => {
+                if remaining <= 1 {
+                    self.end_bonus_round();
+                } else {
+                    self.playing_phase = PlayingPhase::BonusRound {
+                        remaining: remaining - 1,
+                    };
+                    if remaining % BONUS_SPAWN_INTERVAL == 0 {
+                        self.spawn_bonus_pattern(remaining);
+                    }
+                }
+            }
+        }
+
+        self.release_pending_spawns();
+
+        for player_slot in &mut self.players {
+            player_slot.tick_effects();
+        }
+
+        self.update_blackout();
+        self.update_wind();
+        self.update_popups();
+        self.update_whooshes();
+        self.update_despawns();
+        self.ticker.update(self.world.width);
+
+        // Update falling objects
+        let mutator_speed = if self.mutators.double_speed { 2.0 } else { 1.0 };
+        let grace_speed = 1.0 - (1.0 - GRACE_FALL_SPEED) * self.grace();
+        let warm_up = &self.config.warm_up;
+        let time_scale = self.difficulty_multiplier
+            * mutator_speed
+            * self.tunables.fall_speed
+            * self.config.difficulty.preset().fall_speed
+            * warm_up.scale(warm_up.fall_speed, self.frame_count)
+            * grace_speed
+            * self.new_game_plus_speed();
+        let fall_scales: Vec<f64> = self
+            .objects
+            .iter()
+            .map(|obj| self.fall_scale(obj))
+            .collect();
+        for (obj, fall_scale) in self.objects.iter_mut().zip(fall_scales) {
+            self.behaviors.get(obj.obj_type).on_tick(obj);
+            let time_scale = time_scale * fall_scale;
+            obj.step(time_scale);
+            obj.x += self.wind * time_scale;
+        }
+        self.keep_objects_in_halves();
+
+        // Check collisions
+        self.check_collisions();
+
+        // Let each object react once when it first reaches the floor
+        let mut game = BehaviorContext {
+            rules: &self.config.scoring,
+            flags: self.rule_flags(),
+            effects: Vec::new(),
+        };
+        let behaviors = &self.behaviors;
+        let floor_y = self.world.floor_y();
+        let mut burst = Vec::new();
+        self.objects.retain_mut(|obj| {
+            if obj.landed || obj.y + obj.height < floor_y {
+                return true;
+            }
+            obj.landed = true;
+            if behaviors.get(obj.obj_type).on_floor(obj, &mut game) {
+                burst.push(obj.clone());
+                return false;
+            }
+            true
+        });
+        let effects = game.effects;
+        self.apply_world_effects(effects);
+        for obj in burst {
+            self.despawn(obj);
+        }
+        self.update_dda();
+
+        for zone in &mut self.floor_zones {
+            zone.remaining = zone.remaining.saturating_sub(1);
+        }
+        self.floor_zones.retain(|zone| zone.remaining > 0);
+
+        // Remove objects that went off screen
+        let world = self.world;
+        let (gone, objects): (Vec<_>, Vec<_>) = std::mem::take(&mut self.objects)
+            .into_iter()
+            .partition(|obj| obj.is_off_screen(world));
+        self.objects = objects;
+        for obj in gone {
+            self.despawn(obj);
+        }
+    }
+
+    fn spawn_normal(&mut self) {
+        // Spawn new objects based on a difficulty-scaled meter instead of fixed frames.
+        //
+        // Higher difficulty increases how fast the spawn meter fills, which means
+        // more objects per second as you survive longer.
+        let warm_up = &self.config.warm_up;
+        let spawn_fill_rate = self.difficulty_multiplier
+            * self.dda_spawn_rate()
+            * warm_up.scale(warm_up.spawn_rate, self.frame_count);
+        self.spawn_meter += spawn_fill_rate;
+
+        let effective_interval = (self.tunables.spawn_interval / self.difficulty_multiplier)
+            .max(self.tunables.min_spawn_interval);
+
+        while self.spawn_meter >= effective_interval {
+            self.spawn_meter -= effective_interval;
+            self.spawn_object();
+
+            // At very high difficulty, sometimes spawn an extra object for chaos.
+            if self.difficulty_multiplier >= 2.0 && self.rng.gen_bool(0.25) {
+                self.spawn_object();
+            }
+        }
+    }
+
+    // Queues any designed spawns due this frame. Returns whether the random
+    // spawner should run as well.
+    fn run_wave_script(&mut self) -> bool {
+        if self.script_player.wave != self.wave {
+            let script = self.config.waves.for_wave(self.wave);
+            if let Some(index) = script {
+                let name = &self.config.waves.scripts[index].name;
+                if !name.is_empty() {
+                    self.ticker.push(name.clone());
+                }
+            }
+            self.script_player = ScriptPlayer {
+                wave: self.wave,
+                script,
+                next: 0,
+            };
+        }
+        let Some(index) = self.script_player.script else {
+            return true;
+        };
+
+        let script = &self.config.waves.scripts[index];
+        let mut due = Vec::new();
+        while let Some(spawn) = script.spawns.get(self.script_player.next) {
+            if spawn.frame > self.wave_timer {
+                break;
+            }
+            let def = self.config.objects.get(spawn.kind);
+            let x = spawn.x.clamp(0.0, self.world.width - def.width);
+            due.push(FallingObject::dropped(x, spawn.kind, def));
+            self.script_player.next += 1;
+        }
+        let random_spawns = script.random_spawns;
+        for object in due {
+            self.queue_spawn(object);
+        }
+        random_spawns
+    }
+
+    fn update_dda(&mut self) {
+        self.performance.trim(self.frame_count);
+        let target = if self.settings.adaptive_difficulty {
+            self.performance.skill()
+        } else {
+            0.0
+        };
+        self.dda_level += (target - self.dda_level) * DDA_EASING;
+    }
+
+    // Struggling players get fewer objects, and more of them deals
+    pub(crate) fn dda_spawn_rate(&self) -> f64 {
+        1.0 + DDA_SPAWN_RATE_RANGE * self.dda_level
+    }
+
+    pub(crate) fn dda_deal_odds(&self) -> f64 {
+        1.0 - DDA_DEAL_ODDS_RANGE * self.dda_level
+    }
+
+    // 1 through the opening grace period, easing to 0 as it blends out.
+    // Dodge mode has no deals to give, so it skips straight to the real thing.
+    pub(crate) fn grace(&self) -> f64 {
+        let grace_ticks = self.config.difficulty.preset().grace_ticks;
+        if self.mutators.dodge_mode || self.frame_count >= grace_ticks + GRACE_BLEND_FRAMES {
+            return 0.0;
+        }
+        let blended = self.frame_count.saturating_sub(grace_ticks);
+        1.0 - blended as f64 / GRACE_BLEND_FRAMES as f64
+    }
+
+    fn queue_spawn(&mut self, mut object: FallingObject) {
+        object.enlarge(self.piece_scale(), self.world);
+        self.assign_half(&mut object);
+        self.pending_spawns.push(PendingSpawn {
+            object,
+            delay: SPAWN_TELEGRAPH_FRAMES,
+        });
+    }
+
+    fn release_pending_spawns(&mut self) {
+        for pending in &mut self.pending_spawns {
+            pending.delay = pending.delay.saturating_sub(1);
+        }
+        let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_spawns)
+            .into_iter()
+            .partition(|pending| pending.delay == 0);
+        self.pending_spawns = waiting;
+        for pending in ready {
+            let mut object = pending.object;
+            self.behaviors.get(object.obj_type).on_spawn(&mut object);
+            self.objects.push(object);
+        }
+    }
+
+    fn rule_flags(&self) -> RuleFlags {
+        RuleFlags {
+            bonus_round: matches!(self.playing_phase, PlayingPhase::BonusRound { .. }),
+            no_combo_reset: self.mutators.no_combo_reset,
+            dodge_mode: self.mutators.dodge_mode,
+        }
+    }
+
+    fn apply_world_effects(&mut self, effects: Vec<WorldEffect>) {
+        for effect in effects {
+            match effect {
+                WorldEffect::Spill { center_x } => self.spill_at(center_x),
+                WorldEffect::Popup { x, y, text, color } => self.spawn_popup(x, y, text, color),
+                WorldEffect::Announce(message) => self.ticker.push(message),
+                WorldEffect::ScoreAllPlayers(points) => {
+                    for player_slot in self.players.iter_mut().filter(|slot| slot.health > 0) {
+                        player_slot.earn(points);
+                        player_slot.score = player_slot.score.max(0);
+                    }
+                }
+                WorldEffect::Event(event) => self.events.push_back(event),
+            }
+        }
+    }
+
+    // The wave's time is up: pay out for the hearts left and hold off the
+    // next one for a moment
+    fn start_wave_clear(&mut self) {
+        let mut bonus = [0; 2];
+        for slot in self.players.iter_mut().filter(|slot| slot.health > 0) {
+            let points = slot.health * WAVE_CLEAR_POINTS_PER_HEART;
+            slot.earn(points);
+            bonus[slot.player_index] = points;
+        }
+        self.playing_phase = PlayingPhase::WaveClear {
+            remaining: WAVE_CLEAR_FRAMES,
+            bonus,
+        };
+    }
+
+    fn next_wave(&mut self) {
+        self.wave_timer = 0;
+        self.wave += 1;
+        self.difficulty_multiplier +=
+            self.tunables.difficulty_per_wave * self.config.difficulty.preset().ramp;
+
+        if (self.wave - 1).is_multiple_of(BONUS_ROUND_EVERY_WAVES) && !self.mutators.dodge_mode {
+            self.start_bonus_round();
+        }
+        self.events
+            .push_back(GameEvent::WaveStarted { wave: self.wave });
+    }
+
+    fn start_bonus_round(&mut self) {
+        for player_slot in &mut self.players {
+            player_slot.bonus_catches = 0;
+        }
+        self.spawn_meter = 0.0;
+        self.ticker
+            .push("DOORBUSTER! Grab everything - nothing can hurt you!".to_string());
+        let world = self.world;
+        self.zoom_punch(DOORBUSTER_PUNCH, world.width / 2.0, world.floor_y());
+        self.playing_phase = PlayingPhase::BonusRound {
+            remaining: BONUS_ROUND_DURATION,
+        };
+    }
+
+    fn end_bonus_round(&mut self) {
+        for player_slot in &mut self.players {
+            let haul = player_slot.bonus_catches as i32 * BONUS_POINTS_PER_CATCH;
+            let bonus = (haul as f64 * player_slot.bonus_multiplier()) as i32;
+            player_slot.earn(bonus);
+            self.ticker.push(format!(
+                "P{} bonus haul +{}",
+                player_slot.player_index + 1,
+                bonus
+            ));
+            player_slot.bonus_catches = 0;
+        }
+        self.playing_phase = PlayingPhase::Normal;
+    }
+
+    // Two streams of deals weaving across the aisle in opposite directions.
+    fn spawn_bonus_pattern(&mut self, remaining: u32) {
+        let t = remaining as f64 * 0.05;
+        let Some(kind) = self.config.objects.first_of(ObjectType::GoodDeal) else {
+            return;
+        };
+        let def = self.config.objects.get(kind);
+        let span = (self.world.width - def.width) / 2.0;
+        let objects: Vec<FallingObject> = [0.0, std::f64::consts::PI]
+            .iter()
+            .map(|phase| FallingObject::dropped(span + sim_sin(t + phase) * span, kind, def))
+            .collect();
+        for object in objects {
+            self.queue_spawn(object);
+        }
+    }
+
+    fn update_wind(&mut self) {
+        let world = self.world;
+        let rng = &mut self.rng;
+
+        if self.wave >= WIND_FROM_WAVE && self.frame_count.is_multiple_of(WIND_CHANGE_INTERVAL) {
+            let max = (WIND_MAX_BASE + WIND_MAX_PER_WAVE * (self.wave - WIND_FROM_WAVE) as f64)
+                .min(WIND_MAX_CAP);
+            self.wind_target = rng.gen_range(-max..=max);
+        }
+        self.wind += (self.wind_target - self.wind) * WIND_EASING;
+
+        // More dust the harder it blows, entering from the upwind edge
+        let wanted = ((self.wind.abs() / WIND_MAX_CAP) * WIND_PARTICLE_CAP as f64) as usize;
+        if self.wind_particles.len() < wanted {
+            let x = if self.wind > 0.0 { 0.0 } else { world.width };
+            self.wind_particles.push(WindParticle {
+                x,
+                y: rng.gen_range(0.0..world.floor_y()),
+                speed: rng.gen_range(3.0..6.0),
+            });
+        }
+        for particle in &mut self.wind_particles {
+            particle.x += self.wind * particle.speed;
+            particle.y += 0.2;
+        }
+        self.wind_particles
+            .retain(|p| p.x >= 0.0 && p.x <= world.width && p.y < world.floor_y());
+    }
+
+    pub(crate) fn spawn_popup(&mut self, x: f64, y: f64, text: String, color: &'static str) {
+        self.popups.push(TextPopup {
+            x,
+            y,
+            text,
+            color,
+            age: 0,
+        });
+    }
+
+    fn update_popups(&mut self) {
+        for popup in &mut self.popups {
+            popup.y -= 0.5;
+            popup.age += 1;
+        }
+        self.popups.retain(|popup| popup.age < POPUP_LIFETIME);
+    }
+
+    fn update_blackout(&mut self) {
+        match &mut self.blackout {
+            Some(blackout) if blackout.warning > 0 => blackout.warning -= 1,
+            Some(blackout) if blackout.remaining > 1 => blackout.remaining -= 1,
+            Some(_) => self.blackout = None,
+            None => {
+                if self.difficulty_multiplier >= BLACKOUT_MIN_DIFFICULTY
+                    && self.rng.gen_bool(BLACKOUT_CHANCE_PER_FRAME)
+                {
+                    self.blackout = Some(Blackout {
+                        warning: BLACKOUT_WARNING_FRAMES,
+                        remaining: BLACKOUT_DURATION,
+                    });
+                    self.ticker
+                        .push("Power surge! Lights going out...".to_string());
+                }
+            }
+        }
+    }
+
+    fn spill_at(&mut self, center_x: f64) {
+        let right = self.world.width - STICKY_PATCH_WIDTH;
+        let x = (center_x - STICKY_PATCH_WIDTH / 2.0).clamp(0.0, right);
+        self.floor_zones.push(FloorZone {
+            x,
+            width: STICKY_PATCH_WIDTH,
+            remaining: STICKY_PATCH_DURATION,
+        });
+    }
+
+    fn is_on_sticky_floor(&self, player: &Player) -> bool {
+        self.floor_zones
+            .iter()
+            .any(|zone| player.x < zone.x + zone.width && player.x + player.width > zone.x)
+    }
+
+    fn spawn_object(&mut self) {
+        // Odds come from the current wave's spawn table, which shifts toward
+        // hazards in later aisles. Dodge mode only drops hazards, and the
+        // grace period only deals.
+        let dodge_mode = self.mutators.dodge_mode;
+        let deal_odds = self.dda_deal_odds() * self.tunables.deal_weight;
+        let others = 1.0 - self.grace();
+        let tables = &self.config.spawn_tables;
+        let wave = self.wave;
+        let picked = self
+            .config
+            .objects
+            .pick(&mut self.rng, |kind, def| match def.behavior {
+                ObjectType::BadItem => tables.weight(wave, kind, def) * others,
+                _ if dodge_mode => 0.0,
+                ObjectType::GoodDeal => tables.weight(wave, kind, def) * deal_odds,
+                _ => tables.weight(wave, kind, def) * others,
+            });
+        let Some(kind) = picked else {
+            return;
+        };
+        let def = self.config.objects.get(kind);
+        let world = self.world;
+        let lane = self.drop_lane();
+        let (left, right) = self.drop_span(lane);
+        let split = self.split_active();
+        let rng = &mut self.rng;
+        let x = rng.gen_range(left..(right - def.width).max(left + 1.0));
+
+        // Later waves mix in throws and lobs alongside plain drops
+        let thrown = self.wave >= THROWN_OBJECTS_FROM_WAVE;
+        let from_left = rng.gen_bool(0.5);
+        // On a split floor throws come in from the outside edge of their half
+        let from_left = match lane {
+            Some(lane) if split => lane == 0,
+            _ => from_left,
+        };
+        let object = if thrown && rng.gen_bool(DIAGONAL_THROW_CHANCE) {
+            FallingObject::diagonal(from_left, kind, def, world)
+        } else if thrown && rng.gen_bool(LOB_CHANCE) {
+            let height = rng.gen_range(world.height * 0.3..world.height * 0.5);
+            FallingObject::lobbed(from_left, height, kind, def, world)
+        } else {
+            FallingObject {
+                lane,
+                ..FallingObject::dropped(x, kind, def)
+            }
+        };
+        if object.lane.is_some() || split {
+            self.advance_lane();
+        }
+        self.queue_spawn(object);
+    }
+
+    fn check_collisions(&mut self) {
+        #[cfg(feature = "profiling")]
+        let _span = profile::span("collisions");
+        if self.players.is_empty() {
+            return;
+        }
+        self.mark_near_misses();
+
+        let mut to_remove = Vec::new();
+        let mut game = BehaviorContext {
+            rules: &self.config.scoring,
+            flags: self.rule_flags(),
+            effects: Vec::new(),
+        };
+
+        let team = self.mode == PlayerMode::Team;
+        for (i, obj) in self.objects.iter().enumerate() {
+            // Once on the floor it's been counted as missed or dodged, and
+            // is out of play
+            if obj.landed {
+                continue;
+            }
+            // Living players touching the object
+            let touching: Vec<usize> = (0..self.players.len())
+                .filter(|&j| {
+                    let slot = &self.players[j];
+                    slot.health > 0
+                        && slot.player.overlaps(obj)
+                        && obj.thrown_by != Some(slot.player_index)
+                })
+                .collect();
+            if touching.is_empty() {
+                continue;
+            }
+            // The first player to touch an object catches it, unless it's a
+            // team pile-up, which every player in it takes
+            let pileup = self.is_pileup(obj, touching.len());
+            let catchers = if pileup {
+                &touching[..]
+            } else {
+                &touching[..1]
+            };
+            let def = self.config.objects.get(obj.kind);
+            for &j in catchers {
+                let player_slot = &mut self.players[j];
+                // Team players build one combo between them
+                if team {
+                    player_slot.combo = self.team_combo;
+                }
+                self.behaviors
+                    .get(obj.obj_type)
+                    .on_catch(obj, def, player_slot, &mut game);
+                if team {
+                    self.team_combo = player_slot.combo;
+                }
+                if pileup {
+                    player_slot.score = (player_slot.score - PILEUP_PENALTY).max(0);
+                }
+            }
+            if pileup {
+                game.effects.extend(pileup_effects(obj, catchers.len()));
+            }
+            to_remove.push(i);
+        }
+
+        let caught: Vec<FallingObject> = to_remove
+            .iter()
+            .rev()
+            .map(|&i| self.objects.remove(i))
+            .collect();
+        let effects = game.effects;
+        self.apply_world_effects(effects);
+        for obj in caught {
+            self.despawn(obj);
+        }
+
+        for slot in self.players.iter().filter(|slot| slot.health <= 0) {
+            self.events.push_back(GameEvent::PlayerDied {
+                player_index: slot.player_index,
+            });
+        }
+        // Game over when all players are dead
+        if self.players.iter().all(|slot| slot.health <= 0) {
+            self.events.push_back(GameEvent::GameOver);
+        }
+    }
+
+    fn move_player(&mut self, player_index: usize, dx: f64) {
+        let base_speed = self.tunables.player_speed;
+        let (left, right) = self.player_span(player_index);
+        let sticky = self
+            .players
+            .iter()
+            .find(|slot| slot.player_index == player_index)
+            .is_some_and(|slot| self.is_on_sticky_floor(&slot.player));
+
+        // Find player by their original slot index (not array position)
+        if let Some(player_slot) = self
+            .players
+            .iter_mut()
+            .find(|slot| slot.player_index == player_index && slot.health > 0)
+        {
+            // Reversal is applied here rather than at input time so it covers
+            // keyboard, controller and any analog source the same way
+            let dx = if player_slot.has_effect(EffectKind::ReversedControls) {
+                -dx
+            } else {
+                dx
+            };
+            let mut speed = player_slot.speed(base_speed);
+            if sticky {
+                speed *= STICKY_SLOW_FACTOR;
+            }
+            player_slot.player.x += dx * speed;
+            if player_slot.player.x < left {
+                player_slot.player.x = left;
+            }
+            let right = right - player_slot.player.width;
+            if player_slot.player.x > right {
+                player_slot.player.x = right;
+            }
+        }
+    }
+}
+
+// Everything a tick reads or writes, for rolling back a mispredicted stretch
+// of online play. Session-long state (config, settings, the leaderboard, the
+// debug heatmap) stays out of it.
+#[cfg(feature = "netplay")]
+pub(crate) struct Snapshot {
+    players: Vec<PlayerSlot>,
+    objects: Vec<FallingObject>,
+    pending_spawns: Vec<PendingSpawn>,
+    floor_zones: Vec<FloorZone>,
+    blackout: Option<Blackout>,
+    wind: f64,
+    wind_target: f64,
+    wind_particles: Vec<WindParticle>, // Visual, but spawning them draws on the rng
+    popups: Vec<TextPopup>,            // Kept so replayed ticks don't repeat them
+    whooshes: Vec<Whoosh>,             // Likewise for near-miss streaks and zoom-punches
+    dying: Vec<DyingObject>,           // And for objects shrinking away
+    camera: Camera,
+    clutch_remaining: u32,
+    pace_marker: Option<PaceMarker>,
+    ticker: Ticker,
+    frame_count: u32,
+    wave: u32,
+    wave_timer: u32,
+    playing_phase: PlayingPhase,
+    difficulty_multiplier: f64,
+    spawn_meter: f64,
+    performance: PerformanceWindow,
+    team_combo: u32,
+    next_lane: usize,
+    dda_level: f64,
+    script_player: ScriptPlayer,
+    rng: StdRng,
+    input_log_len: usize, // The log only grows, so cutting it back is enough
+    final_scores: Vec<RunResult>,
+    run_time_ms: f64,
+    survival_awards: u32,
+    achievements: Achievements, // Rolled back so a guess can't earn anything for good
+    personal_best: PersonalBest,
+    unsaved_progress: bool,
+}
+
+// Turns elapsed browser time into a whole number of simulation ticks,
+// carrying the remainder over to the next frame.
+#[derive(Default)]
+pub(crate) struct FixedStep {
+    accumulator_ms: f64,
+    last_ms: Option<f64>,
+    stalled: bool, // Since take_stall last asked
+}
+
+impl FixedStep {
+    // `scale` stretches or shrinks elapsed time; 0 holds the simulation still
+    pub(crate) fn advance(&mut self, now_ms: f64, scale: f64) -> u32 {
+        let gap = self.last_ms.map_or(0.0, |last| now_ms - last);
+        self.last_ms = Some(now_ms);
+        let elapsed = if gap >= STALL_GAP_MS && scale > 0.0 {
+            self.stalled = true;
+            self.accumulator_ms = 0.0;
+            STALL_CATCH_UP_MS / scale
+        } else {
+            gap.clamp(0.0, MAX_FRAME_GAP_MS)
+        };
+        self.accumulator_ms += elapsed * scale;
+        let ticks = (self.accumulator_ms / TICK_MS) as u32;
+        self.accumulator_ms -= ticks as f64 * TICK_MS;
+        ticks
+    }
+
+    // Whether the clock has stalled since the last time this was asked
+    pub(crate) fn take_stall(&mut self) -> bool {
+        std::mem::take(&mut self.stalled)
+    }
+
+    // How much of the next tick has gone by, from 0 up to 1
+    pub(crate) fn blend(&self) -> f64 {
+        (self.accumulator_ms / TICK_MS).clamp(0.0, 1.0)
+    }
+}
+
+// FNV-1a, for state hashes that match across builds and machines, which the
+// standard library's keyed hasher doesn't promise. Floats are hashed by their
+// bits: the simulation keeps to arithmetic that's exact and repeatable on
+// every target (see sim_math), so lockstep peers in wasm and a replay checked
+// natively agree exactly, and any difference at all is a desync.
+pub(crate) struct StateHasher(u64);
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        StateHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StateHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headless() -> GameState {
+        GameState::new(GameConfig::default(), Rc::new(MemoryStore::default()))
+    }
+
+    #[test]
+    fn builds_and_ticks_without_a_browser() {
+        let mut state = headless();
+        assert!(matches!(state.phase, GamePhase::ModeSelect { .. }));
+        state.tick(&[]);
+
+        state.start_seeded_game(PlayerMode::Two, 3);
+        for _ in 0..600 {
+            state.tick(&[Command::MoveLeft(0), Command::MoveRight(1)]);
+        }
+        assert_eq!(state.frame_count, 600);
+        assert_eq!(state.input_log.len(), 600);
+        assert_eq!(state.players.len() + state.final_scores.len(), 2);
+    }
+
+    #[test]
+    fn same_seed_same_run() {
+        let hashes = || {
+            let mut state = headless();
+            state.start_seeded_game(PlayerMode::Single, 11);
+            (0..900)
+                .map(|tick| {
+                    let left = tick % 200 < 100;
+                    let command = if left {
+                        Command::MoveLeft(0)
+                    } else {
+                        Command::MoveRight(0)
+                    };
+                    state.tick(&[command])
+                })
+                .collect::<Vec<u64>>()
+        };
+        assert_eq!(hashes(), hashes());
+    }
+}
@@ -0,0 +1,233 @@
+// Golden frames, built with `--features golden-frames`. Load the page with
+// ?golden=check and instead of the game a handful of fixed scenes (menus, and
+// solo and versus runs stopped at set ticks) are drawn one at a time to an
+// offscreen canvas. Each frame's pixels are hashed and compared with the
+// hashes in assets/golden_frames.json, and any scene that draws differently
+// from when they were recorded is listed as failed, on the page and in the
+// console. Once a change to the drawing is meant, ?golden=record draws the
+// same scenes and logs a fresh set of hashes, as JSON to paste over the file.
+//
+// Scenes are built from the built-in config and a memory store, with seeded
+// runs, and drawn with plain shapes and the fallback monospace font, so one
+// browser draws them the same every time. Text rasterizes differently from
+// one browser or system to the next, though: record on the machine that
+// checks.
+
+use super::*;
+use std::collections::BTreeMap;
+use web_sys::UrlSearchParams;
+
+const GOLDEN_HASHES: &str = include_str!("../assets/golden_frames.json");
+const FRAME_SCALE: f64 = 2.0; // Canvas pixels per logical unit
+const REPORT_LINE_HEIGHT: f64 = 14.0;
+
+struct Scene {
+    name: &'static str,
+    build: fn() -> GameState,
+}
+
+const SCENES: [Scene; 4] = [
+    Scene {
+        name: "menu",
+        build: menu,
+    },
+    Scene {
+        name: "settings",
+        build: settings,
+    },
+    Scene {
+        name: "solo",
+        build: solo,
+    },
+    Scene {
+        name: "versus",
+        build: versus,
+    },
+];
+
+fn fresh_state() -> GameState {
+    GameState::new(GameConfig::default(), Rc::new(MemoryStore::default()))
+}
+
+fn menu() -> GameState {
+    let mut state = fresh_state();
+    state.back_to_menu();
+    state
+}
+
+fn settings() -> GameState {
+    let mut state = menu();
+    state.open_settings();
+    state
+}
+
+// A seeded run left to play itself with nobody moving
+fn run_for(mode: PlayerMode, seed: u64, ticks: u32) -> GameState {
+    let mut state = fresh_state();
+    state.start_seeded_game(mode, seed);
+    for _ in 0..ticks {
+        state.tick(&[]);
+    }
+    state
+}
+
+// Early in the first wave: the HUD and the first few drops
+fn solo() -> GameState {
+    run_for(PlayerMode::Single, 1, 240)
+}
+
+// Long enough in for thrown objects, wind and some hits
+fn versus() -> GameState {
+    run_for(PlayerMode::Two, 2, 1200)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum GoldenMode {
+    Check,
+    Record,
+}
+
+// Runs the golden frames in place of the game if the page asks for them;
+// true when it did
+pub(crate) fn run_from_page(canvas: &HtmlCanvasElement) -> Result<bool, JsValue> {
+    let search = web_sys::window().unwrap().location().search()?;
+    let mode = match UrlSearchParams::new_with_str(&search)?
+        .get("golden")
+        .as_deref()
+    {
+        Some("check") => GoldenMode::Check,
+        Some("record") => GoldenMode::Record,
+        _ => return Ok(false),
+    };
+    let mut hashes = Vec::new();
+    for scene in &SCENES {
+        hashes.push((scene.name, frame_hash(&(scene.build)())?));
+    }
+    let report = match mode {
+        GoldenMode::Check => check(&hashes),
+        GoldenMode::Record => record(&hashes),
+    };
+    show_report(canvas, &report);
+    Ok(true)
+}
+
+// Draws the state as the game would, at a fixed size, and hashes the pixels
+fn frame_hash(state: &GameState) -> Result<String, JsValue> {
+    let world = state.world;
+    let width = (world.width * FRAME_SCALE).round();
+    let height = (world.height * FRAME_SCALE).round();
+    let canvas = web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .create_element("canvas")?
+        .dyn_into::<HtmlCanvasElement>()?;
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+    let context = canvas
+        .get_context("2d")?
+        .unwrap()
+        .dyn_into::<CanvasRenderingContext2d>()?;
+
+    let view = Viewport::fit(world, width, height, 1.0);
+    let renderer = CanvasRenderer::new(context.clone());
+    view.begin_frame(&context);
+    renderer.begin_frame();
+    draw(&renderer, state);
+    view.end_frame(&context);
+
+    let pixels = context.get_image_data(0.0, 0.0, width, height)?.data();
+    let mut hasher = StateHasher::default();
+    hasher.write(&pixels);
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+// One line of the report, and whether it's a failure
+type ReportLine = (String, bool);
+
+fn check(hashes: &[(&str, String)]) -> Vec<ReportLine> {
+    let golden: HashMap<String, String> = match serde_json::from_str(GOLDEN_HASHES) {
+        Ok(golden) => golden,
+        Err(err) => return vec![(format!("golden_frames.json: {err}"), true)],
+    };
+    // Nothing to compare against is a failure too, not a pass
+    if golden.is_empty() {
+        return vec![
+            ("no golden frames recorded".to_string(), true),
+            (
+                "load ?golden=record on the reference machine".to_string(),
+                true,
+            ),
+            ("and commit the JSON it logs".to_string(), true),
+        ];
+    }
+
+    let mut lines: Vec<ReportLine> = hashes
+        .iter()
+        .map(|(name, hash)| match golden.get(*name) {
+            Some(expected) if expected == hash => (format!("PASS {name}"), false),
+            Some(expected) => (format!("FAIL {name}: {hash}, expected {expected}"), true),
+            None => (format!("FAIL {name}: {hash}, nothing recorded"), true),
+        })
+        .collect();
+    let failed = lines.iter().filter(|(_, failed)| *failed).count();
+    lines.push(if failed == 0 {
+        (format!("all {} scenes match", hashes.len()), false)
+    } else {
+        (format!("{failed} of {} scenes differ", hashes.len()), true)
+    });
+    lines
+}
+
+fn record(hashes: &[(&str, String)]) -> Vec<ReportLine> {
+    let recorded: BTreeMap<&str, &str> = hashes
+        .iter()
+        .map(|(name, hash)| (*name, hash.as_str()))
+        .collect();
+    let json = serde_json::to_string_pretty(&recorded).unwrap_or_default();
+    log(
+        LogLevel::Info,
+        "golden",
+        &format!("assets/golden_frames.json:\n{json}"),
+    );
+    let mut lines: Vec<ReportLine> = hashes
+        .iter()
+        .map(|(name, hash)| (format!("{name} {hash}"), false))
+        .collect();
+    lines.push(("copy the JSON from the console".to_string(), false));
+    lines
+}
+
+// Logs the report and puts it up on the page's canvas in place of the game
+fn show_report(canvas: &HtmlCanvasElement, report: &[ReportLine]) {
+    for (line, failed) in report {
+        let level = if *failed {
+            LogLevel::Error
+        } else {
+            LogLevel::Info
+        };
+        log(level, "golden", line);
+    }
+
+    let world = WorldConfig::default();
+    let viewport = Cell::new(Viewport::default());
+    fit_canvas(canvas, &viewport, world, 1.0);
+    let Ok(Some(context)) = canvas.get_context("2d") else {
+        return;
+    };
+    let Ok(context) = context.dyn_into::<CanvasRenderingContext2d>() else {
+        return;
+    };
+    let view = viewport.get();
+    let renderer = CanvasRenderer::new(context.clone());
+    view.begin_frame(&context);
+    renderer.begin_frame();
+    renderer.clear("#111");
+    renderer.text("GOLDEN FRAMES", 10.0, 20.0, 10.0, "#fff", Align::Left);
+    for (i, (line, failed)) in report.iter().enumerate() {
+        let color = if *failed { "#ff4444" } else { "#4caf50" };
+        let y = 40.0 + i as f64 * REPORT_LINE_HEIGHT;
+        renderer.text(line, 10.0, y, 7.0, color, Align::Left);
+    }
+    view.end_frame(&context);
+}
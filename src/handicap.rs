@@ -0,0 +1,148 @@
+// Per-player handicaps for 2P games, so a parent and a kid can play each
+// other and both stand a chance. Each player picks one on the ready screen
+// before a local two player game with their own d-pad and readies up with A;
+// the picks stay for the rest of the session. Handicapped scores are flagged
+// on the leaderboard like mutated ones.
+
+use super::*;
+
+const EXTRA_HEALTH: i32 = 2;
+const SCORE_BOOST: f64 = 1.25;
+const SLOW_FALL_SCALE: f64 = 0.7; // Fall speed of drops over a slowed player
+const SLOW_REACH: f64 = 20.0; // How far either side of the player a drop counts as over them
+
+#[derive(Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Handicap {
+    #[default]
+    None,
+    ExtraHealth,
+    ScoreBoost,
+    SlowDrops,
+}
+
+const HANDICAPS: [Handicap; 4] = [
+    Handicap::None,
+    Handicap::ExtraHealth,
+    Handicap::ScoreBoost,
+    Handicap::SlowDrops,
+];
+
+impl Handicap {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Handicap::None => "No handicap",
+            Handicap::ExtraHealth => "+2 health",
+            Handicap::ScoreBoost => "+25% score",
+            Handicap::SlowDrops => "Slow drops",
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            Handicap::None => "Even match",
+            Handicap::ExtraHealth => "Two more hearts",
+            Handicap::ScoreBoost => "Points count extra",
+            Handicap::SlowDrops => "Drops over you fall slower",
+        }
+    }
+
+    fn step(&self, by: usize) -> Self {
+        let i = HANDICAPS.iter().position(|h| h == self).unwrap_or(0);
+        HANDICAPS[(i + by) % HANDICAPS.len()]
+    }
+
+    fn next(&self) -> Self {
+        self.step(1)
+    }
+
+    fn previous(&self) -> Self {
+        self.step(HANDICAPS.len() - 1)
+    }
+}
+
+impl PlayerSlot {
+    // Adds points, scaled up for a player with the score boost. Penalties
+    // are taken as they are.
+    pub(crate) fn earn(&mut self, points: i32) {
+        if points <= 0 {
+            self.score += points;
+            return;
+        }
+        let boost = if self.handicap == Handicap::ScoreBoost {
+            SCORE_BOOST
+        } else {
+            1.0
+        };
+        self.score += (points as f64 * boost * self.loop_multiplier).round() as i32;
+    }
+}
+
+impl GameState {
+    // Local two player games start here rather than straight into play
+    pub(crate) fn open_ready(&mut self) {
+        self.bot = None;
+        self.phase = GamePhase::Ready { ready: [false; 2] };
+    }
+
+    pub(crate) fn handle_ready(&mut self, commands: &[Command]) {
+        let bot_index = self.bot.as_ref().map(|bot| bot.player_index);
+        let GamePhase::Ready { ready } = &mut self.phase else {
+            return;
+        };
+        for command in commands {
+            match *command {
+                // A player who's readied up has to un-ready to change
+                Command::MoveLeft(i) if !ready[i] => {
+                    self.handicaps[i] = self.handicaps[i].previous();
+                }
+                Command::MoveRight(i) if !ready[i] => {
+                    self.handicaps[i] = self.handicaps[i].next();
+                }
+                Command::Ready(i) if bot_index != Some(i) => ready[i] = !ready[i],
+                // Against the bot, P1's up and down pick how good it is
+                Command::CursorUp | Command::CursorDown if !ready[0] => {
+                    if let Some(bot) = &mut self.bot {
+                        self.bot_level = if *command == Command::CursorUp {
+                            bot.level.previous()
+                        } else {
+                            bot.level.next()
+                        };
+                        *bot = AiController::new(bot.player_index, self.bot_level);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if ready.iter().all(|&ready| ready) {
+            self.start_new_game(PlayerMode::Two);
+        }
+    }
+
+    // Handicaps only count in two player runs
+    pub(crate) fn apply_handicaps(&mut self) {
+        if self.mode != PlayerMode::Two {
+            return;
+        }
+        for slot in &mut self.players {
+            slot.handicap = self.handicaps[slot.player_index];
+            if slot.handicap == Handicap::ExtraHealth {
+                slot.health += EXTRA_HEALTH;
+            }
+        }
+    }
+
+    // How much slower an object falls this tick: it crawls while it's over a
+    // living player with slow drops
+    pub(crate) fn fall_scale(&self, obj: &FallingObject) -> f64 {
+        let over_slowed = self
+            .players
+            .iter()
+            .filter(|slot| slot.handicap == Handicap::SlowDrops && slot.health > 0)
+            .any(|slot| {
+                obj.x < slot.player.x + slot.player.width + SLOW_REACH
+                    && obj.x + obj.width > slot.player.x - SLOW_REACH
+            });
+        if over_slowed { SLOW_FALL_SCALE } else { 1.0 }
+    }
+}
@@ -0,0 +1,239 @@
+// How to play: what the controls are, what falls and how scoring works, a
+// page each. It's on the menu, and comes up by itself before the first game
+// played on a machine, then carries on into whichever game was picked.
+
+use super::*;
+
+const PAGE_TITLES: [&str; 3] = ["CONTROLS", "WHAT FALLS", "SCORING"];
+
+// What each behavior is called on the legend page, and what it does to you
+const LEGEND: [(ObjectType, &str, &str); 5] = [
+    (ObjectType::GoodDeal, "Deal", "Catch it for points"),
+    (ObjectType::BadItem, "Hazard", "Dodge it or lose a heart"),
+    (
+        ObjectType::EnergyDrink,
+        "Energy drink",
+        "Power-up: a burst of speed",
+    ),
+    (
+        ObjectType::PrankFlyer,
+        "Prank flyer",
+        "Flips your controls for a bit",
+    ),
+    (
+        ObjectType::SpilledSoda,
+        "Spilled soda",
+        "Leaves a sticky patch on the floor",
+    ),
+];
+
+impl MenuItem {
+    // Whether choosing this goes straight into a game
+    pub(crate) fn starts_game(self) -> bool {
+        matches!(
+            self,
+            MenuItem::Play(_) | MenuItem::VersusBot | MenuItem::Alternate
+        )
+    }
+}
+
+impl GameState {
+    // `then` is the menu item to carry on with afterwards, if any; without
+    // one it goes back to the menu
+    pub(crate) fn open_how_to_play(&mut self, then: Option<MenuItem>) {
+        self.phase = GamePhase::HowToPlay { page: 0, then };
+        if !self.seen_how_to_play {
+            self.seen_how_to_play = true;
+            mark_how_to_play_seen(&*self.store);
+        }
+    }
+
+    // Returns the menu item to carry on with once the last page is done
+    pub(crate) fn handle_how_to_play(&mut self, commands: &[Command]) -> Option<MenuItem> {
+        let GamePhase::HowToPlay { page, then } = &mut self.phase else {
+            return None;
+        };
+        let last = PAGE_TITLES.len() - 1;
+        if menu_prev(commands) {
+            *page = page.saturating_sub(1);
+        } else if menu_next(commands) {
+            *page = (*page + 1).min(last);
+        } else if commands.contains(&Command::Confirm) {
+            if *page < last {
+                *page += 1;
+            } else {
+                let then = *then;
+                self.phase = GamePhase::main_menu();
+                return then;
+            }
+        }
+        None
+    }
+}
+
+pub(crate) fn draw_how_to_play(
+    ctx: &dyn Renderer,
+    state: &GameState,
+    page: usize,
+    then: Option<MenuItem>,
+) {
+    let world = state.world;
+    let center = world.width / 2.0;
+    ctx.text("HOW TO PLAY", center, 28.0, 18.0, "#fff", Align::Center);
+    ctx.text(
+        &format!("{} ({}/{})", PAGE_TITLES[page], page + 1, PAGE_TITLES.len()),
+        center,
+        44.0,
+        10.0,
+        "#ffd700",
+        Align::Center,
+    );
+
+    match page {
+        0 => draw_controls(ctx, world),
+        1 => draw_legend(ctx, state),
+        _ => draw_scoring(ctx, world, &state.config.scoring, state.miss_rule_on()),
+    }
+
+    let done = if page + 1 < PAGE_TITLES.len() {
+        "Next"
+    } else if then.is_some() {
+        "Play"
+    } else {
+        "Done"
+    };
+    ctx.text(
+        &format!("←/→: Page | A: {done}"),
+        10.0,
+        world.height - 10.0,
+        8.0,
+        "#888",
+        Align::Left,
+    );
+}
+
+// The stick, A and the start buttons drawn as they sit on the cabinet, each
+// with what it does underneath, then the keyboard equivalents
+fn draw_controls(ctx: &dyn Renderer, world: WorldConfig) {
+    let y = 92.0;
+    let label_y = y + 34.0;
+
+    let stick_x = world.width * 0.2;
+    ctx.ring(stick_x, y, 16.0, "#888", 2.0);
+    ctx.circle(stick_x, y, 7.0, "#fff");
+    ctx.text(
+        "\u{25c0}",
+        stick_x - 24.0,
+        y + 4.0,
+        10.0,
+        "#aaa",
+        Align::Center,
+    );
+    ctx.text(
+        "\u{25b6}",
+        stick_x + 24.0,
+        y + 4.0,
+        10.0,
+        "#aaa",
+        Align::Center,
+    );
+    ctx.text("MOVE", stick_x, label_y, 10.0, "#fff", Align::Center);
+
+    let a_x = world.width * 0.5;
+    ctx.circle(a_x, y, 14.0, "#ff4444");
+    ctx.text("A", a_x, y + 5.0, 14.0, "#fff", Align::Center);
+    ctx.text("PICK / READY", a_x, label_y, 10.0, "#fff", Align::Center);
+
+    let start_x = world.width * 0.8;
+    for (label, dx) in [("1P", -14.0), ("2P", 14.0)] {
+        ctx.rect(start_x + dx - 11.0, y - 8.0, 22.0, 16.0, "#444");
+        ctx.text(label, start_x + dx, y + 4.0, 8.0, "#fff", Align::Center);
+    }
+    ctx.text("START", start_x, label_y, 10.0, "#fff", Align::Center);
+
+    let keys = [
+        "Keyboard: ←/→ move, Left Ctrl is A",
+        "1 and 2 are the 1P and 2P buttons",
+        "Player 2: D/G move, the A key is A",
+        "On a touch screen, buttons appear on it",
+    ];
+    for (i, line) in keys.iter().enumerate() {
+        let line_y = 160.0 + i as f64 * 14.0;
+        ctx.text(line, world.width / 2.0, line_y, 8.0, "#aaa", Align::Center);
+    }
+}
+
+// Each kind of thing that falls, drawn the way it looks in a game. Behaviors
+// the object catalog doesn't use aren't listed.
+fn draw_legend(ctx: &dyn Renderer, state: &GameState) {
+    let objects = &state.config.objects;
+    let left = state.world.centered(220.0);
+    let mut sprites = Vec::new();
+    let mut y = 62.0;
+    for (behavior, name, description) in LEGEND {
+        let Some(kind) = objects.first_of(behavior) else {
+            continue;
+        };
+        sprites.push(SpriteDraw {
+            sprite: &objects.get(kind).sprite,
+            x: left,
+            y,
+            w: 20.0,
+            h: 20.0,
+        });
+        ctx.text(name, left + 30.0, y + 8.0, 10.0, "#fff", Align::Left);
+        ctx.text(description, left + 30.0, y + 20.0, 8.0, "#aaa", Align::Left);
+        y += 32.0;
+    }
+    ctx.sprites(&sprites);
+}
+
+// Read off the scoring rules in play, so a page that changes them gets an
+// accurate page here too
+fn draw_scoring(ctx: &dyn Renderer, world: WorldConfig, rules: &ScoringRules, miss_rule: bool) {
+    let mut lines = vec![
+        format!("A deal is worth {} points", rules.deal_points),
+        format!("+{} for catching it dead center", rules.perfect_bonus),
+    ];
+    if !rules.combo_breakpoints.is_empty() {
+        let steps: Vec<String> = rules
+            .combo_breakpoints
+            .iter()
+            .enumerate()
+            .map(|(i, combo)| format!("x{} at {combo}", i + 2))
+            .collect();
+        lines.push(format!("Catches in a row: {}", steps.join(", ")));
+    }
+    if rules.miss_penalty > 0 {
+        lines.push(format!(
+            "A dropped deal costs {} points",
+            rules.miss_penalty
+        ));
+    }
+    if miss_rule {
+        lines.push("A dropped deal halves your combo".to_string());
+        lines.push(format!(
+            "Drop more than {} in a row: -{} each",
+            rules.misses_allowed, rules.miss_streak_penalty
+        ));
+    }
+    lines.push(format!(
+        "A hazard costs {} heart(s) and your combo",
+        rules.hazard_damage
+    ));
+    lines.push(format!(
+        "{} catches without a hit win a heart back",
+        rules.heal_streak
+    ));
+    lines.push(format!(
+        "+{} every {} seconds you stay alive",
+        rules.survival_points,
+        (SURVIVAL_INTERVAL_MS / 1000.0) as u32
+    ));
+    lines.push("Run out of hearts and it's over".to_string());
+
+    for (i, line) in lines.iter().enumerate() {
+        let y = 70.0 + i as f64 * 16.0;
+        ctx.text(line, world.width / 2.0, y, 10.0, "#fff", Align::Center);
+    }
+}
@@ -0,0 +1,222 @@
+// Input backends. Every registered InputSource is polled once per frame and
+// the results are OR-ed into a single InputSnapshot, so adding a backend means
+// implementing the trait and registering it in App::new().
+
+use super::*;
+
+const GAMEPAD_DEADZONE: f64 = 0.5;
+
+pub(crate) trait InputSource {
+    // The buttons this source is holding right now
+    fn poll(&self) -> InputSnapshot;
+}
+
+#[derive(Default)]
+pub(crate) struct InputSources {
+    sources: Vec<Box<dyn InputSource>>,
+}
+
+impl InputSources {
+    pub(crate) fn register(&mut self, source: impl InputSource + 'static) {
+        self.sources.push(Box::new(source));
+    }
+
+    pub(crate) fn poll(&self) -> InputSnapshot {
+        let mut snapshot = InputSnapshot::default();
+        for source in &self.sources {
+            snapshot.merge(&source.poll());
+        }
+        snapshot
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+pub(crate) struct InputSnapshot {
+    pub(crate) system_one_player: bool,
+    pub(crate) system_two_player: bool,
+    pub(crate) player1_left: bool,
+    pub(crate) player1_right: bool,
+    pub(crate) player1_up: bool,
+    pub(crate) player1_down: bool,
+    pub(crate) player1_a: bool,
+    pub(crate) player2_left: bool,
+    pub(crate) player2_right: bool,
+    pub(crate) player2_a: bool,
+}
+
+impl InputSnapshot {
+    // Whether any button at all is held
+    pub(crate) fn any(&self) -> bool {
+        self.system_one_player
+            || self.system_two_player
+            || self.player1_left
+            || self.player1_right
+            || self.player1_up
+            || self.player1_down
+            || self.player1_a
+            || self.player2_left
+            || self.player2_right
+            || self.player2_a
+    }
+
+    fn merge(&mut self, other: &InputSnapshot) {
+        self.system_one_player |= other.system_one_player;
+        self.system_two_player |= other.system_two_player;
+        self.player1_left |= other.player1_left;
+        self.player1_right |= other.player1_right;
+        self.player1_up |= other.player1_up;
+        self.player1_down |= other.player1_down;
+        self.player1_a |= other.player1_a;
+        self.player2_left |= other.player2_left;
+        self.player2_right |= other.player2_right;
+        self.player2_a |= other.player2_a;
+    }
+}
+
+// The keyboard's buttons are written straight into a snapshot as keys go
+// down and up, so polling it is a copy.
+impl InputSnapshot {
+    fn handle_code(&mut self, code: &str, pressed: bool) -> bool {
+        match code {
+            "Digit1" => {
+                self.system_one_player = pressed;
+                true
+            }
+            "Digit2" => {
+                self.system_two_player = pressed;
+                true
+            }
+            "ArrowLeft" => {
+                self.player1_left = pressed;
+                true
+            }
+            "ArrowRight" => {
+                self.player1_right = pressed;
+                true
+            }
+            "ArrowUp" => {
+                self.player1_up = pressed;
+                true
+            }
+            "ArrowDown" => {
+                self.player1_down = pressed;
+                true
+            }
+            "ControlLeft" => {
+                self.player1_a = pressed;
+                true
+            }
+            "KeyD" => {
+                self.player2_left = pressed;
+                true
+            }
+            "KeyG" => {
+                self.player2_right = pressed;
+                true
+            }
+            "KeyA" => {
+                self.player2_a = pressed;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+pub(crate) struct Keyboard {
+    held: Rc<Cell<InputSnapshot>>,
+}
+
+impl Keyboard {
+    pub(crate) fn listen() -> Result<Self, JsValue> {
+        let held = Rc::new(Cell::new(InputSnapshot::default()));
+        setup_keyboard_listeners(&held)?;
+        Ok(Keyboard { held })
+    }
+}
+
+impl InputSource for Keyboard {
+    fn poll(&self) -> InputSnapshot {
+        self.held.get()
+    }
+}
+
+fn setup_keyboard_listeners(held: &Rc<Cell<InputSnapshot>>) -> Result<(), JsValue> {
+    let window = web_sys::window().unwrap();
+    for (event_name, pressed) in [("keydown", true), ("keyup", false)] {
+        let held = held.clone();
+        let handler = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+            let mut snapshot = held.get();
+            if snapshot.handle_code(&event.code(), pressed) {
+                held.set(snapshot);
+                event.prevent_default();
+            }
+        }) as Box<dyn FnMut(_)>);
+        window.add_event_listener_with_callback(event_name, handler.as_ref().unchecked_ref())?;
+        handler.forget();
+    }
+    Ok(())
+}
+
+// The cabinet's own controls, once the plugin hands them over
+impl InputSource for ClassicController {
+    fn poll(&self) -> InputSnapshot {
+        let ctrl = self.state();
+        InputSnapshot {
+            system_one_player: ctrl.system_one_player,
+            system_two_player: ctrl.system_two_player,
+            player1_left: ctrl.player1_left,
+            player1_right: ctrl.player1_right,
+            player1_a: ctrl.player1_a,
+            player2_left: ctrl.player2_left,
+            player2_right: ctrl.player2_right,
+            player2_a: ctrl.player2_a,
+            ..InputSnapshot::default()
+        }
+    }
+}
+
+// Browser gamepads in the standard layout: the first pad is player 1, the
+// second player 2. Back and Start act as the 1P and 2P system buttons.
+pub(crate) struct Gamepads;
+
+impl InputSource for Gamepads {
+    fn poll(&self) -> InputSnapshot {
+        let mut snapshot = InputSnapshot::default();
+        let Ok(pads) = web_sys::window().unwrap().navigator().get_gamepads() else {
+            return snapshot;
+        };
+        // Disconnected slots come back as null
+        let pads = pads
+            .iter()
+            .filter_map(|pad| pad.dyn_into::<web_sys::Gamepad>().ok());
+        for (index, pad) in pads.take(2).enumerate() {
+            let buttons = pad.buttons();
+            let pressed = |button: u32| {
+                buttons
+                    .get(button)
+                    .dyn_into::<web_sys::GamepadButton>()
+                    .is_ok_and(|button| button.pressed())
+            };
+            let stick_x = pad.axes().get(0).as_f64().unwrap_or(0.0);
+            let stick_y = pad.axes().get(1).as_f64().unwrap_or(0.0);
+            let left = pressed(14) || stick_x < -GAMEPAD_DEADZONE;
+            let right = pressed(15) || stick_x > GAMEPAD_DEADZONE;
+
+            snapshot.system_one_player |= pressed(8);
+            snapshot.system_two_player |= pressed(9);
+            if index == 0 {
+                snapshot.player1_left = left;
+                snapshot.player1_right = right;
+                snapshot.player1_up = pressed(12) || stick_y < -GAMEPAD_DEADZONE;
+                snapshot.player1_down = pressed(13) || stick_y > GAMEPAD_DEADZONE;
+                snapshot.player1_a = pressed(0);
+            } else {
+                snapshot.player2_left = left;
+                snapshot.player2_right = right;
+                snapshot.player2_a = pressed(0);
+            }
+        }
+        snapshot
+    }
+}
@@ -0,0 +1,59 @@
+// Spawn lanes, a setting for fairer two player games: random drops take
+// turns between the left half of the playfield (P1's) and the right (P2's),
+// each outlined in the color of the player it's meant for, so whoever
+// stands in the middle can't take everything. Throws and lobs cross the
+// whole floor and aren't anyone's; nor are designed wave spawns.
+
+use super::*;
+
+const LANE_OUTLINE_WIDTH: f64 = 1.5;
+
+// Left and right edges of a player's half
+pub(crate) fn lane_span(world: WorldConfig, lane: usize) -> (f64, f64) {
+    let half = world.width / 2.0;
+    (lane as f64 * half, (lane + 1) as f64 * half)
+}
+
+impl GameState {
+    pub(crate) fn lanes_active(&self) -> bool {
+        self.settings.spawn_lanes && self.mode == PlayerMode::Two
+    }
+
+    // Whose lane the next random drop is in, if lanes are on. A split floor
+    // takes turns the same way.
+    pub(crate) fn drop_lane(&self) -> Option<usize> {
+        (self.lanes_active() || self.split_active()).then_some(self.next_lane)
+    }
+
+    // Where a drop in `lane` may land: anywhere it fits, or anywhere in
+    // that half
+    pub(crate) fn drop_span(&self, lane: Option<usize>) -> (f64, f64) {
+        lane.map_or((0.0, self.world.width), |lane| lane_span(self.world, lane))
+    }
+
+    // A drop went into the lane, so the next goes to the other player
+    pub(crate) fn advance_lane(&mut self) {
+        self.next_lane = 1 - self.next_lane;
+    }
+}
+
+// Over the objects, so a sprite never hides whose it is. On a split floor
+// the divider already says.
+pub(crate) fn draw_lane_outlines(ctx: &dyn Renderer, state: &GameState) {
+    if state.split_active() {
+        return;
+    }
+    for obj in &state.objects {
+        let Some(lane) = obj.lane else {
+            continue;
+        };
+        ctx.stroke_rect(
+            obj.x - 1.0,
+            obj.y - 1.0,
+            obj.width + 2.0,
+            obj.height + 2.0,
+            PLAYER_COLORS[lane],
+            LANE_OUTLINE_WIDTH,
+        );
+    }
+}
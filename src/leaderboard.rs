@@ -0,0 +1,224 @@
+// The full leaderboard, from the game over screen: every saved entry on a
+// board rather than the top five, scrolled with the d-pad. It opens on the
+// last run's entries, which are highlighted, so there's no hunting for them.
+//
+// Alongside the saved boards there's TODAY'S BEST: the best score since the
+// page loaded, kept only in memory, so whoever's at the cabinet now has a
+// score within reach and restarting the cabinet starts it over.
+
+use super::*;
+
+const ROW_HEIGHT: f64 = 11.0;
+const ROWS_TOP: f64 = 52.0;
+
+// One line of a board, and whether it's the viewer's own: their latest
+// entries here, or this game's on the cabinet board
+pub(crate) struct BoardRow {
+    pub(crate) text: String,
+    pub(crate) own: bool,
+}
+
+impl BoardRow {
+    pub(crate) fn color(&self) -> &'static str {
+        if self.own { "#ff0" } else { "#fff" }
+    }
+}
+
+pub(crate) fn board_rows(state: &GameState, tab: LeaderboardCategory) -> Vec<BoardRow> {
+    #[cfg(feature = "cabinet-sync")]
+    if tab == LeaderboardCategory::Cabinet {
+        return state
+            .cabinet
+            .champions
+            .iter()
+            .enumerate()
+            .map(|(i, champion)| BoardRow {
+                text: format!(
+                    "{}. {} {} - {}",
+                    i + 1,
+                    board_name(&champion.name),
+                    champion.score,
+                    champion.game
+                ),
+                own: champion.game == cabinet::GAME_ID,
+            })
+            .collect();
+    }
+
+    state
+        .ranked_leaderboard(tab)
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let mode_text = match entry.mode {
+                PlayerMode::Single => "1P",
+                PlayerMode::Two => "2P",
+                PlayerMode::Team => "TEAM",
+            };
+            let mut name = board_name(&entry.name);
+            if entry.mutated {
+                name.push('*');
+            }
+            if entry.new_game_plus > 0 {
+                name.push_str(&format!("+{}", entry.new_game_plus));
+            }
+            let text = if tab == LeaderboardCategory::Survival {
+                format!(
+                    "{}. {} {} ({}) {} pts",
+                    i + 1,
+                    name,
+                    format_duration(entry.time_survived_ms),
+                    mode_text,
+                    entry.score
+                )
+            } else {
+                format!(
+                    "{}. {} {} ({}) {}",
+                    i + 1,
+                    name,
+                    entry.score,
+                    mode_text,
+                    format_duration(entry.time_survived_ms)
+                )
+            };
+            BoardRow {
+                text,
+                own: entry.latest,
+            }
+        })
+        .collect()
+}
+
+// Best named score since the page loaded, team runs aside
+#[derive(Clone)]
+pub(crate) struct SessionBest {
+    pub(crate) name: String,
+    pub(crate) score: i32,
+}
+
+impl SessionBest {
+    pub(crate) fn label(&self) -> String {
+        format!("TODAY'S BEST: {} {}", board_name(&self.name), self.score)
+    }
+}
+
+// How many rows fit between the title and the controls hint
+fn visible_rows(world: WorldConfig) -> usize {
+    ((world.height - ROWS_TOP - 24.0) / ROW_HEIGHT) as usize
+}
+
+impl GameState {
+    pub(crate) fn record_session_best(&mut self, entry: &LeaderboardEntry) {
+        let beaten = self
+            .session_best
+            .as_ref()
+            .is_none_or(|best| entry.score > best.score);
+        if entry.mode != PlayerMode::Team && beaten {
+            self.session_best = Some(SessionBest {
+                name: entry.name.clone(),
+                score: entry.score,
+            });
+        }
+    }
+
+    fn max_scroll(&self, tab: LeaderboardCategory) -> usize {
+        board_rows(self, tab)
+            .len()
+            .saturating_sub(visible_rows(self.world))
+    }
+
+    // Scrolled so the viewer's first entry on the board sits mid-screen
+    pub(crate) fn open_leaderboard(&mut self, tab: LeaderboardCategory) {
+        let own = board_rows(self, tab).iter().position(|row| row.own);
+        let scroll = own
+            .unwrap_or(0)
+            .saturating_sub(visible_rows(self.world) / 2)
+            .min(self.max_scroll(tab));
+        self.phase = GamePhase::Leaderboard { tab, scroll };
+    }
+
+    pub(crate) fn handle_leaderboard(&mut self, commands: &[Command]) {
+        let GamePhase::Leaderboard { tab, .. } = self.phase else {
+            return;
+        };
+        if commands.contains(&Command::Confirm) {
+            self.back_to_menu();
+            return;
+        }
+        if commands.contains(&Command::CursorLeft) {
+            self.open_leaderboard(tab.previous());
+            return;
+        }
+        if commands.contains(&Command::CursorRight) {
+            self.open_leaderboard(tab.next());
+            return;
+        }
+        let max_scroll = self.max_scroll(tab);
+        let GamePhase::Leaderboard { scroll, .. } = &mut self.phase else {
+            return;
+        };
+        if commands.contains(&Command::CursorUp) {
+            *scroll = scroll.saturating_sub(1);
+        } else if commands.contains(&Command::CursorDown) {
+            *scroll = (*scroll + 1).min(max_scroll);
+        }
+    }
+}
+
+pub(crate) fn draw_leaderboard(
+    ctx: &dyn Renderer,
+    state: &GameState,
+    tab: LeaderboardCategory,
+    scroll: usize,
+) {
+    let world = state.world;
+    ctx.text(
+        &format!("< {} >", tab.title()),
+        world.width / 2.0,
+        32.0,
+        12.0,
+        "#fff",
+        Align::Center,
+    );
+
+    let rows = board_rows(state, tab);
+    if rows.is_empty() {
+        let empty = match tab {
+            #[cfg(feature = "cabinet-sync")]
+            LeaderboardCategory::Cabinet => "Waiting for the cabinet...",
+            _ => "No scores yet",
+        };
+        ctx.text(empty, 10.0, ROWS_TOP + ROW_HEIGHT, 9.0, "#888", Align::Left);
+    }
+    let shown = visible_rows(world);
+    for (i, row) in rows.iter().skip(scroll).take(shown).enumerate() {
+        let y = ROWS_TOP + (i + 1) as f64 * ROW_HEIGHT;
+        ctx.text(&row.text, 10.0, y, 9.0, row.color(), Align::Left);
+    }
+
+    // Arrows on the right when there's more above or below
+    let arrow_x = world.width - 10.0;
+    if scroll > 0 {
+        ctx.text(
+            "\u{25b2}",
+            arrow_x,
+            ROWS_TOP + ROW_HEIGHT,
+            8.0,
+            "#888",
+            Align::Right,
+        );
+    }
+    if scroll + shown < rows.len() {
+        let y = ROWS_TOP + shown as f64 * ROW_HEIGHT;
+        ctx.text("\u{25bc}", arrow_x, y, 8.0, "#888", Align::Right);
+    }
+
+    ctx.text(
+        "↑/↓: Scroll | ←/→: Board | A: Menu",
+        10.0,
+        world.height - 10.0,
+        8.0,
+        "#888",
+        Align::Left,
+    );
+}
@@ -0,0 +1,75 @@
+// Loading: whatever the page asks for beyond the game itself (the sprite
+// atlas, the arcade font, the cabinet's board) is fetched behind a progress
+// bar before the menu comes up, so the menu never shows with shapes where the
+// art should be or a blank where its text should be. Everything has a
+// fallback, so a load that's still going when time runs out is left to finish
+// on its own and the menu comes up anyway. The splash screen follows, or
+// comes straight away for a page that asks for nothing.
+
+use super::*;
+
+const LOADING_TIMEOUT_FRAMES: u32 = 300;
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum LoadStatus {
+    Loading,
+    Ready,
+    Failed,
+}
+
+// One thing being loaded, shared with whatever's loading it
+pub(crate) type Preload = Rc<Cell<LoadStatus>>;
+
+impl GameState {
+    pub(crate) fn start_loading(&mut self, total: usize) {
+        if total > 0 {
+            self.phase = GamePhase::Loading {
+                loaded: 0,
+                total,
+                frames: 0,
+            };
+        }
+    }
+
+    // Called once a frame; moves on to the splash once everything has loaded
+    // or failed, or the time's up
+    pub(crate) fn update_loading(&mut self, preloads: &[Preload]) {
+        let GamePhase::Loading {
+            loaded,
+            total,
+            frames,
+        } = &mut self.phase
+        else {
+            return;
+        };
+        *loaded = preloads
+            .iter()
+            .filter(|preload| preload.get() != LoadStatus::Loading)
+            .count();
+        *frames += 1;
+        if *loaded < *total && *frames < LOADING_TIMEOUT_FRAMES {
+            return;
+        }
+        if *loaded < *total {
+            let message = format!("gave up waiting on {} of {total} loads", *total - *loaded);
+            log(LogLevel::Warn, "loading", &message);
+        }
+        self.show_splash();
+    }
+}
+
+pub(crate) fn draw_loading(ctx: &dyn Renderer, world: WorldConfig, loaded: usize, total: usize) {
+    let width = world.width * 0.6;
+    let x = (world.width - width) / 2.0;
+    let y = world.height / 2.0;
+    ctx.text(
+        "LOADING",
+        world.width / 2.0,
+        y - 10.0,
+        10.0,
+        "#fff",
+        Align::Center,
+    );
+    ctx.rect(x, y, width, 6.0, "#333");
+    ctx.rect(x, y, width * loaded as f64 / total as f64, 6.0, "#7fff7f");
+}
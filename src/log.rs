@@ -0,0 +1,146 @@
+// Logging: every message the game has for whoever's looking goes through
+// here, tagged with a level and the part of the game it came from. Messages
+// at or above a module's level go to the browser console (stderr natively)
+// and into a ring buffer of the most recent ones, which the debug console's
+// `log` command reads back. Everything logs at info and up unless the page
+// config's "log" option or the debug console says otherwise:
+//
+//   "log": { "default": "warn", "storage": "debug" }
+
+use super::*;
+
+const MAX_ENTRIES: usize = 100;
+
+#[derive(Copy, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        }
+    }
+
+    #[cfg(feature = "debug-console")]
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        [
+            LogLevel::Error,
+            LogLevel::Warn,
+            LogLevel::Info,
+            LogLevel::Debug,
+        ]
+        .into_iter()
+        .find(|level| level.name() == name)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct LogEntry {
+    pub(crate) level: LogLevel,
+    pub(crate) module: &'static str,
+    pub(crate) message: String,
+}
+
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}: {}",
+            self.level.name(),
+            self.module,
+            self.message
+        )
+    }
+}
+
+struct Logger {
+    default: LogLevel,
+    modules: HashMap<String, LogLevel>, // Overrides of the default, by module
+    entries: VecDeque<LogEntry>,
+}
+
+thread_local! {
+    static LOGGER: RefCell<Logger> = RefCell::new(Logger {
+        default: LogLevel::Info,
+        modules: HashMap::new(),
+        entries: VecDeque::new(),
+    });
+}
+
+pub(crate) fn log(level: LogLevel, module: &'static str, message: &str) {
+    let entry = LogEntry {
+        level,
+        module,
+        message: message.to_string(),
+    };
+    let shown = LOGGER.with(|logger| {
+        // A panic while the logger is busy still gets to the console
+        let Ok(mut logger) = logger.try_borrow_mut() else {
+            return true;
+        };
+        let wanted = logger
+            .modules
+            .get(module)
+            .copied()
+            .unwrap_or(logger.default);
+        if level > wanted {
+            return false;
+        }
+        if logger.entries.len() == MAX_ENTRIES {
+            logger.entries.pop_front();
+        }
+        logger.entries.push_back(entry.clone());
+        true
+    });
+    if shown {
+        write_console(&entry);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_console(entry: &LogEntry) {
+    let text = JsValue::from_str(&entry.to_string());
+    match entry.level {
+        LogLevel::Error => web_sys::console::error_1(&text),
+        LogLevel::Warn => web_sys::console::warn_1(&text),
+        LogLevel::Info => web_sys::console::info_1(&text),
+        LogLevel::Debug => web_sys::console::debug_1(&text),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_console(entry: &LogEntry) {
+    eprintln!("{entry}");
+}
+
+// The level a module logs at; "default" sets it for every module without
+// one of its own
+pub(crate) fn set_log_level(module: &str, level: LogLevel) {
+    LOGGER.with(|logger| {
+        let mut logger = logger.borrow_mut();
+        if module == "default" {
+            logger.default = level;
+        } else {
+            logger.modules.insert(module.to_string(), level);
+        }
+    });
+}
+
+// The last `count` entries, oldest first
+#[cfg(feature = "debug-console")]
+pub(crate) fn recent_log(count: usize) -> Vec<LogEntry> {
+    LOGGER.with(|logger| {
+        let logger = logger.borrow();
+        let skip = logger.entries.len().saturating_sub(count);
+        logger.entries.iter().skip(skip).cloned().collect()
+    })
+}
@@ -0,0 +1,48 @@
+// The miss rule: deals that reach the floor uncaught count against whoever
+// was nearest. A deal on the floor is out of play, so a miss is never undone
+// by a catch a tick later. Each miss halves their combo, and once they've let
+// more than a few through in a row ("missesAllowed" in the scoring rules)
+// every further drop also costs points, until they catch one. It's on by
+// default on hard, and a page can turn it on or off for any difficulty with
+// "missRule".
+
+use super::*;
+
+impl GameState {
+    pub(crate) fn miss_rule_on(&self) -> bool {
+        self.config
+            .scoring
+            .miss_rule
+            .unwrap_or(self.config.difficulty.preset().miss_rule)
+    }
+
+    // A deal landed at `x`, nearest this player
+    pub(crate) fn punish_miss(&mut self, player_index: usize, x: f64) {
+        if !self.miss_rule_on() {
+            return;
+        }
+        let rules = &self.config.scoring;
+        let (allowed, penalty) = (rules.misses_allowed, rules.miss_streak_penalty);
+        let keep_combo = self.mutators.no_combo_reset;
+        let team = self.mode == PlayerMode::Team;
+        let Some(slot) = self.player_mut(player_index) else {
+            return;
+        };
+        slot.misses_in_a_row += 1;
+        if !keep_combo {
+            slot.combo /= 2;
+        }
+        let combo = slot.combo;
+        let charged = slot.misses_in_a_row > allowed && penalty > 0;
+        if charged {
+            slot.score = (slot.score - penalty).max(0);
+        }
+        let y = slot.player.y;
+        if team && !keep_combo {
+            self.team_combo = combo;
+        }
+        if charged {
+            self.spawn_popup(x, y - 4.0, format!("MISS -{penalty}"), "#ff4444");
+        }
+    }
+}
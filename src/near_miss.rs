@@ -0,0 +1,112 @@
+// Near misses: a hazard that falls past within a few pixels of a player
+// without touching them is worth a few style points once it reaches the
+// floor, with a whoosh of streaks off that side of the player.
+//
+// The check runs alongside the collision pass: a hazard that comes within
+// the margin is marked with whoever it brushed past, and if it lands without
+// anyone catching it the mark pays out. Landing takes it out of play, so a
+// paid near miss can't turn into a hit a tick later.
+
+use super::*;
+
+const NEAR_MISS_MARGIN: f64 = 6.0; // How close counts, either side of the player
+const NEAR_MISS_POINTS: i32 = 15;
+const WHOOSH_FRAMES: u32 = 15;
+
+// Streaks flying off a player's side after a near miss. Visual only.
+#[derive(Clone)]
+pub(crate) struct Whoosh {
+    x: f64, // The player's edge the hazard passed
+    y: f64,
+    direction: f64, // 1 flies off to the right, -1 to the left
+    age: u32,
+}
+
+impl Player {
+    // Close beside the object but not touching it
+    fn grazes(&self, obj: &FallingObject) -> bool {
+        !self.overlaps(obj)
+            && self.x - NEAR_MISS_MARGIN < obj.x + obj.width
+            && self.x + self.width + NEAR_MISS_MARGIN > obj.x
+            && self.y < obj.y + obj.height
+            && self.y + self.height > obj.y
+    }
+}
+
+impl GameState {
+    pub(crate) fn mark_near_misses(&mut self) {
+        // Hazards in a bonus round can't hurt anyone, so there's nothing to miss
+        if matches!(self.playing_phase, PlayingPhase::BonusRound { .. }) {
+            return;
+        }
+        let hazards = self.objects.iter_mut().filter(|obj| {
+            obj.obj_type == ObjectType::BadItem && obj.grazed_by.is_none() && !obj.landed
+        });
+
+        for obj in hazards {
+            obj.grazed_by = self
+                .players
+                .iter()
+                .find(|slot| slot.health > 0 && slot.player.grazes(obj))
+                .map(|slot| slot.player_index);
+        }
+    }
+
+    // A hazard marked by mark_near_misses landed at `x`
+    pub(crate) fn reward_near_miss(&mut self, player_index: usize, x: f64) {
+        let Some(slot) = self
+            .players
+            .iter_mut()
+            .find(|slot| slot.player_index == player_index && slot.health > 0)
+        else {
+            return;
+        };
+        slot.earn(NEAR_MISS_POINTS);
+        let player = &slot.player;
+        let direction = if x < player.x + player.width / 2.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        let edge = if direction < 0.0 {
+            player.x
+        } else {
+            player.x + player.width
+        };
+        let (center, top) = (player.x + player.width / 2.0, player.y);
+        self.whooshes.push(Whoosh {
+            x: edge,
+            y: top + player.height / 2.0,
+            direction,
+            age: 0,
+        });
+        self.spawn_popup(
+            center,
+            top - 4.0,
+            format!("CLOSE! +{NEAR_MISS_POINTS}"),
+            "#7fdfff",
+        );
+    }
+
+    pub(crate) fn update_whooshes(&mut self) {
+        for whoosh in &mut self.whooshes {
+            whoosh.age += 1;
+        }
+        self.whooshes.retain(|whoosh| whoosh.age < WHOOSH_FRAMES);
+    }
+}
+
+pub(crate) fn draw_whooshes(ctx: &dyn Renderer, whooshes: &[Whoosh]) {
+    for whoosh in whooshes {
+        let t = whoosh.age as f64 / WHOOSH_FRAMES as f64;
+        let near = whoosh.x + whoosh.direction * (2.0 + 18.0 * t);
+        let far = near + whoosh.direction * 10.0 * (1.0 - t);
+        let streaks: Vec<_> = [-6.0, 0.0, 6.0]
+            .iter()
+            .map(|dy| ((near, whoosh.y + dy), (far, whoosh.y + dy)))
+            .collect();
+        ctx.set_alpha(1.0 - t);
+        ctx.particles(&streaks, "#dff", 1.5);
+    }
+    ctx.set_alpha(1.0);
+}
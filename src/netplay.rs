@@ -0,0 +1,170 @@
+// Online versus over a WebSocket relay, built with `--features netplay`.
+// Loading the page with `?netplay=wss://relay.example/some-room` connects to
+// that address; the relay only has to pass each text message on to the other
+// connection in the room. Whoever starts a two-player game first sends the
+// seed and plays as P1, the other cabinet follows as P2, and each side steers
+// with its own P1 controls. Ticks go through a RollbackSession so neither
+// side waits on the network unless it falls too far behind.
+
+use super::*;
+use web_sys::{MessageEvent, UrlSearchParams, WebSocket};
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum NetMessage {
+    Start {
+        seed: u64,
+    },
+    Input {
+        tick: u32,
+        left: bool,
+        right: bool,
+        #[serde(default)]
+        throw: bool,
+        confirmed: Option<(u32, u64)>, // Sender's latest confirmed tick and its state hash
+    },
+}
+
+struct NetplayLink {
+    socket: WebSocket,
+    unsent: RefCell<Vec<String>>, // Queued until the socket opens
+}
+
+impl NetplayLink {
+    fn send(&self, message: &NetMessage) {
+        let Ok(text) = serde_json::to_string(message) else {
+            return;
+        };
+        let mut unsent = self.unsent.borrow_mut();
+        unsent.push(text);
+        if self.socket.ready_state() == WebSocket::OPEN {
+            for text in unsent.drain(..) {
+                let _ = self.socket.send_with_str(&text);
+            }
+        }
+    }
+}
+
+pub(crate) struct Netplay {
+    link: NetplayLink,
+    session: Option<RollbackSession>,
+}
+
+impl Netplay {
+    // Connects to the relay named in the page URL, if there is one. What the
+    // other side sends is posted to `inbox`.
+    pub(crate) fn from_page(inbox: &Rc<RefCell<Vec<AppMessage>>>) -> Result<Option<Self>, JsValue> {
+        let search = web_sys::window().unwrap().location().search()?;
+        let Some(url) = UrlSearchParams::new_with_str(&search)?.get("netplay") else {
+            return Ok(None);
+        };
+        let socket = WebSocket::new(&url)?;
+
+        let messages = inbox.clone();
+        let onmessage = Closure::wrap(Box::new(move |event: MessageEvent| {
+            let Some(text) = event.data().as_string() else {
+                return;
+            };
+            match serde_json::from_str(&text) {
+                Ok(message) => messages.borrow_mut().push(AppMessage::Netplay(message)),
+                Err(err) => log(
+                    LogLevel::Warn,
+                    "netplay",
+                    &format!("ignoring message: {err}"),
+                ),
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        socket.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        onmessage.forget();
+
+        let closed = inbox.clone();
+        let onclose = Closure::wrap(Box::new(move || {
+            closed.borrow_mut().push(AppMessage::NetplayClosed);
+        }) as Box<dyn FnMut()>);
+        socket.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+        onclose.forget();
+
+        Ok(Some(Netplay {
+            link: NetplayLink {
+                socket,
+                unsent: RefCell::new(Vec::new()),
+            },
+            session: None,
+        }))
+    }
+
+    // Starts a two-player game on both cabinets, with this one as P1
+    pub(crate) fn host(&mut self, state: &mut GameState) {
+        let seed = rand::random();
+        self.link.send(&NetMessage::Start { seed });
+        // Handicaps are picked on a local ready screen; online games go without
+        state.handicaps = [Handicap::None; 2];
+        state.start_seeded_game(PlayerMode::Two, seed);
+        self.session = Some(RollbackSession::new(0));
+    }
+
+    pub(crate) fn handle(&mut self, state: &mut GameState, message: NetMessage) {
+        match message {
+            NetMessage::Start { seed } => {
+                state.handicaps = [Handicap::None; 2];
+                state.start_seeded_game(PlayerMode::Two, seed);
+                self.session = Some(RollbackSession::new(1));
+            }
+            NetMessage::Input {
+                tick,
+                left,
+                right,
+                throw,
+                confirmed,
+            } => {
+                let Some(session) = &mut self.session else {
+                    return;
+                };
+                session.receive(state, tick, TickInput { left, right, throw });
+                if let Some((tick, hash)) = confirmed
+                    && !session.in_sync(tick, hash)
+                {
+                    state
+                        .ticker
+                        .push("Out of sync with the other cabinet!".to_string());
+                    self.session = None;
+                }
+            }
+        }
+    }
+
+    // The game carries on locally, with the other player standing still
+    pub(crate) fn disconnected(&mut self, state: &mut GameState) {
+        if self.session.take().is_some() {
+            state
+                .ticker
+                .push("The other cabinet disconnected".to_string());
+        }
+    }
+
+    // Plays one tick of an online game. Returns false when there isn't one
+    // going and the caller should tick the game itself.
+    pub(crate) fn tick(&mut self, state: &mut GameState, commands: &[Command]) -> bool {
+        let Some(session) = &mut self.session else {
+            return false;
+        };
+        if !matches!(state.phase, GamePhase::Playing) {
+            // Over, unless a late input turns out to change the ending
+            if session.settled() {
+                self.session = None;
+            }
+            return false;
+        }
+        let input = TickInput::from_commands(commands, 0);
+        if let Some(tick) = session.advance(state, input) {
+            self.link.send(&NetMessage::Input {
+                tick,
+                left: input.left,
+                right: input.right,
+                throw: input.throw,
+                confirmed: session.latest_confirmed(),
+            });
+        }
+        true
+    }
+}
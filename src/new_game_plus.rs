@@ -0,0 +1,135 @@
+// NEW GAME+: a solo run that ends past a set score ("newGamePlusScore" in the
+// scoring rules) is offered another go, one loop further on. Every loop pays
+// more for every point but has everything falling faster from the first
+// second, and stays that way until the player turns an offer down or misses
+// the score. Runs from a loop are flagged on the leaderboard with their loop,
+// so they aren't taken for ordinary runs.
+
+use super::*;
+
+const SCORE_STEP: f64 = 0.25; // Extra points earned per loop
+const SPEED_STEP: f64 = 0.15; // Extra fall speed per loop
+
+pub(crate) const NEW_GAME_PLUS_ITEMS: [&str; 2] = ["NEW GAME+", "NO THANKS"];
+
+fn loop_multiplier(level: u32) -> f64 {
+    1.0 + level as f64 * SCORE_STEP
+}
+
+impl GameState {
+    // Multiplies fall speed for the whole run
+    pub(crate) fn new_game_plus_speed(&self) -> f64 {
+        1.0 + self.new_game_plus as f64 * SPEED_STEP
+    }
+
+    // When a run starts
+    pub(crate) fn apply_new_game_plus(&mut self) {
+        let multiplier = loop_multiplier(self.new_game_plus);
+        for slot in &mut self.players {
+            slot.loop_multiplier = multiplier;
+        }
+        if self.new_game_plus > 0 {
+            self.ticker.push(format!(
+                "NEW GAME+ {} - x{multiplier:.2}",
+                self.new_game_plus
+            ));
+        }
+    }
+
+    fn earned_new_game_plus(&self) -> bool {
+        let needed = self.config.scoring.new_game_plus_score;
+        needed > 0
+            && self.mode == PlayerMode::Single
+            && self.tournament.is_none()
+            && self.bot.is_none()
+            && self
+                .final_scores
+                .iter()
+                .any(|result| result.score >= needed)
+    }
+
+    // Where a run ends up once its scores have names: the offer of another
+    // loop if it earned one, otherwise game over
+    pub(crate) fn show_game_over(&mut self) {
+        if self.earned_new_game_plus() {
+            self.phase = GamePhase::NewGamePlus { selection: 0 };
+            return;
+        }
+        self.new_game_plus = 0;
+        self.phase = GamePhase::GameOver {
+            tab: LeaderboardCategory::Score,
+        };
+    }
+
+    pub(crate) fn handle_new_game_plus(&mut self, commands: &[Command]) {
+        let GamePhase::NewGamePlus { selection } = &mut self.phase else {
+            return;
+        };
+        move_selection(
+            selection,
+            NEW_GAME_PLUS_ITEMS.len(),
+            menu_prev(commands),
+            menu_next(commands),
+        );
+        if !commands.contains(&Command::Confirm) {
+            return;
+        }
+        if *selection == 0 {
+            self.new_game_plus += 1;
+            self.start_new_game(PlayerMode::Single);
+        } else {
+            self.new_game_plus = 0;
+            self.phase = GamePhase::GameOver {
+                tab: LeaderboardCategory::Score,
+            };
+        }
+    }
+}
+
+pub(crate) fn draw_new_game_plus(ctx: &dyn Renderer, state: &GameState, selection: usize) {
+    let world = state.world;
+    let center = world.width / 2.0;
+    let next = state.new_game_plus + 1;
+    ctx.text(
+        &format!("NEW GAME+ {next}"),
+        center,
+        50.0,
+        18.0,
+        "#ffd700",
+        Align::Center,
+    );
+    let terms = [
+        format!("Every point x{:.2}", loop_multiplier(next)),
+        format!(
+            "Everything falls {:.0}% faster",
+            next as f64 * SPEED_STEP * 100.0
+        ),
+        format!("Scores are marked +{next} on the board"),
+    ];
+    for (i, line) in terms.iter().enumerate() {
+        let y = 80.0 + i as f64 * 14.0;
+        ctx.text(line, center, y, 10.0, "#fff", Align::Center);
+    }
+
+    for (i, label) in NEW_GAME_PLUS_ITEMS.iter().enumerate() {
+        let selected = i == selection;
+        let prefix = if selected { ">" } else { " " };
+        ctx.text(
+            &format!("{prefix} {label}"),
+            world.centered(120.0),
+            140.0 + i as f64 * 20.0,
+            12.0,
+            if selected { "#0ff" } else { "#fff" },
+            Align::Left,
+        );
+    }
+
+    ctx.text(
+        "↑/↓: Select | A: Choose",
+        10.0,
+        world.height - 10.0,
+        8.0,
+        "#888",
+        Align::Left,
+    );
+}
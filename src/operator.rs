@@ -0,0 +1,232 @@
+// The operator menu, for whoever looks after the cabinet: hold 1P and 2P
+// together on the main menu for five seconds. It can wipe the leaderboard
+// (A twice, in case of a slip), light up each button as it's pressed to
+// check the controls, and shows where scores are being saved and which build
+// is running.
+
+use super::*;
+
+const OPERATOR_HOLD_MS: f64 = 5000.0;
+
+#[derive(Copy, Clone, PartialEq)]
+enum OperatorItem {
+    WipeLeaderboard,
+    InputTest,
+    Exit,
+}
+
+const OPERATOR_ITEMS: [OperatorItem; 3] = [
+    OperatorItem::WipeLeaderboard,
+    OperatorItem::InputTest,
+    OperatorItem::Exit,
+];
+
+pub(crate) struct OperatorMenu {
+    selection: usize, // Index into OPERATOR_ITEMS
+    confirming_wipe: bool,
+    wiped: bool,
+    testing_inputs: bool,
+    inputs: InputSnapshot, // This frame's buttons, for the input test
+    storage_works: bool,   // Whether a test write read back, checked on opening
+}
+
+impl GameState {
+    // Called with the menu's commands and the time since the last frame;
+    // opens the operator menu once 1P and 2P have been held long enough.
+    // Timed rather than counted in frames, which come faster on a fast display.
+    pub(crate) fn hold_for_operator(&mut self, commands: &[Command], elapsed_ms: f64) {
+        let GamePhase::ModeSelect {
+            service_held_ms, ..
+        } = &mut self.phase
+        else {
+            return;
+        };
+        if !commands.contains(&Command::ServiceHeld) {
+            *service_held_ms = 0.0;
+            return;
+        }
+        *service_held_ms += elapsed_ms;
+        if *service_held_ms >= OPERATOR_HOLD_MS {
+            log(LogLevel::Info, "operator", "operator menu opened");
+            self.phase = GamePhase::Operator(OperatorMenu {
+                selection: 0,
+                confirming_wipe: false,
+                wiped: false,
+                testing_inputs: false,
+                inputs: InputSnapshot::default(),
+                storage_works: store_works(&*self.store),
+            });
+        }
+    }
+
+    pub(crate) fn handle_operator(&mut self, commands: &[Command]) {
+        let GamePhase::Operator(menu) = &mut self.phase else {
+            return;
+        };
+        // Every button is being tested, so only both start buttons leave
+        if menu.testing_inputs {
+            if commands.contains(&Command::ServiceHeld) {
+                menu.testing_inputs = false;
+            }
+            return;
+        }
+
+        let (prev, next) = (menu_prev(commands), menu_next(commands));
+        move_selection(&mut menu.selection, OPERATOR_ITEMS.len(), prev, next);
+        if prev || next {
+            menu.confirming_wipe = false;
+        }
+        if !commands.contains(&Command::Confirm) {
+            return;
+        }
+        match OPERATOR_ITEMS[menu.selection] {
+            OperatorItem::WipeLeaderboard if menu.confirming_wipe => {
+                menu.confirming_wipe = false;
+                menu.wiped = true;
+                self.wipe_leaderboard();
+            }
+            OperatorItem::WipeLeaderboard => menu.confirming_wipe = true,
+            OperatorItem::InputTest => menu.testing_inputs = true,
+            OperatorItem::Exit => self.phase = GamePhase::main_menu(),
+        }
+    }
+
+    // Called once a frame with the raw buttons, for the input test
+    pub(crate) fn update_operator(&mut self, inputs: &InputSnapshot) {
+        if let GamePhase::Operator(menu) = &mut self.phase {
+            menu.inputs = *inputs;
+        }
+    }
+}
+
+// Optional parts compiled into this build
+fn build_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "netplay") {
+        features.push("netplay");
+    }
+    if cfg!(feature = "cabinet-sync") {
+        features.push("cabinet-sync");
+    }
+    if cfg!(feature = "editor") {
+        features.push("editor");
+    }
+    features
+}
+
+pub(crate) fn draw_operator(ctx: &dyn Renderer, state: &GameState, menu: &OperatorMenu) {
+    let world = state.world;
+    ctx.text(
+        "OPERATOR",
+        world.width / 2.0,
+        40.0,
+        18.0,
+        "#fff",
+        Align::Center,
+    );
+    if menu.testing_inputs {
+        draw_input_test(ctx, world, &menu.inputs);
+        return;
+    }
+
+    for (i, item) in OPERATOR_ITEMS.iter().enumerate() {
+        let label = match item {
+            OperatorItem::WipeLeaderboard if menu.confirming_wipe => {
+                "Really wipe? A again to confirm".to_string()
+            }
+            OperatorItem::WipeLeaderboard if menu.wiped => "Leaderboard wiped".to_string(),
+            OperatorItem::WipeLeaderboard => {
+                format!("Wipe leaderboard ({} saved)", state.leaderboard.len())
+            }
+            OperatorItem::InputTest => "Input test".to_string(),
+            OperatorItem::Exit => "Exit".to_string(),
+        };
+        let selected = i == menu.selection;
+        let prefix = if selected { ">" } else { " " };
+        let color = if selected && menu.confirming_wipe {
+            "#ff4444"
+        } else if selected {
+            "#0ff"
+        } else {
+            "#fff"
+        };
+        ctx.text(
+            &format!("{prefix} {label}"),
+            world.centered(230.0),
+            70.0 + i as f64 * 18.0,
+            12.0,
+            color,
+            Align::Left,
+        );
+    }
+
+    let storage = if menu.storage_works {
+        "saving"
+    } else {
+        "NOT SAVING"
+    };
+    let features = build_features();
+    let info = [
+        format!("Storage: {} ({storage})", state.store.name()),
+        format!(
+            "Version: {} {}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        ),
+        format!(
+            "Features: {}",
+            if features.is_empty() {
+                "none".to_string()
+            } else {
+                features.join(", ")
+            }
+        ),
+    ];
+    for (i, line) in info.iter().enumerate() {
+        let y = 140.0 + i as f64 * 13.0;
+        ctx.text(line, world.centered(230.0), y, 9.0, "#aaa", Align::Left);
+    }
+
+    ctx.text(
+        "↑/↓: Select | A: Choose",
+        10.0,
+        world.height - 10.0,
+        8.0,
+        "#888",
+        Align::Left,
+    );
+}
+
+// Every button on the cabinet, lit while it's held
+fn draw_input_test(ctx: &dyn Renderer, world: WorldConfig, inputs: &InputSnapshot) {
+    let buttons = [
+        ("1P", inputs.system_one_player),
+        ("2P", inputs.system_two_player),
+        ("P1 \u{25c0}", inputs.player1_left),
+        ("P1 \u{25b6}", inputs.player1_right),
+        ("P1 \u{25b2}", inputs.player1_up),
+        ("P1 \u{25bc}", inputs.player1_down),
+        ("P1 A", inputs.player1_a),
+        ("P2 \u{25c0}", inputs.player2_left),
+        ("P2 \u{25b6}", inputs.player2_right),
+        ("P2 A", inputs.player2_a),
+    ];
+    let (width, height) = (40.0, 22.0);
+    let left = world.centered(5.0 * (width + 6.0));
+    for (i, (label, held)) in buttons.iter().enumerate() {
+        let x = left + (i % 5) as f64 * (width + 6.0);
+        let y = 70.0 + (i / 5) as f64 * (height + 8.0);
+        ctx.rect(x, y, width, height, if *held { "#7fff7f" } else { "#333" });
+        let color = if *held { "#000" } else { "#fff" };
+        ctx.text(label, x + width / 2.0, y + 15.0, 9.0, color, Align::Center);
+    }
+
+    ctx.text(
+        "Press 1P and 2P together to go back",
+        10.0,
+        world.height - 10.0,
+        8.0,
+        "#888",
+        Align::Left,
+    );
+}
@@ -0,0 +1,188 @@
+// Pace: how a run is doing against the ones before it, before game over.
+//
+// The pace marker is a quiet note under the wave gauge each time a player's
+// score climbs past someone on the leaderboard they're headed for. The ghost
+// line is a thin bar under each player's score that shows it against how the
+// personal best run was going at the same point, from the score that run had
+// every five seconds.
+//
+// Every peer has its own leaderboard and personal best, so none of this goes
+// into the state hash; it only decides what one screen shows.
+
+use super::*;
+
+const PACE_MARKER_FRAMES: u32 = 150;
+const PACE_FADE_FRAMES: u32 = 30;
+const PACE_SAMPLE_MS: f64 = 5000.0;
+const GHOST_BAR_WIDTH: f64 = 80.0;
+
+#[derive(Clone)]
+pub(crate) struct PaceMarker {
+    text: String,
+    age: u32,
+}
+
+// The best run outside team mode, kept for the ghost line
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct PersonalBest {
+    pub(crate) score: i32,
+    pub(crate) pace: Vec<i32>, // The run's score at the end of each five seconds
+}
+
+impl PersonalBest {
+    // What the best run had scored by this point, eased between samples
+    fn score_at(&self, run_time_ms: f64) -> f64 {
+        let sample = run_time_ms / PACE_SAMPLE_MS;
+        let i = sample as usize;
+        if i >= self.pace.len() {
+            return self.score as f64;
+        }
+        let before = if i == 0 { 0 } else { self.pace[i - 1] };
+        before as f64 + (self.pace[i] - before) as f64 * sample.fract()
+    }
+}
+
+impl GameState {
+    // The board this run's scores will land on
+    fn pace_board(&self) -> Vec<&LeaderboardEntry> {
+        let category = if self.mode == PlayerMode::Team {
+            LeaderboardCategory::Team
+        } else {
+            LeaderboardCategory::Score
+        };
+        self.ranked_leaderboard(category)
+    }
+
+    // Entries a score hasn't beaten yet; the one it passes next is last
+    fn entries_ahead(&self, score: i32) -> usize {
+        self.pace_board()
+            .iter()
+            .filter(|entry| entry.score >= score)
+            .count()
+    }
+
+    pub(crate) fn start_pace(&mut self) {
+        let ahead = self.entries_ahead(0);
+        for slot in &mut self.players {
+            slot.entries_ahead = ahead;
+        }
+        self.pace_marker = None;
+    }
+
+    pub(crate) fn sample_pace(&mut self) {
+        let samples = (self.run_time_ms / PACE_SAMPLE_MS) as usize;
+        for slot in &mut self.players {
+            while slot.pace.len() < samples {
+                slot.pace.push(slot.score);
+            }
+        }
+    }
+
+    fn has_ghost(&self, player_index: usize) -> bool {
+        self.mode != PlayerMode::Team && !self.is_bot(player_index)
+    }
+
+    // A player is out; if that was their best run yet it becomes the new ghost
+    pub(crate) fn record_personal_best(&mut self, player_index: usize) {
+        if !self.has_ghost(player_index) {
+            return;
+        }
+        let Some(slot) = self
+            .players
+            .iter()
+            .find(|slot| slot.player_index == player_index)
+        else {
+            return;
+        };
+        if slot.score <= self.personal_best.score {
+            return;
+        }
+        self.personal_best = PersonalBest {
+            score: slot.score,
+            pace: slot.pace.clone(),
+        };
+        self.progress_changed();
+    }
+
+    pub(crate) fn update_pace(&mut self) {
+        if let Some(marker) = &mut self.pace_marker {
+            marker.age += 1;
+            if marker.age >= PACE_MARKER_FRAMES {
+                self.pace_marker = None;
+            }
+        }
+        if !matches!(self.phase, GamePhase::Playing) {
+            return;
+        }
+        for i in 0..self.players.len() {
+            let (player_index, score) = (self.players[i].player_index, self.players[i].score);
+            let ahead = self.entries_ahead(score);
+            if ahead >= self.players[i].entries_ahead {
+                continue;
+            }
+            self.players[i].entries_ahead = ahead;
+            if self.is_bot(player_index) {
+                continue;
+            }
+            // Several at once only mentions the best of them
+            let passed = self.pace_board()[ahead];
+            let who = if self.mode == PlayerMode::Single {
+                "You".to_string()
+            } else {
+                format!("P{}", player_index + 1)
+            };
+            let text = format!(
+                "{who} just passed #{} - {} {}!",
+                ahead + 1,
+                passed.name,
+                passed.score
+            );
+            self.pace_marker = Some(PaceMarker { text, age: 0 });
+        }
+    }
+}
+
+pub(crate) fn draw_pace_marker(ctx: &dyn Renderer, state: &GameState) {
+    let Some(marker) = &state.pace_marker else {
+        return;
+    };
+    let left = PACE_MARKER_FRAMES - marker.age;
+    ctx.set_alpha((left as f64 / PACE_FADE_FRAMES as f64).min(1.0));
+    ctx.text(
+        &marker.text,
+        state.world.width - 5.0,
+        44.0,
+        8.0,
+        "#ccc",
+        Align::Right,
+    );
+    ctx.set_alpha(1.0);
+}
+
+// The bar fills toward the personal best's final score, with a tick where
+// that run was at this point; green when ahead of it, red when behind.
+pub(crate) fn draw_ghost_line(ctx: &dyn Renderer, state: &GameState, slot: &PlayerSlot, y: f64) {
+    let best = &state.personal_best;
+    if best.score <= 0 || !state.has_ghost(slot.player_index) {
+        return;
+    }
+    let full = best.score as f64;
+    let ghost = best.score_at(state.run_time_ms);
+    let score = slot.score as f64;
+    let color = if score >= ghost { "#7fff7f" } else { "#ff6666" };
+    ctx.rect(5.0, y, GHOST_BAR_WIDTH, 2.0, "#333");
+    ctx.rect(
+        5.0,
+        y,
+        GHOST_BAR_WIDTH * (score / full).min(1.0),
+        2.0,
+        color,
+    );
+    ctx.rect(
+        5.0 + GHOST_BAR_WIDTH * (ghost / full).min(1.0),
+        y - 1.0,
+        1.0,
+        4.0,
+        "#fff",
+    );
+}
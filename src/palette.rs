@@ -0,0 +1,56 @@
+// Canvas colors as the JsValues the 2D context takes. Building one from a
+// &str copies the string over to JS every time, and the same few dozen colors
+// get set hundreds of times a frame, so each is built the first time it's
+// drawn with and kept.
+//
+// A handful of colors are formatted on the fly (the darkness fading in, say),
+// so the palette empties itself rather than growing without end.
+
+use super::*;
+
+const MAX_COLORS: usize = 256;
+
+#[derive(Default)]
+pub(crate) struct Palette {
+    colors: RefCell<HashMap<String, JsValue>>,
+}
+
+impl Palette {
+    pub(crate) fn color(&self, css: &str) -> Ref<'_, JsValue> {
+        {
+            let mut colors = self.colors.borrow_mut();
+            if !colors.contains_key(css) {
+                if colors.len() >= MAX_COLORS {
+                    colors.clear();
+                }
+                colors.insert(css.to_string(), JsValue::from_str(css));
+            }
+        }
+        Ref::map(self.colors.borrow(), |colors| &colors[css])
+    }
+}
+
+// The context's style setters, taking a palette color. web-sys only has these
+// deprecated, in favor of ones taking a &str, which copy the string over again.
+#[wasm_bindgen]
+extern "C" {
+    type StyleSetters;
+
+    #[wasm_bindgen(method, setter = fillStyle)]
+    fn set_fill_style(this: &StyleSetters, value: &JsValue);
+
+    #[wasm_bindgen(method, setter = strokeStyle)]
+    fn set_stroke_style(this: &StyleSetters, value: &JsValue);
+}
+
+pub(crate) fn set_fill_color(context: &CanvasRenderingContext2d, color: &JsValue) {
+    context
+        .unchecked_ref::<StyleSetters>()
+        .set_fill_style(color);
+}
+
+pub(crate) fn set_stroke_color(context: &CanvasRenderingContext2d, color: &JsValue) {
+    context
+        .unchecked_ref::<StyleSetters>()
+        .set_stroke_style(color);
+}
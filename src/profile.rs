@@ -0,0 +1,62 @@
+// Frame time by subsystem, built with `--features profiling`. A span leaves
+// performance.mark/measure entries under its name, so the browser profiler's
+// timeline shows update, collisions and draw as labelled blocks, and feeds a
+// running average per name that the debug overlay shows.
+
+use super::*;
+
+const SMOOTHING: f64 = 0.05; // How much each new timing moves the average
+
+thread_local! {
+    static AVERAGES: RefCell<Vec<(&'static str, f64)>> = const { RefCell::new(Vec::new()) };
+}
+
+// Measures from creation until it's dropped
+pub(crate) struct Span {
+    name: &'static str,
+    start: f64,
+}
+
+pub(crate) fn span(name: &'static str) -> Span {
+    if let Some(performance) = performance() {
+        let _ = performance.mark(&format!("{name}-start"));
+    }
+    Span { name, start: now() }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let elapsed = now() - self.start;
+        if let Some(performance) = performance() {
+            let _ = performance.measure_with_start_mark(self.name, &format!("{}-start", self.name));
+        }
+        AVERAGES.with(|averages| {
+            let mut averages = averages.borrow_mut();
+            match averages.iter_mut().find(|(name, _)| *name == self.name) {
+                Some((_, average)) => *average += (elapsed - *average) * SMOOTHING,
+                None => averages.push((self.name, elapsed)),
+            }
+        });
+    }
+}
+
+// Average milliseconds per span, in the order they were first seen
+#[cfg(feature = "debug-overlay")]
+pub(crate) fn averages() -> Vec<(&'static str, f64)> {
+    AVERAGES.with(|averages| averages.borrow().clone())
+}
+
+// Natively there's no profiler to feed, so spans only cost a clock read
+#[cfg(target_arch = "wasm32")]
+fn performance() -> Option<web_sys::Performance> {
+    web_sys::window()?.performance()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn performance() -> Option<web_sys::Performance> {
+    None
+}
+
+fn now() -> f64 {
+    performance().map_or(0.0, |performance| performance.now())
+}
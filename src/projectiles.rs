@@ -0,0 +1,130 @@
+// Throwing hazards back in versus. In a two player game a hazard that hits
+// you isn't gone: you keep it, one at a time, shown over your head. Tap A
+// with one kept and at least THROW_COST points to spend them and lob it up
+// and over to land where your opponent is standing. It comes down like any
+// other hazard and can be caught, and kept, in turn. The thrower can't be
+// hit by their own throw. Holding A longer is for emotes, and never throws.
+
+use super::*;
+
+const THROW_COST: i32 = 30;
+pub(crate) const THROW_TAP_TICKS: u32 = 12; // Longest A press that still counts as a tap
+const THROW_SPEED_Y: f64 = -5.0; // Straight up at launch, in logical units a tick
+const THROW_GRAVITY: f64 = 0.12;
+const KEPT_ICON_SIZE: f64 = 12.0;
+
+impl GameState {
+    fn throws_allowed(&self) -> bool {
+        self.mode == PlayerMode::Two
+    }
+
+    // A hazard hit this player; they keep it to throw
+    pub(crate) fn keep_hazard(&mut self, player_index: usize, kind: usize) {
+        if !self.throws_allowed() {
+            return;
+        }
+        if let Some(slot) = self.player_mut(player_index) {
+            slot.kept_hazard = Some(kind);
+        }
+    }
+
+    // Called every tick with which players are holding A. A throw goes when
+    // a tap is let go, so holding A doesn't empty the score.
+    pub(crate) fn update_throws(&mut self, holding: [bool; 2]) {
+        let mut throws = Vec::new();
+        for slot in &mut self.players {
+            if holding[slot.player_index] {
+                slot.a_held += 1;
+                continue;
+            }
+            if (1..=THROW_TAP_TICKS).contains(&slot.a_held) {
+                throws.push(slot.player_index);
+            }
+            slot.a_held = 0;
+        }
+        for player_index in throws {
+            self.throw_hazard(player_index);
+        }
+    }
+
+    fn throw_hazard(&mut self, player_index: usize) {
+        if !self.throws_allowed() {
+            return;
+        }
+        let target = self
+            .players
+            .iter()
+            .find(|slot| slot.player_index != player_index && slot.health > 0)
+            .map(|slot| slot.player.x + slot.player.width / 2.0);
+        let Some(target) = target else {
+            return;
+        };
+        let Some(slot) = self
+            .players
+            .iter_mut()
+            .find(|slot| slot.player_index == player_index && slot.health > 0)
+        else {
+            return;
+        };
+        let Some(kind) = slot.kept_hazard else {
+            return;
+        };
+        if slot.score < THROW_COST {
+            return;
+        }
+        slot.score -= THROW_COST;
+        slot.kept_hazard = None;
+
+        // Launched from overhead with a fixed upward speed; the sideways speed
+        // is whatever lands it on the target's center when it reaches the floor
+        let def = self.config.objects.get(kind);
+        let player = &slot.player;
+        let x = player.x + player.width / 2.0 - def.width / 2.0;
+        let y = player.y - def.height - 1.0;
+        let drop = self.world.floor_y() - def.height - y;
+        let speed_y = THROW_SPEED_Y * def.speed;
+        let gravity = THROW_GRAVITY * def.speed * def.speed;
+        let flight = (-speed_y + (speed_y * speed_y + 2.0 * gravity * drop).sqrt()) / gravity;
+        let speed_x = (target - def.width / 2.0 - x) / flight;
+
+        let vx = speed_x / def.speed;
+        let mut object = FallingObject::new(kind, def, x, y, vx, THROW_SPEED_Y, THROW_GRAVITY);
+        object.thrown_by = Some(player_index);
+        object.enlarge(self.piece_scale(), self.world);
+        self.objects.push(object);
+        self.spawn_popup(x + def.width / 2.0, y, format!("-{THROW_COST}"), "#ff4444");
+    }
+}
+
+// A kept hazard bobs over its keeper's head, lit up once they can afford to
+// throw it
+pub(crate) fn draw_kept_hazards(ctx: &dyn Renderer, state: &GameState) {
+    let mut sprites = Vec::new();
+    for slot in &state.players {
+        let Some(kind) = slot.kept_hazard else {
+            continue;
+        };
+        let player = &state.drawn_player(slot);
+        let x = player.x + player.width / 2.0 - KEPT_ICON_SIZE / 2.0;
+        let bob = (state.frame_count as f64 * 0.1).sin() * 2.0;
+        let y = player.y - KEPT_ICON_SIZE - 6.0 + bob;
+        sprites.push(SpriteDraw {
+            sprite: &state.config.objects.get(kind).sprite,
+            x,
+            y,
+            w: KEPT_ICON_SIZE,
+            h: KEPT_ICON_SIZE,
+        });
+        if slot.score >= THROW_COST {
+            ctx.text(
+                "A",
+                x + KEPT_ICON_SIZE + 4.0,
+                y + KEPT_ICON_SIZE - 2.0,
+                8.0,
+                "#ffd700",
+                Align::Center,
+            );
+        }
+    }
+    ctx.sprites(&sprites);
+}
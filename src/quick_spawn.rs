@@ -0,0 +1,78 @@
+// Balancing hotkeys, built with `--features quick-spawn`. While a run is in
+// progress, number-pad 1-9 drop the matching catalog object (in
+// assets/objects.json order) just above player 1, and +/- step the
+// difficulty multiplier.
+
+use super::*;
+
+const SPAWN_HEIGHT: f64 = 80.0; // How far above the player's head objects appear
+const DIFFICULTY_STEP: f64 = 0.25;
+const MIN_DIFFICULTY: f64 = 0.25;
+
+#[derive(Copy, Clone)]
+pub(crate) enum QuickSpawnCommand {
+    Spawn(usize), // Catalog index
+    Difficulty(f64),
+}
+
+pub(crate) fn setup_listeners() -> Result<Rc<RefCell<Vec<QuickSpawnCommand>>>, JsValue> {
+    let commands = Rc::new(RefCell::new(Vec::new()));
+    let window = web_sys::window().unwrap();
+
+    let queue = commands.clone();
+    let keydown = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        let code = event.code();
+        let command = match code.as_str() {
+            "NumpadAdd" | "Equal" => QuickSpawnCommand::Difficulty(DIFFICULTY_STEP),
+            "NumpadSubtract" | "Minus" => QuickSpawnCommand::Difficulty(-DIFFICULTY_STEP),
+            _ => match code
+                .strip_prefix("Numpad")
+                .and_then(|n| n.parse::<usize>().ok())
+            {
+                Some(n @ 1..=9) => QuickSpawnCommand::Spawn(n - 1),
+                _ => return,
+            },
+        };
+        queue.borrow_mut().push(command);
+        event.prevent_default();
+    }) as Box<dyn FnMut(_)>);
+    window.add_event_listener_with_callback("keydown", keydown.as_ref().unchecked_ref())?;
+    keydown.forget();
+
+    Ok(commands)
+}
+
+pub(crate) fn run_queued(commands: &RefCell<Vec<QuickSpawnCommand>>, state: &mut GameState) {
+    let commands = std::mem::take(&mut *commands.borrow_mut());
+    if !matches!(state.phase, GamePhase::Playing) {
+        return;
+    }
+    for command in commands {
+        match command {
+            QuickSpawnCommand::Spawn(kind) => spawn_above_player(state, kind),
+            QuickSpawnCommand::Difficulty(step) => {
+                state.difficulty_multiplier =
+                    (state.difficulty_multiplier + step).max(MIN_DIFFICULTY);
+                let text = format!("difficulty {:.2}", state.difficulty_multiplier);
+                let x = state.world.width / 2.0;
+                state.spawn_popup(x, 40.0, text, "#0ff");
+            }
+        }
+    }
+}
+
+fn spawn_above_player(state: &mut GameState, kind: usize) {
+    if kind >= state.config.objects.defs.len() {
+        return;
+    }
+    let Some(slot) = state.players.first() else {
+        return;
+    };
+    let def = state.config.objects.get(kind);
+    let x = (slot.player.x + (slot.player.width - def.width) / 2.0)
+        .clamp(0.0, state.world.width - def.width);
+    let mut object = FallingObject::dropped(x, kind, def);
+    object.y = (slot.player.y - SPAWN_HEIGHT).max(-def.height);
+    state.behaviors.get(object.obj_type).on_spawn(&mut object);
+    state.objects.push(object);
+}
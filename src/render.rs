@@ -0,0 +1,1067 @@
+// Drawing for every phase, through whichever Renderer backend is in use.
+
+use super::*;
+
+const MAX_HEART_ICONS: i32 = 5; // Beyond this the HUD shows a count instead
+const BIG_HEAD_SCALE: f64 = 0.7; // Head radius as a fraction of player width
+const SHARE_MODULE_SIZE: f64 = 2.0; // Pixels per QR code module
+const SERIES_COLUMN_WIDTH: f64 = 40.0; // Rounds shown on the series screen are the latest that fit
+const EDGE_MARGIN: f64 = 5.0; // Closest centered text gets to the sides
+const HEAT_GAUGE_WIDTH: f64 = 30.0; // Narrow enough to clear the catch rate column
+const HEAT_FULL: f64 = 2.0; // Difficulty over the starting 1.0 that fills the gauge
+const HEAT_PULSE_FRAMES: u32 = 45; // How long the gauge flashes after each step up
+const BOARD_NAME_CHARS: usize = 8; // Longer names from old saves or other games are cut short
+pub(crate) const PLAYER_COLORS: [&str; 2] = ["#4a9eff", "#ff9f43"]; // P1, then P2
+
+// Where the HUD's columns go on each line
+struct HudColumns {
+    hearts: f64,
+    hearts_stagger: f64, // Further right per player, so both rows of hearts don't line up
+    catch_rate: f64,
+}
+
+// Positions on screens whose shape depends on the world's size. Fixed-size
+// blocks of text and options are centered, and the rest hangs off the edges.
+impl WorldConfig {
+    // Left edge for a block `block_width` wide to sit in the middle
+    pub(crate) fn centered(self, block_width: f64) -> f64 {
+        ((self.width - block_width) / 2.0).max(EDGE_MARGIN)
+    }
+
+    fn hud_columns(self) -> HudColumns {
+        if self.width > self.height {
+            HudColumns {
+                hearts: 120.0,
+                hearts_stagger: 70.0,
+                catch_rate: 240.0,
+            }
+        } else {
+            // Too narrow to stagger; each player's hearts are on their own line anyway
+            HudColumns {
+                hearts: 95.0,
+                hearts_stagger: 0.0,
+                catch_rate: 165.0,
+            }
+        }
+    }
+
+    fn series_columns(self) -> usize {
+        ((self.width - 90.0) / SERIES_COLUMN_WIDTH) as usize
+    }
+}
+
+// Everything goes through the camera, so a zoom-punch takes the HUD along
+// with the playfield
+pub(crate) fn draw(ctx: &dyn Renderer, state: &GameState) {
+    let (dx, dy, scale) = state.camera.transform();
+    ctx.push_transform(dx, dy, scale);
+    draw_screen(ctx, state);
+    ctx.pop_transform();
+}
+
+fn draw_screen(ctx: &dyn Renderer, state: &GameState) {
+    let world = state.world;
+    ctx.clear("#111");
+
+    if let GamePhase::ModeSelect {
+        selection,
+        unlock_flash,
+        backdrop,
+        ..
+    } = &state.phase
+    {
+        backdrop.draw(ctx, &state.config.objects);
+        ctx.text(
+            "BLACK FRIDAY",
+            world.centered(200.0),
+            62.0,
+            18.0,
+            "#fff",
+            Align::Left,
+        );
+
+        for (i, item) in MENU_ITEMS.iter().enumerate() {
+            let prefix = if i == *selection { ">" } else { " " };
+            let label = match item {
+                MenuItem::Play(PlayerMode::Single) => "1P – Solo shopper",
+                MenuItem::Play(PlayerMode::Two) => "2P – Shop with friend",
+                MenuItem::Play(PlayerMode::Team) => "2P – Team up",
+                MenuItem::VersusBot => "1P – Vs CPU",
+                MenuItem::Alternate => "2P – Take turns",
+                MenuItem::Tournament => "Tournament",
+                MenuItem::Mutators if state.mutators.any() => "Mutators (active)",
+                MenuItem::Mutators => "Mutators",
+                MenuItem::Settings => "Settings",
+                MenuItem::HowToPlay => "How to play",
+                MenuItem::Credits => "Credits",
+                #[cfg(feature = "editor")]
+                MenuItem::Editor => "Wave editor",
+            };
+            ctx.text(
+                &format!("{prefix} {label}"),
+                world.centered(210.0),
+                94.0 + i as f64 * 12.0,
+                12.0,
+                "#fff",
+                Align::Left,
+            );
+        }
+
+        ctx.text(
+            "←/→: Select | A or 1P/2P: Start",
+            world.centered(220.0),
+            world.height - 10.0,
+            10.0,
+            "#aaa",
+            Align::Left,
+        );
+
+        // An unlock takes the line under the title while it flashes
+        if let Some((unlock, frames)) = *unlock_flash {
+            if (frames / 8) % 2 == 0 {
+                ctx.text(
+                    &format!("UNLOCKED: {} (see Mutators)", unlock.name()),
+                    world.width / 2.0,
+                    78.0,
+                    10.0,
+                    "#ffd700",
+                    Align::Center,
+                );
+            }
+        } else if let Some(best) = &state.session_best {
+            ctx.text(
+                &best.label(),
+                world.width / 2.0,
+                78.0,
+                10.0,
+                "#7fff7f",
+                Align::Center,
+            );
+        }
+        return;
+    }
+
+    if let GamePhase::Settings { selection } = &state.phase {
+        let labels: Vec<String> = SETTINGS_ITEMS
+            .iter()
+            .map(|item| state.settings.label(*item))
+            .collect();
+        draw_option_list(ctx, world, "SETTINGS", &labels, *selection);
+        return;
+    }
+
+    #[cfg(feature = "editor")]
+    if matches!(state.phase, GamePhase::Editor(_)) {
+        editor::draw(ctx, state);
+        return;
+    }
+
+    if let GamePhase::Mutators { selection } = &state.phase {
+        let labels: Vec<String> = state
+            .mutator_items()
+            .iter()
+            .map(|item| state.mutators.label(*item))
+            .collect();
+        draw_option_list(ctx, world, "MUTATORS", &labels, *selection);
+        return;
+    }
+
+    if let GamePhase::TournamentSetup { selection, setup } = &state.phase {
+        let labels: Vec<String> = TOURNAMENT_ITEMS
+            .iter()
+            .map(|item| setup.label(*item))
+            .collect();
+        draw_option_list(ctx, world, "TOURNAMENT", &labels, *selection);
+        return;
+    }
+
+    if let GamePhase::Ready { ready } = &state.phase {
+        draw_ready(ctx, state, ready);
+        return;
+    }
+
+    if let GamePhase::Crashed(report) = &state.phase {
+        draw_crashed(ctx, world, report);
+        return;
+    }
+
+    if let GamePhase::Loading { loaded, total, .. } = state.phase {
+        draw_loading(ctx, world, loaded, total);
+        return;
+    }
+
+    if let GamePhase::Splash { frames } = state.phase {
+        draw_splash(ctx, world, frames);
+        return;
+    }
+
+    if let GamePhase::Credits { frames } = state.phase {
+        draw_credits(ctx, world, frames);
+        return;
+    }
+
+    if let GamePhase::Leaderboard { tab, scroll } = state.phase {
+        draw_leaderboard(ctx, state, tab, scroll);
+        return;
+    }
+
+    if let GamePhase::NewGamePlus { selection } = state.phase {
+        draw_new_game_plus(ctx, state, selection);
+        return;
+    }
+
+    if let GamePhase::Operator(menu) = &state.phase {
+        draw_operator(ctx, state, menu);
+        return;
+    }
+
+    if let GamePhase::HowToPlay { page, then } = state.phase {
+        draw_how_to_play(ctx, state, page, then);
+        return;
+    }
+
+    if let GamePhase::ResumePrompt { selection } = &state.phase {
+        let labels = RESUME_ITEMS.map(String::from);
+        draw_option_list(ctx, world, "RESUME RUN?", &labels, *selection);
+        return;
+    }
+
+    if let (GamePhase::Series, Some(tournament)) = (&state.phase, &state.tournament) {
+        draw_series(ctx, state, tournament);
+        return;
+    }
+
+    if let GamePhase::NameEntry {
+        name,
+        cursor,
+        queue,
+    } = &state.phase
+    {
+        if let Some(result) = queue.first() {
+            ctx.text(
+                &format!("{} SCORE: {}", result_label(result, state), result.score),
+                world.centered(120.0),
+                50.0,
+                14.0,
+                "#fff",
+                Align::Left,
+            );
+            ctx.text(
+                "ENTER NAME",
+                world.centered(100.0),
+                80.0,
+                12.0,
+                "#fff",
+                Align::Left,
+            );
+
+            let name_width = NAME_LENGTH as f64 * 20.0;
+            let name_x = world.width / 2.0 - name_width / 2.0;
+            let name_y = 120.0;
+
+            // Draw each character with cursor indicator
+            for (i, ch) in name.iter().enumerate() {
+                let char_x = name_x + (i as f64 * 20.0);
+                let is_cursor = i == *cursor;
+
+                if is_cursor {
+                    // Draw cursor line below
+                    ctx.rect(char_x, name_y + 20.0, 15.0, 2.0, "#0ff");
+                }
+
+                let color = if is_cursor { "#0ff" } else { "#fff" };
+                ctx.text(&ch.to_string(), char_x, name_y, 20.0, color, Align::Left);
+            }
+
+            ctx.text(
+                "↑↓: Letter | ←→: Position",
+                world.centered(230.0),
+                160.0,
+                8.0,
+                "#888",
+                Align::Left,
+            );
+            let x = world.centered(90.0);
+            ctx.text("A: Confirm", x, 175.0, 8.0, "#888", Align::Left);
+        }
+        return;
+    }
+
+    if let GamePhase::GameOver { tab } = &state.phase {
+        ctx.text(
+            "GAME OVER",
+            world.centered(100.0),
+            30.0,
+            18.0,
+            "#fff",
+            Align::Left,
+        );
+
+        // Show current game scores
+        let mut score_y = draw_run_summary(ctx, state, 55.0);
+
+        // Beside the leaderboard, for phones at the cabinet
+        if let Some(share) = &state.share_code {
+            let size = share.drawn_size(SHARE_MODULE_SIZE);
+            let x = world.width - size - 6.0;
+            share.draw(ctx, x, score_y, SHARE_MODULE_SIZE);
+            ctx.text(
+                "SCAN YOUR SCORE",
+                x + size / 2.0,
+                score_y + size + 10.0,
+                8.0,
+                "#aaa",
+                Align::Center,
+            );
+        }
+
+        if let Some(best) = &state.session_best {
+            ctx.text(
+                &best.label(),
+                10.0,
+                score_y + 5.0,
+                9.0,
+                "#7fff7f",
+                Align::Left,
+            );
+            score_y += 14.0;
+        }
+
+        // Show the top 5 of the selected board; A opens the whole thing
+        ctx.text(
+            &format!("< {} >", tab.title()),
+            10.0,
+            score_y + 5.0,
+            9.0,
+            "#aaa",
+            Align::Left,
+        );
+        score_y += 18.0;
+
+        // Our own entries stand out, among the other games' on the cabinet's
+        for row in board_rows(state, *tab).iter().take(5) {
+            ctx.text(&row.text, 10.0, score_y, 9.0, row.color(), Align::Left);
+            score_y += 11.0;
+        }
+        #[cfg(feature = "cabinet-sync")]
+        if *tab == LeaderboardCategory::Cabinet && state.cabinet.champions.is_empty() {
+            ctx.text(
+                "Waiting for the cabinet...",
+                10.0,
+                score_y,
+                9.0,
+                "#888",
+                Align::Left,
+            );
+        }
+
+        ctx.text(
+            "←/→: Board | A: Full board | 1P/2P: Restart",
+            10.0,
+            world.height - 10.0,
+            8.0,
+            "#888",
+            Align::Left,
+        );
+        return;
+    }
+
+    // The rainbow cheat cycles the hue of the whole playfield and a clutch
+    // moment drains its color; the HUD keeps its colors
+    let mut filter = ColorFilter {
+        saturation: state.clutch_saturation(),
+        ..ColorFilter::default()
+    };
+    if state.mutators.rainbow {
+        filter.hue_rotation = (state.frame_count * 4) % 360;
+    }
+    ctx.set_color_filter(filter);
+
+    // Wind-blown dust streaks sit at the very back
+    let streaks: Vec<_> = state
+        .wind_particles
+        .iter()
+        .map(|particle| {
+            let tail = particle.x - state.wind * particle.speed * 2.0;
+            ((particle.x, particle.y), (tail, particle.y))
+        })
+        .collect();
+    ctx.particles(&streaks, "rgba(200, 200, 200, 0.35)", 1.0);
+
+    // Sticky patches sit under players and objects and fade as they dry up
+    for zone in &state.floor_zones {
+        let fade = (zone.remaining as f64 / 60.0).min(1.0);
+        ctx.set_alpha(0.7 * fade);
+        ctx.ellipse(
+            zone.x + zone.width / 2.0,
+            world.floor_y(),
+            zone.width / 2.0,
+            4.0,
+            "#8b5a2b",
+        );
+    }
+    ctx.set_alpha(1.0);
+
+    if state.split_active() {
+        draw_divider(ctx, state);
+    }
+
+    // Landing shadows: faint at first, darker as the object gets close
+    if state.settings.landing_shadows {
+        for obj in &state.objects {
+            if let Some(land_x) = obj.landing_x(state.wind, world.floor_y()) {
+                let closeness = ((obj.y + obj.height) / world.floor_y()).clamp(0.0, 1.0);
+                ctx.set_alpha(0.15 + 0.35 * closeness);
+                ctx.ellipse(
+                    land_x + obj.width / 2.0,
+                    world.floor_y(),
+                    obj.width / 2.0 * (0.5 + 0.5 * closeness),
+                    3.0,
+                    "#000",
+                );
+            }
+        }
+        ctx.set_alpha(1.0);
+    }
+
+    // Players go down in passes too: every body in its own color, then the
+    // white outlines and labels over all of them
+    let big_head = |player: &Player| {
+        let radius = player.width * BIG_HEAD_SCALE;
+        (
+            player.x + player.width / 2.0,
+            player.y - radius * 0.6,
+            radius,
+        )
+    };
+    for slot in &state.players {
+        let color = PLAYER_COLORS
+            .get(slot.player_index)
+            .unwrap_or(&PLAYER_COLORS[0]);
+        let player = &state.drawn_player(slot);
+
+        // Motion trail: older positions are fainter
+        for (i, (trail_x, trail_y)) in slot.trail.iter().enumerate() {
+            ctx.set_alpha(0.3 * (i + 1) as f64 / (TRAIL_LENGTH + 1) as f64);
+            ctx.rect(*trail_x, *trail_y, player.width, player.height, color);
+        }
+        ctx.set_alpha(1.0);
+
+        ctx.rect(player.x, player.y, player.width, player.height, color);
+        if state.mutators.big_heads {
+            let (head_x, head_y, radius) = big_head(player);
+            ctx.circle(head_x, head_y, radius, color);
+        }
+    }
+    for slot in &state.players {
+        let player = &state.drawn_player(slot);
+        ctx.stroke_rect(player.x, player.y, player.width, player.height, "#fff", 2.0);
+        if state.mutators.big_heads {
+            let (head_x, head_y, radius) = big_head(player);
+            ctx.ring(head_x, head_y, radius, "#fff", 2.0);
+        }
+    }
+    draw_kept_hazards(ctx, state);
+    draw_emotes(ctx, state);
+    for slot in &state.players {
+        let player = &state.drawn_player(slot);
+        ctx.text(
+            &player_label(state, slot.player_index),
+            player.x + player.width / 2.0,
+            player.y + player.height / 2.0 + 3.0,
+            8.0,
+            "#fff",
+            Align::Center,
+        );
+
+        if slot.has_effect(EffectKind::Shield) {
+            ctx.ring(
+                player.x + player.width / 2.0,
+                player.y + player.height / 2.0,
+                player.width.max(player.height) * 0.8,
+                "#66ccff",
+                1.5,
+            );
+        }
+
+        if slot.has_effect(EffectKind::ReversedControls) {
+            draw_swirl(
+                ctx,
+                player.x + player.width / 2.0,
+                player.y - 10.0,
+                state.frame_count,
+            );
+        }
+    }
+
+    // Spawn telegraphs: a blinking chevron at the edge each object will enter from
+    if (state.frame_count / 4).is_multiple_of(2) {
+        for pending in &state.pending_spawns {
+            let obj = &pending.object;
+            let chevron = if obj.y < 0.0 {
+                let cx = (obj.x + obj.width / 2.0).clamp(5.0, world.width - 5.0);
+                [(cx - 5.0, 0.0), (cx + 5.0, 0.0), (cx, 6.0)]
+            } else if obj.x < 0.0 {
+                let cy = obj.y + obj.height / 2.0;
+                [(0.0, cy - 5.0), (0.0, cy + 5.0), (6.0, cy)]
+            } else {
+                let cy = obj.y + obj.height / 2.0;
+                [
+                    (world.width, cy - 5.0),
+                    (world.width, cy + 5.0),
+                    (world.width - 6.0, cy),
+                ]
+            };
+            ctx.polygon(&chevron, "#ffd700");
+        }
+    }
+
+    // Draw falling objects, a color at a time. Overlapping objects of
+    // different colors stack by color rather than by age.
+    let mut sprites = Vec::with_capacity(state.objects.len());
+    for obj in &state.objects {
+        let def = state.config.objects.get(obj.kind);
+        state
+            .behaviors
+            .get(obj.obj_type)
+            .draw(&mut sprites, &state.drawn_object(obj), def);
+    }
+    sprites.sort_by(|a, b| a.sprite.color.cmp(&b.sprite.color));
+    ctx.sprites(&sprites);
+    draw_despawns(ctx, state);
+    ctx.set_color_filter(ColorFilter::default());
+    draw_lane_outlines(ctx, state);
+
+    if let Some(blackout) = &state.blackout {
+        draw_blackout(ctx, state, blackout);
+    }
+
+    draw_whooshes(ctx, &state.whooshes);
+
+    // Floating text on top of the playfield
+    for popup in &state.popups {
+        ctx.set_alpha(1.0 - popup.age as f64 / POPUP_LIFETIME as f64);
+        ctx.text(
+            &popup.text,
+            popup.x,
+            popup.y,
+            9.0,
+            popup.color,
+            Align::Center,
+        );
+    }
+    ctx.set_alpha(1.0);
+
+    if let PlayingPhase::BonusRound { remaining } = state.playing_phase {
+        ctx.text(
+            &format!("BONUS ROUND {}s", remaining.div_ceil(60)),
+            world.centered(120.0),
+            60.0,
+            14.0,
+            "#ffd700",
+            Align::Left,
+        );
+
+        let mut tally_y = 75.0;
+        for slot in &state.players {
+            ctx.text(
+                &format!("P{} x{:.1}", slot.player_index + 1, slot.bonus_multiplier()),
+                world.centered(50.0),
+                tally_y,
+                10.0,
+                "#ffd700",
+                Align::Left,
+            );
+            tally_y += 12.0;
+        }
+    }
+
+    if let PlayingPhase::WaveClear { bonus, .. } = state.playing_phase {
+        ctx.text(
+            &format!("AISLE {} CLEARED", state.wave),
+            world.width / 2.0,
+            60.0,
+            14.0,
+            "#7fff7f",
+            Align::Center,
+        );
+        let tally = if state.mode == PlayerMode::Two {
+            let players: Vec<String> = state
+                .players
+                .iter()
+                .map(|slot| format!("P{} +{}", slot.player_index + 1, bonus[slot.player_index]))
+                .collect();
+            format!("BONUS {}", players.join("  "))
+        } else {
+            format!("BONUS +{}", bonus.iter().sum::<i32>())
+        };
+        ctx.text(
+            &tally,
+            world.width / 2.0,
+            78.0,
+            10.0,
+            "#7fff7f",
+            Align::Center,
+        );
+    }
+
+    // Draw HUD
+    let columns = world.hud_columns();
+    let mut hud_y = 15.0;
+    let hud = &state.hud_text;
+    if state.mode == PlayerMode::Team {
+        let team = hud
+            .team
+            .get((state.team_score(), state.team_combo), |(score, combo)| {
+                format!("TEAM Score: {score}  Combo {combo}")
+            });
+        ctx.text(&team, 5.0, hud_y, 10.0, "#fff", Align::Left);
+        hud_y += 15.0;
+    }
+    for slot in &state.players {
+        let index = slot.player_index;
+        let label = hud.scores[index].get(
+            (state.mode, state.is_bot(index), slot.score),
+            |(mode, _, score)| match mode {
+                PlayerMode::Team => format!("P{}", index + 1),
+                PlayerMode::Single | PlayerMode::Two => {
+                    format!("{} Score: {score}", player_label(state, index))
+                }
+            },
+        );
+        ctx.text(&label, 5.0, hud_y, 10.0, "#fff", Align::Left);
+
+        let heart = "\u{2665}";
+        let hearts_x = columns.hearts + slot.player_index as f64 * columns.hearts_stagger;
+        if slot.health > MAX_HEART_ICONS {
+            let hearts = hud.hearts[index].get(slot.health, |health| format!("{heart}x{health}"));
+            ctx.text(&hearts, hearts_x, hud_y, 10.0, "#ff4444", Align::Left);
+        } else {
+            for i in 0..slot.health {
+                let x = hearts_x + i as f64 * 12.0;
+                ctx.text(heart, x, hud_y, 10.0, "#ff4444", Align::Left);
+            }
+        }
+
+        // Live catch rate, once there's something to measure
+        if let Some(rate) = slot.stats.catch_rate() {
+            let percent = (rate * 100.0).round() as u32;
+            let text = hud.catch_rates[index].get(percent, |percent| format!("{percent}%"));
+            ctx.text(&text, columns.catch_rate, hud_y, 8.0, "#aaa", Align::Left);
+        }
+        draw_ghost_line(ctx, state, slot, hud_y + 3.0);
+        hud_y += 15.0;
+    }
+    if state.split_active() {
+        draw_score_bar(ctx, state, hud_y - 8.0);
+    }
+
+    let seconds = (state.run_time_ms / 1000.0) as u32;
+    let clock = hud
+        .clock
+        .get(seconds, |&seconds| format_duration(seconds as f64 * 1000.0));
+    ctx.text(&clock, world.width - 5.0, 15.0, 10.0, "#fff", Align::Right);
+    draw_heat(ctx, state);
+    draw_pace_marker(ctx, state);
+
+    // Bottom line: the event ticker when it has news, otherwise the instructions
+    if let Some(message) = &state.ticker.current {
+        ctx.text(
+            message,
+            world.width - state.ticker.offset,
+            world.height - 5.0,
+            8.0,
+            "#ffd700",
+            Align::Left,
+        );
+    } else if state.in_demo() {
+        if (state.frame_count / 30).is_multiple_of(2) {
+            ctx.text(
+                "DEMO - PRESS ANY BUTTON",
+                world.width / 2.0,
+                world.height - 5.0,
+                8.0,
+                "#ffd700",
+                Align::Center,
+            );
+        }
+    } else {
+        let instruction = if state.mode.player_count() == 2 {
+            "P1 & P2: D-Pads Move | $ = Good | X = Bad"
+        } else {
+            "D-Pad: Move | $ = Good | X = Bad"
+        };
+        ctx.text(
+            instruction,
+            world.centered(250.0),
+            world.height - 5.0,
+            8.0,
+            "#888",
+            Align::Left,
+        );
+    }
+}
+
+// The wave and a heat gauge for how far the difficulty has climbed, under
+// the clock. Each step up makes it flash and swell for a moment, so a run
+// suddenly feeling faster has an explanation on screen.
+fn draw_heat(ctx: &dyn Renderer, state: &GameState) {
+    let world = state.world;
+    let right = world.width - 5.0;
+    let pulse = if state.wave > 1 && state.wave_timer < HEAT_PULSE_FRAMES {
+        1.0 - state.wave_timer as f64 / HEAT_PULSE_FRAMES as f64
+    } else {
+        0.0
+    };
+    let color = if pulse > 0.0 && (state.wave_timer / 6).is_multiple_of(2) {
+        "#fff"
+    } else {
+        "#ff8c00"
+    };
+    let wave = state
+        .hud_text
+        .wave
+        .get(state.wave, |wave| format!("WAVE {wave}"));
+    ctx.text(&wave, right, 26.0, 8.0 + 2.0 * pulse, color, Align::Right);
+
+    let heat = ((state.difficulty_multiplier - 1.0) / HEAT_FULL).clamp(0.0, 1.0);
+    let height = 4.0 + 2.0 * pulse;
+    let (x, y) = (right - HEAT_GAUGE_WIDTH, 30.0);
+    ctx.rect(x, y, HEAT_GAUGE_WIDTH, height, "#333");
+    ctx.rect(x, y, HEAT_GAUGE_WIDTH * heat, height, color);
+}
+
+// One line of a centered column of text
+#[derive(Clone, Copy)]
+pub(crate) struct TextLine<'a> {
+    pub(crate) text: &'a str,
+    pub(crate) size: f64,
+    pub(crate) color: &'a str,
+}
+
+impl TextLine<'_> {
+    fn height(&self) -> f64 {
+        self.size * 1.5
+    }
+}
+
+pub(crate) fn column_height(lines: &[TextLine]) -> f64 {
+    lines.iter().map(TextLine::height).sum()
+}
+
+// Lines centered on `center_x` going down from `top`, which may be off
+// screen for text that scrolls. Only lines inside `visible` (top and bottom
+// y) are drawn.
+pub(crate) fn draw_text_column(
+    ctx: &dyn Renderer,
+    lines: &[TextLine],
+    center_x: f64,
+    top: f64,
+    visible: (f64, f64),
+) {
+    let mut y = top;
+    for line in lines {
+        y += line.height();
+        if y >= visible.0 + line.size && y <= visible.1 && !line.text.is_empty() {
+            ctx.text(line.text, center_x, y, line.size, line.color, Align::Center);
+        }
+    }
+}
+
+// Title plus a vertical list of options with the selected one highlighted.
+fn draw_option_list(
+    ctx: &dyn Renderer,
+    world: WorldConfig,
+    title: &str,
+    labels: &[String],
+    selection: usize,
+) {
+    ctx.text(title, world.width / 2.0, 50.0, 18.0, "#fff", Align::Center);
+
+    // Closer together when a long list would run into the hint
+    let spacing = ((world.height - 115.0) / labels.len() as f64).min(20.0);
+    for (i, label) in labels.iter().enumerate() {
+        let selected = i == selection;
+        let prefix = if selected { ">" } else { " " };
+        ctx.text(
+            &format!("{prefix} {label}"),
+            world.centered(230.0),
+            85.0 + i as f64 * spacing,
+            12.0,
+            if selected { "#0ff" } else { "#fff" },
+            Align::Left,
+        );
+    }
+
+    ctx.text(
+        "←/→: Select | A: Toggle",
+        10.0,
+        world.height - 10.0,
+        8.0,
+        "#888",
+        Align::Left,
+    );
+}
+
+// Each player's score, time and catch/hit stats for the run just played.
+// Returns where the next line goes.
+fn draw_run_summary(ctx: &dyn Renderer, state: &GameState, mut y: f64) -> f64 {
+    for result in &state.final_scores {
+        let mut text = format!(
+            "{}: {}  ({} survived)",
+            result_label(result, state),
+            result.score,
+            format_duration(result.time_survived_ms)
+        );
+        if result.handicap != Handicap::None {
+            text += &format!("  [{}]", result.handicap.label());
+        }
+        ctx.text(&text, 10.0, y, 10.0, "#fff", Align::Left);
+        y += 11.0;
+
+        let stats = &result.stats;
+        let rate = stats
+            .catch_rate()
+            .map_or("--".to_string(), |rate| format!("{:.0}%", rate * 100.0));
+        ctx.text(
+            &format!(
+                "  Deals {}/{} ({rate}, {} perfect)  Hits {} taken, {} dodged",
+                stats.deals_caught,
+                stats.deals_caught + stats.deals_missed,
+                stats.perfect_catches,
+                stats.hits_taken,
+                stats.hits_avoided
+            ),
+            10.0,
+            y,
+            9.0,
+            "#aaa",
+            Align::Left,
+        );
+        y += 13.0;
+    }
+    y
+}
+
+// Both players' handicap picks side by side, each column on its own d-pad.
+fn draw_ready(ctx: &dyn Renderer, state: &GameState, ready: &[bool; 2]) {
+    let world = state.world;
+    ctx.text(
+        "GET READY",
+        world.width / 2.0,
+        50.0,
+        18.0,
+        "#fff",
+        Align::Center,
+    );
+
+    for (i, handicap) in state.handicaps.iter().enumerate() {
+        let x = world.width * (i as f64 * 2.0 + 1.0) / 4.0;
+        if let Some(bot) = state.bot.as_ref().filter(|bot| bot.player_index == i) {
+            ctx.text("CPU", x, 90.0, 14.0, "#fff", Align::Center);
+            let level = format!("↑ {} ↓", bot.level.label());
+            ctx.text(&level, x, 115.0, 11.0, "#0ff", Align::Center);
+            ctx.text("P1 picks how tough", x, 130.0, 8.0, "#aaa", Align::Center);
+            ctx.text("READY!", x, 160.0, 10.0, "#7fff7f", Align::Center);
+            continue;
+        }
+        ctx.text(&format!("P{}", i + 1), x, 90.0, 14.0, "#fff", Align::Center);
+        let (label, color) = if ready[i] {
+            (handicap.label().to_string(), "#fff")
+        } else {
+            (format!("< {} >", handicap.label()), "#0ff")
+        };
+        ctx.text(&label, x, 115.0, 11.0, color, Align::Center);
+        ctx.text(handicap.description(), x, 130.0, 8.0, "#aaa", Align::Center);
+        let (status, color) = if ready[i] {
+            ("READY!", "#7fff7f")
+        } else {
+            ("A: Ready", "#888")
+        };
+        ctx.text(status, x, 160.0, 10.0, color, Align::Center);
+    }
+
+    let hint = if state.bot.is_some() {
+        "←/→: Handicap | ↑/↓: CPU level | A: Ready"
+    } else {
+        "←/→: Handicap | A: Ready"
+    };
+    ctx.text(hint, 10.0, world.height - 10.0, 8.0, "#888", Align::Left);
+}
+
+// "P1", "P2", or "CPU" for the bot
+fn player_label(state: &GameState, player_index: usize) -> String {
+    if state.is_bot(player_index) {
+        "CPU".to_string()
+    } else {
+        format!("P{}", player_index + 1)
+    }
+}
+
+// A name as the boards show it, cut at a whole character
+pub(crate) fn board_name(name: &str) -> String {
+    name.chars().take(BOARD_NAME_CHARS).collect()
+}
+
+// Whose result it is: a player's, or in team mode the team's combined one
+fn result_label(result: &RunResult, state: &GameState) -> String {
+    match state.mode {
+        PlayerMode::Team => "TEAM".to_string(),
+        PlayerMode::Single | PlayerMode::Two => player_label(state, result.player_index),
+    }
+}
+
+// Standings between tournament runs: a column per round with the winning
+// score picked out, total wins, then the run just played.
+fn draw_series(ctx: &dyn Renderer, state: &GameState, tournament: &Tournament) {
+    let world = state.world;
+    let champion = tournament.champion();
+    let title = match champion {
+        Some(winner) if tournament.ranked => format!("P{} WINS!", winner + 1),
+        None if tournament.ranked => "IT'S A TIE!".to_string(),
+        Some(winner) => format!("P{} IS THE CHAMPION!", winner + 1),
+        None => format!(
+            "ROUND {} - BEST OF {}",
+            tournament.rounds.len(),
+            tournament.setup.rounds
+        ),
+    };
+    let title_color = if champion.is_some() {
+        "#ffd700"
+    } else {
+        "#fff"
+    };
+    ctx.text(
+        &title,
+        world.width / 2.0,
+        30.0,
+        14.0,
+        title_color,
+        Align::Center,
+    );
+
+    let first_shown = tournament
+        .rounds
+        .len()
+        .saturating_sub(world.series_columns());
+    let column_x = |column: usize| 45.0 + column as f64 * SERIES_COLUMN_WIDTH;
+    let mut y = 55.0;
+    for column in first_shown..tournament.rounds.len() {
+        let label = format!("R{}", column + 1);
+        ctx.text(
+            &label,
+            column_x(column - first_shown),
+            y,
+            9.0,
+            "#aaa",
+            Align::Left,
+        );
+    }
+    ctx.text("WINS", world.width - 10.0, y, 9.0, "#aaa", Align::Right);
+    let wins = tournament.wins();
+    for entrant in 0..tournament.setup.entrants {
+        y += 12.0;
+        let color = if champion == Some(entrant) {
+            "#ffd700"
+        } else {
+            "#fff"
+        };
+        ctx.text(
+            &format!("P{}", entrant + 1),
+            10.0,
+            y,
+            10.0,
+            color,
+            Align::Left,
+        );
+        for (column, round) in tournament.rounds.iter().enumerate().skip(first_shown) {
+            let Some(result) = &round[entrant] else {
+                continue;
+            };
+            let won = Tournament::round_winner(round) == Some(entrant);
+            ctx.text(
+                &result.score.to_string(),
+                column_x(column - first_shown),
+                y,
+                9.0,
+                if won { "#ff0" } else { "#ccc" },
+                Align::Left,
+            );
+        }
+        ctx.text(
+            &wins[entrant].to_string(),
+            world.width - 10.0,
+            y,
+            10.0,
+            color,
+            Align::Right,
+        );
+    }
+
+    let heading = if tournament.ranked {
+        "BOTH RUNS"
+    } else {
+        "LAST RUN"
+    };
+    ctx.text(heading, 10.0, y + 22.0, 9.0, "#aaa", Align::Left);
+    draw_run_summary(ctx, state, y + 36.0);
+
+    let prompt = match (champion, tournament.setup.format) {
+        _ if tournament.ranked => "A: Continue".to_string(),
+        (Some(_), _) => "A: Menu".to_string(),
+        (None, TournamentFormat::Turns) => format!("A: P{}'s turn", tournament.turn + 1),
+        (None, TournamentFormat::Versus) => "A: Next round".to_string(),
+    };
+    ctx.text(&prompt, 10.0, world.height - 10.0, 8.0, "#888", Align::Left);
+}
+
+// m:ss for the run timer and summaries.
+pub(crate) fn format_duration(ms: f64) -> String {
+    let total_seconds = (ms / 1000.0) as u32;
+    format!("{}:{:02}", total_seconds / 60, total_seconds % 60)
+}
+
+// Darkness overlay with a hole punched around each player. The HUD is drawn
+// afterwards so it stays readable.
+fn draw_blackout(ctx: &dyn Renderer, state: &GameState, blackout: &Blackout) {
+    if blackout.warning > 0 {
+        // Failing fluorescent tubes: brief dark flashes at an uneven rhythm
+        if (blackout.warning / 4).is_multiple_of(3) {
+            ctx.clear("rgba(0, 0, 0, 0.6)");
+        }
+        return;
+    }
+
+    let lights: Vec<Point> = state
+        .players
+        .iter()
+        .map(|slot| {
+            let player = &state.drawn_player(slot);
+            (
+                player.x + player.width / 2.0,
+                player.y + player.height / 2.0,
+            )
+        })
+        .collect();
+    // Two passes with shrinking holes give the light a soft edge
+    for (alpha, radius) in [
+        (0.85, BLACKOUT_LIGHT_RADIUS),
+        (0.5, BLACKOUT_LIGHT_RADIUS * 0.7),
+    ] {
+        ctx.darken(&format!("rgba(0, 0, 0, {alpha})"), &lights, radius);
+    }
+}
+
+// Spinning spiral drawn above a player whose controls are reversed.
+fn draw_swirl(ctx: &dyn Renderer, cx: f64, cy: f64, frame: u32) {
+    let rotation = frame as f64 * 0.2;
+    let points: Vec<Point> = (0..=24)
+        .map(|step| {
+            let t = step as f64 / 24.0;
+            let angle = rotation + t * std::f64::consts::PI * 4.0;
+            let radius = 1.0 + t * 6.0;
+            (cx + angle.cos() * radius, cy + angle.sin() * radius)
+        })
+        .collect();
+    ctx.polyline(&points, "#ff66ff", 1.5);
+}
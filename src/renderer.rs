@@ -0,0 +1,480 @@
+// Drawing backend. The draw code in render.rs only talks to a Renderer, so a
+// WebGL backend, a headless test renderer or a spectator mirror can reuse it;
+// the browser canvas is the implementation the game ships with.
+//
+// Colors are CSS color strings and text is sized in pixels, in the page's
+// arcade font if it has one and monospace otherwise.
+//
+// The canvas backend remembers which fill, stroke, font and alignment the
+// context has, and only sets them again when they change: draw code tends to
+// draw runs of things in the same color, and every setter is a call out to JS.
+//
+// With pixel snap on, the canvas backend rounds every position and size to
+// whole logical pixels on the way in, and scales sprite images without
+// smoothing, so the playfield looks like a low resolution screen blown up.
+
+use super::*;
+
+const FONT_WAIT_FRAMES: u32 = 180; // How long text waits on a web font before going without
+
+pub(crate) type Point = (f64, f64);
+
+// Recolors everything drawn while it's set
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct ColorFilter {
+    pub(crate) hue_rotation: u32, // Degrees
+    pub(crate) saturation: f64,   // 1 leaves colors as they are, 0 is grayscale
+}
+
+impl Default for ColorFilter {
+    fn default() -> Self {
+        ColorFilter {
+            hue_rotation: 0,
+            saturation: 1.0,
+        }
+    }
+}
+
+// One object's sprite, where and how big to draw it
+#[derive(Clone, Copy)]
+pub(crate) struct SpriteDraw<'a> {
+    pub(crate) sprite: &'a Sprite,
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) w: f64,
+    pub(crate) h: f64,
+}
+
+impl<'a> SpriteDraw<'a> {
+    pub(crate) fn of(obj: &FallingObject, sprite: &'a Sprite) -> Self {
+        SpriteDraw {
+            sprite,
+            x: obj.x,
+            y: obj.y,
+            w: obj.width,
+            h: obj.height,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+pub(crate) trait Renderer {
+    fn clear(&self, color: &str);
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64, color: &str);
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64, color: &str, line_width: f64);
+    fn circle(&self, x: f64, y: f64, radius: f64, color: &str);
+    fn ring(&self, x: f64, y: f64, radius: f64, color: &str, line_width: f64);
+    fn ellipse(&self, x: f64, y: f64, radius_x: f64, radius_y: f64, color: &str);
+    fn polygon(&self, points: &[Point], color: &str);
+    fn polyline(&self, points: &[Point], color: &str, line_width: f64);
+    // Unconnected line segments, used for particle streaks
+    fn particles(&self, segments: &[(Point, Point)], color: &str, line_width: f64);
+    fn text(&self, text: &str, x: f64, y: f64, size: f64, color: &str, align: Align);
+    // Covers the whole screen except a circle of `radius` around each light
+    fn darken(&self, color: &str, lights: &[Point], radius: f64);
+    // Applies to everything drawn until it's set again
+    fn set_alpha(&self, alpha: f64);
+    fn set_color_filter(&self, filter: ColorFilter);
+    // Draws everything after it moved and scaled, p becoming p * scale +
+    // (dx, dy), until the matching pop_transform
+    fn push_transform(&self, dx: f64, dy: f64, scale: f64);
+    fn pop_transform(&self);
+    // Called before each frame is drawn, once the viewport has set it up
+    fn begin_frame(&self) {}
+    // Whole logical pixels for everything drawn until it's set again
+    fn set_pixel_snap(&self, _snap: bool) {}
+
+    // Backends with real textures can override this; by default the sprites
+    // are built from the primitives above. They go down in passes, so a run of
+    // sprites sorted by color draws without switching colors back and forth.
+    fn sprites(&self, sprites: &[SpriteDraw]) {
+        draw_shapes(self, sprites);
+    }
+}
+
+// Sprites built from the primitives, every body and then every detail on top
+fn draw_shapes<R: Renderer + ?Sized>(ctx: &R, sprites: &[SpriteDraw]) {
+    for &SpriteDraw { sprite, x, y, w, h } in sprites {
+        let color = &sprite.color;
+        match sprite.shape {
+            SpriteShape::Box => ctx.rect(x, y, w, h, color),
+            SpriteShape::Flyer => ctx.rect(x, y + h * 0.1, w, h * 0.8, color),
+            SpriteShape::Can => ctx.rect(x + w * 0.2, y, w * 0.6, h, color),
+            SpriteShape::Cup => ctx.rect(x + w * 0.15, y + h * 0.2, w * 0.7, h * 0.8, color),
+        }
+    }
+    // Cups have a straw poking out of the lid
+    for &SpriteDraw { sprite, x, y, w, h } in sprites {
+        if sprite.shape == SpriteShape::Cup {
+            ctx.rect(x + w * 0.55, y, 2.0, h * 0.3, "#fff");
+        }
+    }
+    for &SpriteDraw { sprite, x, y, w, h } in sprites {
+        if sprite.glyph.is_empty() {
+            continue;
+        }
+        let (gx, gy, size) = if sprite.shape == SpriteShape::Can {
+            (0.3, 0.7, 0.6)
+        } else {
+            (0.25, 0.75, 0.7)
+        };
+        ctx.text(
+            &sprite.glyph,
+            x + w * gx,
+            y + h * gy,
+            (h * size).round(),
+            &sprite.glyph_color,
+            Align::Left,
+        );
+    }
+}
+
+pub(crate) struct CanvasRenderer {
+    context: CanvasRenderingContext2d,
+    palette: Palette,
+    // What's set on the context now; empty or None when it isn't known
+    fill: RefCell<String>,
+    stroke: RefCell<String>,
+    line_width: Cell<Option<f64>>,
+    font_size: Cell<Option<f64>>,
+    align: Cell<Option<Align>>,
+    atlas: Rc<RefCell<Option<SpriteAtlas>>>, // Filled in once it's loaded
+    font_family: RefCell<String>,            // CSS font-family for all text
+    font_status: Preload,                    // Ready when there's no web font to wait for
+    frames_drawn: Cell<u32>,                 // Drives sprite animation and the font wait
+    pixel_snap: Cell<bool>,
+}
+
+impl CanvasRenderer {
+    pub(crate) fn new(context: CanvasRenderingContext2d) -> Self {
+        CanvasRenderer {
+            context,
+            palette: Palette::default(),
+            fill: RefCell::default(),
+            stroke: RefCell::default(),
+            line_width: Cell::new(None),
+            font_size: Cell::new(None),
+            align: Cell::new(None),
+            atlas: Rc::default(),
+            font_family: RefCell::new("monospace".to_string()),
+            font_status: Rc::new(Cell::new(LoadStatus::Ready)),
+            frames_drawn: Cell::new(0),
+            pixel_snap: Cell::new(false),
+        }
+    }
+
+    // Switches text over to a web font, holding it back until the font loads
+    pub(crate) fn load_font(&self, source: FontSource) -> Preload {
+        *self.font_family.borrow_mut() = format!("\"{}\", monospace", source.family);
+        self.font_size.set(None);
+        self.font_status.set(LoadStatus::Loading);
+        source.load(self.font_status.clone());
+        self.font_status.clone()
+    }
+
+    // Starts loading the atlas; sprites use it from the first frame after
+    pub(crate) fn load_atlas(&self, source: AtlasSource) -> Preload {
+        let atlas = self.atlas.clone();
+        let status = Rc::new(Cell::new(LoadStatus::Loading));
+        let loading = status.clone();
+        spawn_local(async move {
+            match SpriteAtlas::load(&source).await {
+                Ok(loaded) => {
+                    *atlas.borrow_mut() = Some(loaded);
+                    loading.set(LoadStatus::Ready);
+                }
+                Err(err) => {
+                    log(
+                        LogLevel::Warn,
+                        "atlas",
+                        &format!("sprite atlas unavailable, drawing shapes: {err:?}"),
+                    );
+                    loading.set(LoadStatus::Failed);
+                }
+            }
+        });
+        status
+    }
+
+    fn set_fill(&self, color: &str) {
+        let mut fill = self.fill.borrow_mut();
+        if *fill != color {
+            set_fill_color(&self.context, &self.palette.color(color));
+            fill.clear();
+            fill.push_str(color);
+        }
+    }
+
+    fn set_stroke(&self, color: &str, line_width: f64) {
+        let mut stroke = self.stroke.borrow_mut();
+        if *stroke != color {
+            set_stroke_color(&self.context, &self.palette.color(color));
+            stroke.clear();
+            stroke.push_str(color);
+        }
+        if self.line_width.get() != Some(line_width) {
+            self.context.set_line_width(line_width);
+            self.line_width.set(Some(line_width));
+        }
+    }
+
+    fn set_font(&self, size: f64, align: Align) {
+        if self.font_size.get() != Some(size) {
+            self.context
+                .set_font(&format!("{size}px {}", self.font_family.borrow()));
+            self.font_size.set(Some(size));
+        }
+        if self.align.get() != Some(align) {
+            self.context.set_text_align(match align {
+                Align::Left => "start",
+                Align::Center => "center",
+                Align::Right => "right",
+            });
+            self.align.set(Some(align));
+        }
+    }
+
+    fn snap(&self, value: f64) -> f64 {
+        if self.pixel_snap.get() {
+            value.round()
+        } else {
+            value
+        }
+    }
+
+    fn snap_point(&self, (x, y): Point) -> Point {
+        (self.snap(x), self.snap(y))
+    }
+
+    // Snaps both edges rather than the size, so neighbours still meet
+    fn snap_span(&self, start: f64, length: f64) -> (f64, f64) {
+        let snapped = self.snap(start);
+        (snapped, self.snap(start + length) - snapped)
+    }
+
+    fn trace_path(&self, points: &[Point]) {
+        if let Some((&first, rest)) = points.split_first() {
+            let (x, y) = self.snap_point(first);
+            self.context.move_to(x, y);
+            for &point in rest {
+                let (x, y) = self.snap_point(point);
+                self.context.line_to(x, y);
+            }
+        }
+    }
+
+    // A restore can put any of them back to what they were
+    fn forget_styles(&self) {
+        self.fill.borrow_mut().clear();
+        self.stroke.borrow_mut().clear();
+        self.line_width.set(None);
+        self.font_size.set(None);
+        self.align.set(None);
+    }
+}
+
+impl Renderer for CanvasRenderer {
+    fn clear(&self, color: &str) {
+        self.set_fill(color);
+        let ctx = &self.context;
+        ctx.save();
+        cover_canvas(ctx);
+        ctx.restore();
+        ctx.fill();
+    }
+
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64, color: &str) {
+        let (x, width) = self.snap_span(x, width);
+        let (y, height) = self.snap_span(y, height);
+        self.set_fill(color);
+        self.context.fill_rect(x, y, width, height);
+    }
+
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64, color: &str, line_width: f64) {
+        let (x, width) = self.snap_span(x, width);
+        let (y, height) = self.snap_span(y, height);
+        self.set_stroke(color, line_width);
+        self.context.stroke_rect(x, y, width, height);
+    }
+
+    fn circle(&self, x: f64, y: f64, radius: f64, color: &str) {
+        let (x, y) = self.snap_point((x, y));
+        self.set_fill(color);
+        let ctx = &self.context;
+        ctx.begin_path();
+        ctx.arc(x, y, radius, 0.0, std::f64::consts::PI * 2.0)
+            .unwrap();
+        ctx.fill();
+    }
+
+    fn ring(&self, x: f64, y: f64, radius: f64, color: &str, line_width: f64) {
+        let (x, y) = self.snap_point((x, y));
+        self.set_stroke(color, line_width);
+        let ctx = &self.context;
+        ctx.begin_path();
+        ctx.arc(x, y, radius, 0.0, std::f64::consts::PI * 2.0)
+            .unwrap();
+        ctx.stroke();
+    }
+
+    fn ellipse(&self, x: f64, y: f64, radius_x: f64, radius_y: f64, color: &str) {
+        let (x, y) = self.snap_point((x, y));
+        self.set_fill(color);
+        let ctx = &self.context;
+        ctx.begin_path();
+        ctx.ellipse(
+            x,
+            y,
+            radius_x,
+            radius_y,
+            0.0,
+            0.0,
+            std::f64::consts::PI * 2.0,
+        )
+        .unwrap();
+        ctx.fill();
+    }
+
+    fn polygon(&self, points: &[Point], color: &str) {
+        self.set_fill(color);
+        let ctx = &self.context;
+        ctx.begin_path();
+        self.trace_path(points);
+        ctx.close_path();
+        ctx.fill();
+    }
+
+    fn polyline(&self, points: &[Point], color: &str, line_width: f64) {
+        self.set_stroke(color, line_width);
+        let ctx = &self.context;
+        ctx.begin_path();
+        self.trace_path(points);
+        ctx.stroke();
+    }
+
+    fn particles(&self, segments: &[(Point, Point)], color: &str, line_width: f64) {
+        self.set_stroke(color, line_width);
+        let ctx = &self.context;
+        ctx.begin_path();
+        for &(from, to) in segments {
+            let (x1, y1) = self.snap_point(from);
+            let (x2, y2) = self.snap_point(to);
+            ctx.move_to(x1, y1);
+            ctx.line_to(x2, y2);
+        }
+        ctx.stroke();
+    }
+
+    fn text(&self, text: &str, x: f64, y: f64, size: f64, color: &str, align: Align) {
+        if self.font_status.get() == LoadStatus::Loading
+            && self.frames_drawn.get() < FONT_WAIT_FRAMES
+        {
+            return;
+        }
+        let (x, y) = self.snap_point((x, y));
+        self.set_fill(color);
+        self.set_font(self.snap(size), align);
+        self.context.fill_text(text, x, y).unwrap();
+    }
+
+    fn darken(&self, color: &str, lights: &[Point], radius: f64) {
+        // Even-odd filling turns each light's circle into a hole
+        self.set_fill(color);
+        let ctx = &self.context;
+        ctx.save();
+        cover_canvas(ctx);
+        ctx.restore();
+        for &light in lights {
+            let (cx, cy) = self.snap_point(light);
+            ctx.move_to(cx + radius, cy);
+            ctx.arc(cx, cy, radius, 0.0, std::f64::consts::PI * 2.0)
+                .unwrap();
+        }
+        ctx.fill_with_canvas_winding_rule(CanvasWindingRule::Evenodd);
+    }
+
+    fn set_alpha(&self, alpha: f64) {
+        self.context.set_global_alpha(alpha);
+    }
+
+    fn set_color_filter(&self, filter: ColorFilter) {
+        if filter == ColorFilter::default() {
+            self.context.set_filter("none");
+        } else {
+            self.context.set_filter(&format!(
+                "hue-rotate({}deg) saturate({})",
+                filter.hue_rotation, filter.saturation
+            ));
+        }
+    }
+
+    fn push_transform(&self, dx: f64, dy: f64, scale: f64) {
+        self.context.save();
+        self.context
+            .transform(scale, 0.0, 0.0, scale, dx, dy)
+            .unwrap();
+    }
+
+    fn pop_transform(&self) {
+        self.context.restore();
+        self.forget_styles();
+    }
+
+    fn begin_frame(&self) {
+        self.forget_styles();
+        self.frames_drawn
+            .set(self.frames_drawn.get().wrapping_add(1));
+    }
+
+    fn set_pixel_snap(&self, snap: bool) {
+        self.pixel_snap.set(snap);
+        self.context.set_image_smoothing_enabled(!snap);
+    }
+
+    fn sprites(&self, sprites: &[SpriteDraw]) {
+        let atlas = self.atlas.borrow();
+        let Some(atlas) = &*atlas else {
+            draw_shapes(self, sprites);
+            return;
+        };
+        let ticks = self.frames_drawn.get();
+        let shapes: Vec<SpriteDraw> = sprites
+            .iter()
+            .map(|draw| {
+                let (x, w) = self.snap_span(draw.x, draw.w);
+                let (y, h) = self.snap_span(draw.y, draw.h);
+                SpriteDraw {
+                    x,
+                    y,
+                    w,
+                    h,
+                    ..*draw
+                }
+            })
+            .filter(|draw| !atlas.draw(&self.context, draw, ticks))
+            .collect();
+        draw_shapes(self, &shapes);
+    }
+}
+
+// Starts a path over the whole canvas in its own pixels, whatever the
+// transform. Whatever's filled with it still stays inside the playfield clip.
+fn cover_canvas(ctx: &CanvasRenderingContext2d) {
+    ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0).unwrap();
+    ctx.begin_path();
+    if let Some(canvas) = ctx.canvas() {
+        CanvasRenderingContext2d::rect(
+            ctx,
+            0.0,
+            0.0,
+            canvas.width() as f64,
+            canvas.height() as f64,
+        );
+    }
+}
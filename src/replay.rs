@@ -0,0 +1,246 @@
+// A finished run as the seed and inputs that played it, attached to remote
+// score submissions. Anyone with the same game-config can play it back
+// through the headless core and see whether it really ends with the claimed
+// scores; `simulate --replay` does exactly that. The same bundle is how a
+// run still in progress survives a page reload.
+//
+// Inputs are one byte per tick, as logged by GameState::tick, run-length
+// encoded as comma-separated "held*ticks" pairs: "0*90,1*12,0*40" is ninety
+// ticks of nothing, twelve of P1 holding left, then forty of nothing. Bits
+// 16 and 32 are P1's and P2's A, which throws in versus.
+
+use super::*;
+
+// Four hours of play; longer claims aren't worth expanding
+const MAX_REPLAY_TICKS: usize = 4 * 60 * 60 * 60;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplayBundle {
+    pub(crate) players: usize,
+    #[serde(default)]
+    pub(crate) team: bool, // Two players on one score
+    pub(crate) seed: String, // Decimal, as a JS number can't hold every u64
+    pub(crate) mutators: Mutators,
+    pub(crate) adaptive_difficulty: bool,
+    #[serde(default)]
+    pub(crate) big_pieces: bool,
+    #[serde(default)]
+    pub(crate) spawn_lanes: bool,
+    #[serde(default)]
+    pub(crate) world: WorldConfig, // The playfield's size changes where things land
+    #[serde(default)]
+    pub(crate) difficulty: Difficulty,
+    #[serde(default)]
+    pub(crate) handicaps: [Handicap; 2],
+    #[serde(default)]
+    pub(crate) new_game_plus: u32,
+    pub(crate) inputs: String,
+}
+
+// What playing a bundle back came to
+#[cfg(any(test, feature = "simulator"))]
+pub(crate) struct ReplayOutcome {
+    pub(crate) scores: Vec<RunResult>, // In the order players went out
+    pub(crate) ticks: usize,
+    pub(crate) input_hash: u64,
+}
+
+impl ReplayBundle {
+    // The run that just ended, or is still going
+    pub(crate) fn record(state: &GameState) -> Self {
+        ReplayBundle {
+            players: state.mode.player_count(),
+            team: state.mode == PlayerMode::Team,
+            seed: state.seed.to_string(),
+            mutators: state.mutators,
+            adaptive_difficulty: state.settings.adaptive_difficulty,
+            big_pieces: state.settings.big_pieces,
+            spawn_lanes: state.settings.spawn_lanes,
+            world: state.world,
+            difficulty: state.config.difficulty,
+            handicaps: state.handicaps,
+            new_game_plus: state.new_game_plus,
+            inputs: encode_inputs(&state.input_log),
+        }
+    }
+
+    // Starts the bundle's run on `state`, returning the inputs to play it with
+    pub(crate) fn start(&self, state: &mut GameState) -> Result<Vec<u8>, String> {
+        let mode = match (self.players, self.team) {
+            (1, false) => PlayerMode::Single,
+            (2, false) => PlayerMode::Two,
+            (2, true) => PlayerMode::Team,
+            (1, true) => return Err("a team has 2 players".to_string()),
+            (other, _) => return Err(format!("a run has 1 or 2 players, not {other}")),
+        };
+        let seed = self
+            .seed
+            .parse()
+            .map_err(|_| format!("bad seed {:?}", self.seed))?;
+        let inputs = decode_inputs(&self.inputs)?;
+        if state.world != self.world {
+            return Err("the playfield is a different size".to_string());
+        }
+
+        state.config.difficulty = self.difficulty;
+        state.mutators = self.mutators;
+        state.settings.adaptive_difficulty = self.adaptive_difficulty;
+        state.settings.big_pieces = self.big_pieces;
+        state.settings.spawn_lanes = self.spawn_lanes;
+        state.handicaps = self.handicaps;
+        state.new_game_plus = self.new_game_plus;
+        state.start_seeded_game(mode, seed);
+        Ok(inputs)
+    }
+
+    #[cfg(any(test, feature = "simulator"))]
+    pub(crate) fn play(&self, mut config: GameConfig) -> Result<ReplayOutcome, String> {
+        config.world = self.world;
+        let mut state = GameState::new(config, Rc::new(MemoryStore::default()));
+        let inputs = self.start(&mut state)?;
+        for (tick, &held) in inputs.iter().enumerate() {
+            if !matches!(state.phase, GamePhase::Playing) {
+                return Err(format!(
+                    "the run ended after {tick} of {} ticks",
+                    inputs.len()
+                ));
+            }
+            state.replay_tick(held);
+        }
+        if matches!(state.phase, GamePhase::Playing) {
+            return Err(format!(
+                "the run was still going after {} ticks",
+                inputs.len()
+            ));
+        }
+        Ok(ReplayOutcome {
+            scores: state.final_scores.clone(),
+            ticks: inputs.len(),
+            input_hash: state.input_hash(),
+        })
+    }
+}
+
+impl GameState {
+    // One logged tick played back
+    pub(crate) fn replay_tick(&mut self, held: u8) {
+        // Every bit goes back in, even for a player not in the run, so the
+        // replayed log hashes the same as the original
+        let commands: Vec<Command> = (0..PlayerMode::Two.player_count())
+            .flat_map(|player_index| {
+                let bits = held >> (player_index * 2);
+                let left = (bits & 1 != 0).then_some(Command::MoveLeft(player_index));
+                let right = (bits & 2 != 0).then_some(Command::MoveRight(player_index));
+                let throw =
+                    (held & (16 << player_index) != 0).then_some(Command::Throw(player_index));
+                left.into_iter().chain(right).chain(throw)
+            })
+            .collect();
+        self.tick(&commands);
+    }
+
+    // Digest of the input log, which is what score signatures cover
+    #[cfg(any(test, feature = "cabinet-sync", feature = "simulator"))]
+    pub(crate) fn input_hash(&self) -> u64 {
+        let mut hasher = StateHasher::default();
+        hasher.write(&self.input_log);
+        hasher.finish()
+    }
+}
+
+fn encode_inputs(inputs: &[u8]) -> String {
+    let mut runs: Vec<(u8, usize)> = Vec::new();
+    for &held in inputs {
+        match runs.last_mut() {
+            Some((last, count)) if *last == held => *count += 1,
+            _ => runs.push((held, 1)),
+        }
+    }
+    runs.iter()
+        .map(|(held, count)| format!("{held}*{count}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn decode_inputs(text: &str) -> Result<Vec<u8>, String> {
+    let mut inputs = Vec::new();
+    for run in text.split(',').filter(|run| !run.is_empty()) {
+        let parsed = run.split_once('*').and_then(|(held, count)| {
+            Some((held.parse::<u8>().ok()?, count.parse::<usize>().ok()?))
+        });
+        let Some((held, count)) = parsed else {
+            return Err(format!("bad input run {run:?}"));
+        };
+        if count > MAX_REPLAY_TICKS - inputs.len() {
+            return Err("replay is too long".to_string());
+        }
+        inputs.extend(std::iter::repeat_n(held, count));
+    }
+    Ok(inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inputs_round_trip() {
+        let inputs = [0, 0, 0, 1, 1, 2, 0, 48, 48, 48, 5];
+        let encoded = encode_inputs(&inputs);
+        assert_eq!(encoded, "0*3,1*2,2*1,0*1,48*3,5*1");
+        assert_eq!(decode_inputs(&encoded), Ok(inputs.to_vec()));
+        assert_eq!(encode_inputs(&[]), "");
+        assert_eq!(decode_inputs(""), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn malformed_runs_are_rejected() {
+        for text in [
+            "0", "0*", "*5", "256*1", "1*x", "-1*2", "0*-1", "1*2*3", "0*1;1*1",
+        ] {
+            assert!(decode_inputs(text).is_err(), "{text:?} decoded");
+        }
+    }
+
+    #[test]
+    fn overlong_replays_are_rejected() {
+        assert_eq!(
+            decode_inputs(&format!("0*{MAX_REPLAY_TICKS}")).map(|inputs| inputs.len()),
+            Ok(MAX_REPLAY_TICKS)
+        );
+        assert!(decode_inputs(&format!("0*{}", MAX_REPLAY_TICKS + 1)).is_err());
+        assert!(decode_inputs(&format!("0*{MAX_REPLAY_TICKS},1*1")).is_err());
+        assert!(decode_inputs(&format!("0*{}", usize::MAX)).is_err());
+    }
+
+    #[test]
+    fn playing_a_recording_gets_the_same_scores() {
+        let mut state = GameState::new(GameConfig::default(), Rc::new(MemoryStore::default()));
+        state.start_seeded_game(PlayerMode::Two, 7);
+        let mut ticks = 0;
+        while matches!(state.phase, GamePhase::Playing) {
+            // Each player drifts one way and then the other
+            let commands = match (ticks / 90) % 3 {
+                0 => vec![Command::MoveLeft(0), Command::MoveRight(1)],
+                1 => vec![Command::MoveRight(0)],
+                _ => vec![Command::MoveLeft(1), Command::Throw(0)],
+            };
+            state.tick(&commands);
+            ticks += 1;
+            assert!(ticks < MAX_REPLAY_TICKS, "the run never ended");
+        }
+        let bundle = ReplayBundle::record(&state);
+        let outcome = bundle.play(GameConfig::default()).unwrap();
+
+        assert_eq!(outcome.ticks, ticks);
+        assert_eq!(outcome.input_hash, state.input_hash());
+        let scores = |results: &[RunResult]| -> Vec<(usize, i32)> {
+            results
+                .iter()
+                .map(|result| (result.player_index, result.score))
+                .collect()
+        };
+        assert_eq!(scores(&outcome.scores), scores(&state.final_scores));
+    }
+}
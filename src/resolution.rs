@@ -0,0 +1,87 @@
+// Dynamic resolution, for kiosk hardware that can't keep up at full size.
+// The time between frames is watched all the time: once it's been over
+// budget for a second or so, the canvas drops to a lower internal resolution
+// and the browser stretches it back over the same box on the page, so
+// there's less to fill every frame. After a long enough run of frames back on
+// time it tries the next size up again. Only how many canvas pixels there
+// are changes; the game still lays out and draws in logical units.
+
+use super::*;
+
+const RENDER_SCALES: [f64; 3] = [1.0, 0.75, 0.5]; // Share of full resolution, best first
+const FRAME_BUDGET_MS: f64 = TICK_MS * 1.25; // Slower than this on average and frames are being missed
+const ON_TIME_MS: f64 = TICK_MS * 1.05; // Faster than this on average and there's headroom
+const AVERAGE_WEIGHT: f64 = 0.05; // How much each new frame moves the running average
+const SLOW_FRAMES_TO_DROP: u32 = 60;
+const ON_TIME_FRAMES_TO_RESTORE: u32 = 600; // Longer than dropping, so it doesn't flip back and forth
+
+pub(crate) struct ResolutionGovernor {
+    level: usize, // Index into RENDER_SCALES
+    last_ms: Option<f64>,
+    average_ms: f64,
+    slow_frames: u32,
+    on_time_frames: u32,
+}
+
+impl Default for ResolutionGovernor {
+    fn default() -> Self {
+        ResolutionGovernor {
+            level: 0,
+            last_ms: None,
+            average_ms: TICK_MS,
+            slow_frames: 0,
+            on_time_frames: 0,
+        }
+    }
+}
+
+impl ResolutionGovernor {
+    pub(crate) fn scale(&self) -> f64 {
+        RENDER_SCALES[self.level]
+    }
+
+    // Called at the start of every frame; true when the scale has changed and
+    // the canvas needs fitting again
+    pub(crate) fn update(&mut self, now_ms: f64) -> bool {
+        let Some(last_ms) = self.last_ms.replace(now_ms) else {
+            return false;
+        };
+        let elapsed = now_ms - last_ms;
+        // A stall is a hiccup or the page in the background, not a slow frame
+        if !(0.0..STALL_GAP_MS).contains(&elapsed) {
+            return false;
+        }
+        self.average_ms += (elapsed - self.average_ms) * AVERAGE_WEIGHT;
+
+        if self.average_ms > FRAME_BUDGET_MS {
+            self.slow_frames += 1;
+            self.on_time_frames = 0;
+        } else if self.average_ms < ON_TIME_MS {
+            self.on_time_frames += 1;
+            self.slow_frames = 0;
+        }
+
+        let level = if self.slow_frames >= SLOW_FRAMES_TO_DROP {
+            (self.level + 1).min(RENDER_SCALES.len() - 1)
+        } else if self.on_time_frames >= ON_TIME_FRAMES_TO_RESTORE {
+            self.level.saturating_sub(1)
+        } else {
+            return false;
+        };
+        self.slow_frames = 0;
+        self.on_time_frames = 0;
+        if level == self.level {
+            return false;
+        }
+        self.level = level;
+        // Start the next judgement from on time, so one step is all a slow
+        // spell takes before the new size gets a chance
+        self.average_ms = TICK_MS;
+        log(
+            LogLevel::Info,
+            "render",
+            &format!("drawing at {}% resolution", self.scale() * 100.0),
+        );
+        true
+    }
+}
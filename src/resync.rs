@@ -0,0 +1,42 @@
+// What a stall looks like. When the frame clock stalls mid-run, the
+// simulation only catches up a couple of ticks (see FixedStep), so the
+// playfield doesn't leap ahead, but the time it lost has still gone by. The
+// screen dims and comes back over a third of a second, with a RESYNC note,
+// so the hitch reads as a deliberate beat rather than a glitch.
+
+use super::*;
+
+const RESYNC_FADE_FRAMES: u32 = 20;
+const RESYNC_DIM: f64 = 0.6; // How dark the screen starts
+
+#[derive(Default)]
+pub(crate) struct ResyncFade {
+    frames_left: u32,
+}
+
+impl ResyncFade {
+    pub(crate) fn start(&mut self) {
+        self.frames_left = RESYNC_FADE_FRAMES;
+    }
+
+    // Drawn over everything else, once a frame
+    pub(crate) fn draw(&mut self, ctx: &dyn Renderer, world: WorldConfig) {
+        if self.frames_left == 0 {
+            return;
+        }
+        let fade = self.frames_left as f64 / RESYNC_FADE_FRAMES as f64;
+        self.frames_left -= 1;
+        ctx.set_alpha(RESYNC_DIM * fade);
+        ctx.rect(0.0, 0.0, world.width, world.height, "#000");
+        ctx.set_alpha(fade);
+        ctx.text(
+            "RESYNC",
+            world.width / 2.0,
+            world.height / 2.0,
+            12.0,
+            "#fff",
+            Align::Center,
+        );
+        ctx.set_alpha(1.0);
+    }
+}
@@ -0,0 +1,169 @@
+// Rollback for online versus, built with `--features netplay`. The session
+// plays ahead of the network: when the other player's input for a tick hasn't
+// arrived yet, it guesses they're still holding whatever they last sent and
+// keeps a snapshot from before the tick. If the real input turns out to be
+// different, the state is wound back to that snapshot and the ticks since are
+// played again with what's now known.
+//
+// Achievements and personal bests earned on a guessed tick are rolled back
+// with everything else, and only saved once every tick up to them is
+// confirmed, so a death a rollback takes back can't set a best for good.
+//
+// Both sides must start from the same seed and deliver their inputs in order;
+// netplay.rs takes care of that.
+
+use super::*;
+
+// How many ticks can be played on guesses before waiting for the other side,
+// which bounds both the snapshot count and the length of a replay
+const MAX_GUESSED_TICKS: usize = 8;
+// Hashes of fully confirmed ticks, kept to compare with the other side's
+const CONFIRMED_HASHES_KEPT: usize = 120;
+
+// One player's held buttons for a tick
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) struct TickInput {
+    pub(crate) left: bool,
+    pub(crate) right: bool,
+    pub(crate) throw: bool,
+}
+
+impl TickInput {
+    pub(crate) fn from_commands(commands: &[Command], player_index: usize) -> Self {
+        TickInput {
+            left: commands.contains(&Command::MoveLeft(player_index)),
+            right: commands.contains(&Command::MoveRight(player_index)),
+            throw: commands.contains(&Command::Throw(player_index)),
+        }
+    }
+
+    fn push_commands(self, player_index: usize, commands: &mut Vec<Command>) {
+        if self.left {
+            commands.push(Command::MoveLeft(player_index));
+        }
+        if self.right {
+            commands.push(Command::MoveRight(player_index));
+        }
+        if self.throw {
+            commands.push(Command::Throw(player_index));
+        }
+    }
+}
+
+// A tick played before the other side's input for it arrived
+struct Guess {
+    before: Snapshot,  // State going into the tick
+    remote: TickInput, // What the other player was assumed to hold
+    hash: u64,         // State hash after the tick, final if the guess was right
+}
+
+pub(crate) struct RollbackSession {
+    local_player: usize,
+    local: Vec<TickInput>,    // Ours, for every tick played so far
+    remote: Vec<TickInput>,   // Theirs, as far as it has arrived
+    guesses: VecDeque<Guess>, // One per tick from remote.len() on, oldest first
+    confirmed_hashes: VecDeque<(u32, u64)>,
+}
+
+impl RollbackSession {
+    pub(crate) fn new(local_player: usize) -> Self {
+        RollbackSession {
+            local_player,
+            local: Vec::new(),
+            remote: Vec::new(),
+            guesses: VecDeque::new(),
+            confirmed_hashes: VecDeque::new(),
+        }
+    }
+
+    // Plays the next tick with our input. Returns the tick's number to send
+    // along with the input, or None when it's too far ahead of the other side
+    // and has to wait for them.
+    pub(crate) fn advance(&mut self, state: &mut GameState, input: TickInput) -> Option<u32> {
+        if self.guesses.len() >= MAX_GUESSED_TICKS {
+            return None;
+        }
+        let tick = self.local.len();
+        self.local.push(input);
+        self.play(state, tick);
+        Some(tick as u32)
+    }
+
+    // The other side's input for `tick`. Anything but the next one expected
+    // is a duplicate or out of order and is ignored.
+    pub(crate) fn receive(&mut self, state: &mut GameState, tick: u32, input: TickInput) {
+        if tick as usize != self.remote.len() {
+            return;
+        }
+        self.remote.push(input);
+        let Some(guess) = self.guesses.pop_front() else {
+            // Not played yet; it'll be used when we get there
+            return;
+        };
+        if guess.remote == input {
+            self.confirm(tick, guess.hash);
+        } else {
+            // Wrong guess: wind back to that tick and replay up to where we were
+            let played = self.local.len();
+            self.guesses.clear();
+            state.restore(guess.before);
+            for tick in tick as usize..played {
+                self.play(state, tick);
+            }
+        }
+        if self.settled() {
+            state.save_progress();
+        }
+    }
+
+    // Every tick played so far has been confirmed by the other side
+    pub(crate) fn settled(&self) -> bool {
+        self.guesses.is_empty()
+    }
+
+    // The latest tick both sides' inputs are known for, and its state hash
+    pub(crate) fn latest_confirmed(&self) -> Option<(u32, u64)> {
+        self.confirmed_hashes.back().copied()
+    }
+
+    // False if the other side's hash for a confirmed tick differs from ours
+    pub(crate) fn in_sync(&self, tick: u32, hash: u64) -> bool {
+        self.confirmed_hashes
+            .iter()
+            .find(|(confirmed, _)| *confirmed == tick)
+            .is_none_or(|(_, ours)| *ours == hash)
+    }
+
+    fn play(&mut self, state: &mut GameState, tick: usize) {
+        let known = self.remote.get(tick).copied();
+        let remote = known.unwrap_or_else(|| self.remote.last().copied().unwrap_or_default());
+        let before = known.is_none().then(|| state.snapshot());
+
+        let mut inputs = [TickInput::default(); 2];
+        inputs[self.local_player] = self.local[tick];
+        inputs[1 - self.local_player] = remote;
+        let mut commands = Vec::new();
+        for (player_index, input) in inputs.into_iter().enumerate() {
+            input.push_commands(player_index, &mut commands);
+        }
+        state.hold_saves = before.is_some();
+        let hash = state.tick(&commands);
+        state.hold_saves = false;
+
+        match before {
+            Some(before) => self.guesses.push_back(Guess {
+                before,
+                remote,
+                hash,
+            }),
+            None => self.confirm(tick as u32, hash),
+        }
+    }
+
+    fn confirm(&mut self, tick: u32, hash: u64) {
+        if self.confirmed_hashes.len() >= CONFIRMED_HASHES_KEPT {
+            self.confirmed_hashes.pop_front();
+        }
+        self.confirmed_hashes.push_back((tick, hash));
+    }
+}
@@ -0,0 +1,66 @@
+// The QR code on the game over screen. It encodes a link to the share page
+// with the run's score, seed and initials, so a player can snap their run at
+// the cabinet and take it home on their phone.
+
+use super::*;
+use qrcodegen::{QrCode, QrCodeEcc};
+
+// Light border a scanner needs around the code, in modules
+const QUIET_ZONE: i32 = 4;
+
+pub(crate) struct ShareCode {
+    pub(crate) score: i32,
+    qr: QrCode,
+}
+
+impl ShareCode {
+    pub(crate) fn new(base_url: &str, result: &RunResult, seed: u64, name: &str) -> Option<Self> {
+        let separator = if base_url.contains('?') { '&' } else { '?' };
+        let url = format!(
+            "{base_url}{separator}score={}&seed={seed}&name={}",
+            result.score,
+            encode_query_value(name)
+        );
+        let qr = QrCode::encode_text(&url, QrCodeEcc::Low).ok()?;
+        Some(ShareCode {
+            score: result.score,
+            qr,
+        })
+    }
+
+    // Width and height when drawn at `module` pixels per module
+    pub(crate) fn drawn_size(&self, module: f64) -> f64 {
+        (self.qr.size() + QUIET_ZONE * 2) as f64 * module
+    }
+
+    pub(crate) fn draw(&self, ctx: &dyn Renderer, x: f64, y: f64, module: f64) {
+        let size = self.drawn_size(module);
+        ctx.rect(x, y, size, size, "#fff");
+        for row in 0..self.qr.size() {
+            for col in 0..self.qr.size() {
+                if self.qr.get_module(col, row) {
+                    ctx.rect(
+                        x + (col + QUIET_ZONE) as f64 * module,
+                        y + (row + QUIET_ZONE) as f64 * module,
+                        module,
+                        module,
+                        "#000",
+                    );
+                }
+            }
+        }
+    }
+}
+
+// Names can have spaces and hearts in them
+fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
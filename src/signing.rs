@@ -0,0 +1,122 @@
+// Signatures on scores submitted to a remote leaderboard. Builds made with
+// BLACK_FRIDAY_SCORE_KEY set in the environment sign every score they
+// publish with HMAC-SHA256 under that key; a service holding the same key
+// checks submissions with `verify`, natively through this crate or by
+// recomputing the MAC over `ScoreClaim::message` itself. The key ships inside
+// the wasm, so this keeps out hand-written POSTs rather than a determined
+// player.
+
+use hmac_sha256::HMAC;
+
+// What a signature covers
+pub struct ScoreClaim {
+    pub score: i32,
+    pub seed: u64,
+    pub input_hash: u64,   // Digest of the run's held inputs, tick by tick
+    pub timestamp_ms: u64, // Unix time of submission
+}
+
+impl ScoreClaim {
+    // The signed bytes: "score:seed:input_hash:timestamp_ms" in decimal
+    pub fn message(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.score, self.seed, self.input_hash, self.timestamp_ms
+        )
+    }
+}
+
+// The key this build signs with, if it was given one
+pub fn build_key() -> Option<&'static [u8]> {
+    option_env!("BLACK_FRIDAY_SCORE_KEY").map(str::as_bytes)
+}
+
+// Lowercase hex of the MAC
+pub fn sign(claim: &ScoreClaim, key: &[u8]) -> String {
+    HMAC::mac(claim.message(), key)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+// Whether `signature` (as made by `sign`) is right for the claim. The
+// comparison takes the same time wherever the first wrong byte is.
+pub fn verify(claim: &ScoreClaim, signature: &str, key: &[u8]) -> bool {
+    let Some(expected) = decode_hex(signature) else {
+        return false;
+    };
+    HMAC::verify(claim.message(), key, &expected)
+}
+
+fn decode_hex(text: &str) -> Option<[u8; 32]> {
+    let text = text.as_bytes();
+    if text.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0; 32];
+    for (byte, pair) in bytes.iter_mut().zip(text.chunks(2)) {
+        *byte = (hex_digit(pair[0])? << 4) | hex_digit(pair[1])?;
+    }
+    Some(bytes)
+}
+
+// Digits and a-f in either case, and nothing else; from_str_radix would also
+// take a sign
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"test key";
+
+    fn claim() -> ScoreClaim {
+        ScoreClaim {
+            score: 1234,
+            seed: 42,
+            input_hash: 0xdead_beef,
+            timestamp_ms: 1_700_000_000_000,
+        }
+    }
+
+    #[test]
+    fn signature_verifies() {
+        let signature = sign(&claim(), KEY);
+        assert_eq!(signature.len(), 64);
+        assert!(verify(&claim(), &signature, KEY));
+        assert!(verify(&claim(), &signature.to_uppercase(), KEY));
+    }
+
+    #[test]
+    fn tampered_claim_fails() {
+        let signature = sign(&claim(), KEY);
+        let raised = ScoreClaim {
+            score: 99_999,
+            ..claim()
+        };
+        assert!(!verify(&raised, &signature, KEY));
+        assert!(!verify(&claim(), &signature, b"another key"));
+    }
+
+    #[test]
+    fn malformed_signature_fails() {
+        let signature = sign(&claim(), KEY);
+        assert!(!verify(&claim(), "", KEY));
+        assert!(!verify(&claim(), &signature[..62], KEY));
+        assert!(!verify(&claim(), &format!("{signature}00"), KEY));
+        let not_hex = format!("zz{}", &signature[2..]);
+        assert!(!verify(&claim(), &not_hex, KEY));
+    }
+
+    #[test]
+    fn signed_digits_are_not_hex() {
+        // "+f" parses as 15 with from_str_radix
+        let signature = sign(&claim(), KEY);
+        let signed = format!("+{}", &signature[1..]);
+        assert_eq!(decode_hex(&signed), None);
+        assert_eq!(decode_hex(&"+f".repeat(32)), None);
+        assert_eq!(decode_hex(&"0f".repeat(32)), Some([15; 32]));
+    }
+}
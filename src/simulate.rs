@@ -0,0 +1,355 @@
+// Headless balancing runs, built with `--features simulator` and run natively:
+//
+//   cargo run --release --features simulator --bin simulate -- --runs 2000
+//
+//   --runs N             how many games to play (default 500)
+//   --strategy NAME      idle, random or chase (default chase)
+//   --players 1|2|team   one or two players per game, or a team of two
+//                        (default 1)
+//   --config FILE        a game-config JSON blob, as embedded in index.html
+//   --seed N             seed of the first run; later runs count up from it
+//   --verify             play every run twice and fail if the two differ
+//   --replay FILE        instead play back a submitted score's replay (or a
+//                        bare replay bundle) and check the claimed score
+//
+// Every game runs tick by tick as fast as the machine allows, and the score,
+// survival time and wave reached of each player are summarized at the end.
+// The strategies are deliberately naive; compare distributions between
+// configs rather than reading the absolute numbers as human play.
+
+use super::*;
+
+// A run that's still going after this long is cut off and counted as is
+const MAX_RUN_MINUTES: f64 = 30.0;
+// Random strategy: how long each direction (or standing still) is held
+const RANDOM_HOLD_TICKS: std::ops::Range<u32> = 5..40;
+// Chase strategy: how far above a player a hazard is worth stepping away from
+const CHASE_DANGER_HEIGHT: f64 = 120.0;
+const HISTOGRAM_BUCKETS: usize = 10;
+const HISTOGRAM_WIDTH: usize = 40;
+
+#[derive(Clone, Copy)]
+enum Strategy {
+    Idle,   // Never moves
+    Random, // Wanders left and right at random
+    Chase,  // Heads for the lowest deal, sidestepping hazards about to land
+}
+
+struct Options {
+    runs: u32,
+    strategy: Strategy,
+    mode: PlayerMode,
+    config: Option<String>, // Contents of --config
+    seed: u64,
+    verify: bool,
+    replay: Option<String>, // Contents of --replay
+}
+
+// One player's run
+struct Outcome {
+    score: i32,
+    seconds: f64,
+    wave: u32,
+    timed_out: bool,
+}
+
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), String> {
+    let options = parse_args(args)?;
+    if let Some(text) = &options.replay {
+        return check_replay(&options, text);
+    }
+    let mut outcomes = Vec::new();
+    for run in 0..options.runs {
+        let seed = options.seed.wrapping_add(run as u64);
+        let (outcome, digest) = play_one(&options, seed);
+        if options.verify && play_one(&options, seed).1 != digest {
+            return Err(format!(
+                "run with seed {seed} played out differently the second time"
+            ));
+        }
+        outcomes.extend(outcome);
+    }
+    print_report(&options, &outcomes);
+    Ok(())
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        runs: 500,
+        strategy: Strategy::Chase,
+        mode: PlayerMode::Single,
+        config: None,
+        seed: rand::random(),
+        verify: false,
+        replay: None,
+    };
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or(format!("{flag} needs a value"));
+        match flag.as_str() {
+            "--runs" => {
+                options.runs = value()?
+                    .parse()
+                    .map_err(|_| "--runs takes a number".to_string())?;
+            }
+            "--strategy" => {
+                options.strategy = match value()?.as_str() {
+                    "idle" => Strategy::Idle,
+                    "random" => Strategy::Random,
+                    "chase" => Strategy::Chase,
+                    other => return Err(format!("unknown strategy {other}")),
+                };
+            }
+            "--players" => {
+                options.mode = match value()?.as_str() {
+                    "1" => PlayerMode::Single,
+                    "2" => PlayerMode::Two,
+                    "team" => PlayerMode::Team,
+                    other => return Err(format!("--players takes 1, 2 or team, not {other}")),
+                };
+            }
+            "--config" => {
+                let path = value()?;
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|err| format!("can't read {path}: {err}"))?;
+                options.config = Some(text);
+            }
+            "--seed" => {
+                options.seed = value()?
+                    .parse()
+                    .map_err(|_| "--seed takes a number".to_string())?;
+            }
+            "--verify" => options.verify = true,
+            "--replay" => {
+                let path = value()?;
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|err| format!("can't read {path}: {err}"))?;
+                options.replay = Some(text);
+            }
+            other => return Err(format!("unknown option {other}")),
+        }
+    }
+    Ok(options)
+}
+
+// Also returns a digest of every tick's state hash, for --verify
+fn play_one(options: &Options, seed: u64) -> (Vec<Outcome>, u64) {
+    let mut state = GameState::new(game_config(options), Rc::new(MemoryStore::default()));
+    state.start_seeded_game(options.mode, seed);
+
+    // The strategy's own choices are seeded too, so a seed replays exactly
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut digest = StateHasher::default();
+    let mut held = [None, None]; // Random strategy: (command, ticks left) per player
+    let max_ticks = (MAX_RUN_MINUTES * 60_000.0 / TICK_MS) as u32;
+    let mut ticks = 0;
+    while matches!(state.phase, GamePhase::Playing) && ticks < max_ticks {
+        let mut commands = Vec::new();
+        let player_count = options.mode.player_count();
+        for (player_index, held) in held.iter_mut().enumerate().take(player_count) {
+            let command = match options.strategy {
+                Strategy::Idle => None,
+                Strategy::Random => {
+                    let (command, left) = held.get_or_insert_with(|| {
+                        let command = match rng.gen_range(0..3) {
+                            0 => Some(Command::MoveLeft(player_index)),
+                            1 => Some(Command::MoveRight(player_index)),
+                            _ => None,
+                        };
+                        (command, rng.gen_range(RANDOM_HOLD_TICKS))
+                    });
+                    let command = *command;
+                    *left -= 1;
+                    if *left == 0 {
+                        *held = None;
+                    }
+                    command
+                }
+                Strategy::Chase => chase(&state, player_index),
+            };
+            commands.extend(command);
+        }
+        state.tick(&commands).hash(&mut digest);
+        ticks += 1;
+    }
+
+    let timed_out = matches!(state.phase, GamePhase::Playing);
+    let mut results = state.final_scores.clone();
+    // Players still standing when the run was cut off count with what they have
+    results.extend(state.players.iter().map(|slot| RunResult {
+        player_index: slot.player_index,
+        score: slot.score,
+        time_survived_ms: state.run_time_ms,
+        stats: slot.stats,
+        handicap: slot.handicap,
+    }));
+    let outcomes = results
+        .iter()
+        .map(|result| Outcome {
+            score: result.score,
+            seconds: result.time_survived_ms / 1000.0,
+            wave: state.wave,
+            timed_out,
+        })
+        .collect();
+    (outcomes, digest.finish())
+}
+
+fn game_config(options: &Options) -> GameConfig {
+    match &options.config {
+        Some(text) => GameConfig::from_json(text),
+        None => GameConfig::default(),
+    }
+}
+
+// Plays the run back and holds it to whatever the submission claims
+fn check_replay(options: &Options, text: &str) -> Result<(), String> {
+    let submission: serde_json::Value =
+        serde_json::from_str(text).map_err(|err| format!("replay isn't JSON: {err}"))?;
+    let bundle = submission.get("replay").unwrap_or(&submission);
+    let bundle: ReplayBundle =
+        serde_json::from_value(bundle.clone()).map_err(|err| format!("bad replay: {err}"))?;
+    let outcome = bundle.play(game_config(options))?;
+
+    println!(
+        "{} player(s), seed {}, {} ticks, input hash {}",
+        bundle.players, bundle.seed, outcome.ticks, outcome.input_hash
+    );
+    for result in &outcome.scores {
+        println!(
+            "P{} scored {} in {:.1}s",
+            result.player_index + 1,
+            result.score,
+            result.time_survived_ms / 1000.0
+        );
+    }
+
+    if let Some(claimed) = submission.get("score").and_then(|score| score.as_i64()) {
+        if !outcome
+            .scores
+            .iter()
+            .any(|result| result.score as i64 == claimed)
+        {
+            return Err(format!(
+                "claimed score {claimed} isn't what the replay scores"
+            ));
+        }
+        println!("claimed score {claimed} matches");
+    }
+    if let Some(claimed) = submission.get("inputHash").and_then(|hash| hash.as_str()) {
+        if claimed != outcome.input_hash.to_string() {
+            return Err(format!("claimed input hash {claimed} isn't the replay's"));
+        }
+        println!("claimed input hash matches");
+    }
+    Ok(())
+}
+
+fn chase(state: &GameState, player_index: usize) -> Option<Command> {
+    let slot = state
+        .players
+        .iter()
+        .find(|slot| slot.player_index == player_index)?;
+    let center = slot.player.x + slot.player.width / 2.0;
+    let object_center = |obj: &FallingObject| obj.x + obj.width / 2.0;
+    let toward = |x: f64| {
+        if x < center - slot.player.width / 4.0 {
+            Some(Command::MoveLeft(player_index))
+        } else if x > center + slot.player.width / 4.0 {
+            Some(Command::MoveRight(player_index))
+        } else {
+            None
+        }
+    };
+
+    let wanted = |obj: &&FallingObject| {
+        matches!(obj.obj_type, ObjectType::GoodDeal | ObjectType::EnergyDrink)
+    };
+    let danger = state.objects.iter().filter(|obj| !wanted(obj)).find(|obj| {
+        obj.y + obj.height > slot.player.y - CHASE_DANGER_HEIGHT
+            && obj.y < slot.player.y + slot.player.height
+            && (object_center(obj) - center).abs() < (obj.width + slot.player.width) / 2.0
+    });
+    if let Some(hazard) = danger {
+        // Step away from it, towards whichever side it isn't on
+        return if object_center(hazard) > center {
+            Some(Command::MoveLeft(player_index))
+        } else {
+            Some(Command::MoveRight(player_index))
+        };
+    }
+
+    let target = state
+        .objects
+        .iter()
+        .filter(wanted)
+        .max_by(|a, b| a.y.total_cmp(&b.y))?;
+    toward(object_center(target))
+}
+
+fn print_report(options: &Options, outcomes: &[Outcome]) {
+    let strategy = match options.strategy {
+        Strategy::Idle => "idle",
+        Strategy::Random => "random",
+        Strategy::Chase => "chase",
+    };
+    println!(
+        "{} runs, {} player(s), {strategy} strategy, seeds from {}",
+        options.runs,
+        options.mode.player_count(),
+        options.seed
+    );
+    if options.verify {
+        println!("every run replayed identically");
+    }
+    if outcomes.is_empty() {
+        return;
+    }
+    let timed_out = outcomes.iter().filter(|outcome| outcome.timed_out).count();
+    if timed_out > 0 {
+        println!("{timed_out} player run(s) cut off after {MAX_RUN_MINUTES} minutes");
+    }
+    println!();
+
+    let scores: Vec<f64> = outcomes.iter().map(|o| o.score as f64).collect();
+    let seconds: Vec<f64> = outcomes.iter().map(|o| o.seconds).collect();
+    let waves: Vec<f64> = outcomes.iter().map(|o| o.wave as f64).collect();
+    print_summary("score", &scores);
+    print_summary("seconds", &seconds);
+    print_summary("wave", &waves);
+
+    println!();
+    print_histogram("score", &scores);
+}
+
+fn print_summary(label: &str, values: &[f64]) {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+    let mean = sorted.iter().sum::<f64>() / sorted.len() as f64;
+    println!(
+        "{label:<8} min {:>8.1}  p10 {:>8.1}  median {:>8.1}  p90 {:>8.1}  max {:>8.1}  mean {mean:>8.1}",
+        percentile(0.0),
+        percentile(0.1),
+        percentile(0.5),
+        percentile(0.9),
+        percentile(1.0),
+    );
+}
+
+fn print_histogram(label: &str, values: &[f64]) {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let bucket_size = ((max - min) / HISTOGRAM_BUCKETS as f64).max(1.0);
+    let mut counts = [0usize; HISTOGRAM_BUCKETS];
+    for value in values {
+        let bucket = ((value - min) / bucket_size) as usize;
+        counts[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+    }
+    let tallest = counts.iter().copied().max().unwrap_or(0).max(1);
+    println!("{label} distribution");
+    for (i, count) in counts.iter().enumerate() {
+        let from = min + bucket_size * i as f64;
+        let bar = "#".repeat(count * HISTOGRAM_WIDTH / tallest);
+        println!("{from:>8.0} - {:<8.0} {count:>6} {bar}", from + bucket_size);
+    }
+}
@@ -0,0 +1,53 @@
+// Drawing between ticks. The simulation moves in fixed ticks, but the screen
+// refreshes whenever the browser gets to it, rarely in step, so drawing
+// everything where the latest tick left it makes motion judder. Smooth
+// drawing, the default, puts objects and players part way from where they
+// were a tick before to where they are now, by however much of the next tick
+// has gone by: always a fraction of a tick behind, which nobody can see.
+//
+// The pixel snap setting trades that for a chunky retro look: everything is
+// drawn where the latest tick left it, rounded to whole logical pixels, and
+// sprite images are scaled up without smoothing.
+
+use super::*;
+
+impl GameState {
+    // Called at the start of every tick, before anything moves
+    pub(crate) fn remember_positions(&mut self) {
+        for obj in &mut self.objects {
+            obj.before = (obj.x, obj.y);
+        }
+        for slot in &mut self.players {
+            slot.player.before = (slot.player.x, slot.player.y);
+        }
+    }
+
+    // Called once a frame before drawing, with how much of the next tick the
+    // clock has already counted
+    pub(crate) fn set_blend(&mut self, blend: f64) {
+        self.blend = if self.settings.pixel_snap { 1.0 } else { blend };
+    }
+
+    fn blended(&self, before: (f64, f64), x: f64, y: f64) -> (f64, f64) {
+        let (before_x, before_y) = before;
+        (
+            before_x + (x - before_x) * self.blend,
+            before_y + (y - before_y) * self.blend,
+        )
+    }
+
+    // The object as it's drawn this frame
+    pub(crate) fn drawn_object(&self, obj: &FallingObject) -> FallingObject {
+        let mut drawn = obj.clone();
+        (drawn.x, drawn.y) = self.blended(obj.before, obj.x, obj.y);
+        drawn
+    }
+
+    // The player as they're drawn this frame
+    pub(crate) fn drawn_player(&self, slot: &PlayerSlot) -> Player {
+        let player = &slot.player;
+        let mut drawn = player.clone();
+        (drawn.x, drawn.y) = self.blended(player.before, player.x, player.y);
+        drawn
+    }
+}
@@ -0,0 +1,74 @@
+// Splash: the RCade and Black Friday marks, about two seconds of them, before
+// the menu, the way the other cabinet titles open. Any button skips it.
+//
+// The logo drops in from above with a little overshoot, a price tag swings
+// under it, and the whole thing fades out at the end.
+
+use super::*;
+
+const SPLASH_FRAMES: u32 = 120;
+const DROP_FRAMES: u32 = 30; // How long the logo takes to land
+const FADE_FRAMES: u32 = 20;
+
+impl GameState {
+    pub(crate) fn show_splash(&mut self) {
+        self.phase = GamePhase::Splash { frames: 0 };
+    }
+
+    fn finish_splash(&mut self) {
+        self.phase = GamePhase::main_menu();
+        self.offer_resume();
+    }
+
+    pub(crate) fn handle_splash(&mut self, commands: &[Command]) {
+        if !commands.is_empty() {
+            self.finish_splash();
+        }
+    }
+
+    // Called once a frame
+    pub(crate) fn update_splash(&mut self) {
+        let GamePhase::Splash { frames } = &mut self.phase else {
+            return;
+        };
+        *frames += 1;
+        if *frames >= SPLASH_FRAMES {
+            self.finish_splash();
+        }
+    }
+}
+
+// Lands at 1 after passing it a little, like something dropped onto a shelf
+fn drop_in(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0) - 1.0;
+    1.0 + t * t * (2.7 * t + 1.7)
+}
+
+pub(crate) fn draw_splash(ctx: &dyn Renderer, world: WorldConfig, frames: u32) {
+    let center = world.width / 2.0;
+    let fade = ((SPLASH_FRAMES - frames) as f64 / FADE_FRAMES as f64).min(1.0);
+    ctx.set_alpha(fade);
+
+    ctx.text(
+        "RCADE PRESENTS",
+        center,
+        world.height * 0.3,
+        8.0,
+        "#888",
+        Align::Center,
+    );
+
+    let landed = drop_in(frames as f64 / DROP_FRAMES as f64);
+    let logo_y = world.height * 0.5 * landed;
+    ctx.text("BLACK FRIDAY", center, logo_y, 22.0, "#fff", Align::Center);
+
+    // A price tag hanging under the logo, swinging to a stop
+    let swing = (frames as f64 * 0.25).sin() * 8.0 * (-(frames as f64) / 40.0).exp();
+    let tag_x = center + swing;
+    let tag_y = logo_y + 14.0;
+    ctx.polyline(&[(center, logo_y + 4.0), (tag_x, tag_y)], "#888", 1.0);
+    ctx.rect(tag_x - 14.0, tag_y, 28.0, 14.0, "#ff4444");
+    ctx.text("SALE", tag_x, tag_y + 10.0, 8.0, "#fff", Align::Center);
+
+    ctx.set_alpha(1.0);
+}
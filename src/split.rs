@@ -0,0 +1,92 @@
+// Split floor, a mutator for two player games: a line down the middle that
+// neither players nor anything falling can cross, which turns a 2P game into
+// two solo runs side by side. Everything belongs to the half it starts in,
+// drops take turns between the halves as with spawn lanes, and throws come
+// in from the outside edge of theirs. A bar under the scores shows who's
+// ahead.
+
+use super::*;
+
+const DIVIDER_WIDTH: f64 = 2.0;
+const BAR_WIDTH: f64 = 120.0;
+const BAR_HEIGHT: f64 = 4.0;
+
+impl GameState {
+    pub(crate) fn split_active(&self) -> bool {
+        self.mutators.split_floor && self.mode == PlayerMode::Two
+    }
+
+    // Where a player may stand: their own half on a split floor
+    pub(crate) fn player_span(&self, player_index: usize) -> (f64, f64) {
+        if self.split_active() {
+            lane_span(self.world, player_index)
+        } else {
+            (0.0, self.world.width)
+        }
+    }
+
+    // Gives anything queued without a half the one it starts in
+    pub(crate) fn assign_half(&self, obj: &mut FallingObject) {
+        if self.split_active() && obj.lane.is_none() {
+            let center = obj.x + obj.width / 2.0;
+            obj.lane = Some(usize::from(center >= self.world.width / 2.0));
+        }
+    }
+
+    // Called after objects move, so wind and throws stop at the line
+    pub(crate) fn keep_objects_in_halves(&mut self) {
+        if !self.split_active() {
+            return;
+        }
+        let world = self.world;
+        for obj in &mut self.objects {
+            if let Some(lane) = obj.lane {
+                let (left, right) = lane_span(world, lane);
+                obj.x = obj.x.clamp(left, (right - obj.width).max(left));
+            }
+        }
+    }
+}
+
+pub(crate) fn draw_divider(ctx: &dyn Renderer, state: &GameState) {
+    let world = state.world;
+    let x = world.width / 2.0 - DIVIDER_WIDTH / 2.0;
+    ctx.set_alpha(0.5);
+    ctx.rect(x, 0.0, DIVIDER_WIDTH, world.floor_y(), "#fff");
+    ctx.set_alpha(1.0);
+}
+
+// Each player's share of the points scored so far, in their color; even
+// until someone scores. A player who's out keeps the score they finished on.
+pub(crate) fn draw_score_bar(ctx: &dyn Renderer, state: &GameState, y: f64) {
+    let mut scores = [0; 2];
+    for slot in &state.players {
+        scores[slot.player_index] = slot.score.max(0);
+    }
+    for result in &state.final_scores {
+        scores[result.player_index] = result.score.max(0);
+    }
+    let total = scores[0] + scores[1];
+    let share = if total > 0 {
+        scores[0] as f64 / total as f64
+    } else {
+        0.5
+    };
+    let left = state.world.centered(BAR_WIDTH);
+    let split = BAR_WIDTH * share;
+    ctx.rect(left, y, split, BAR_HEIGHT, PLAYER_COLORS[0]);
+    ctx.rect(
+        left + split,
+        y,
+        BAR_WIDTH - split,
+        BAR_HEIGHT,
+        PLAYER_COLORS[1],
+    );
+    ctx.rect(
+        left + BAR_WIDTH / 2.0 - 0.5,
+        y - 1.0,
+        1.0,
+        BAR_HEIGHT + 2.0,
+        "#fff",
+    );
+}
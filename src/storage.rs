@@ -0,0 +1,714 @@
+// Persistence for the leaderboard, settings and unlocked cheat modes. Game
+// code only sees a KeyValueStore; main() decides which backend it gets.
+
+use super::*;
+
+const LEADERBOARD_KEY: &str = "black_friday_leaderboard";
+const SETTINGS_KEY: &str = "black_friday_settings";
+const UNLOCKS_KEY: &str = "black_friday_unlocks";
+const ACHIEVEMENTS_KEY: &str = "black_friday_achievements";
+const PERSONAL_BEST_KEY: &str = "black_friday_personal_best";
+const SUSPENDED_RUN_KEY: &str = "black_friday_suspended_run";
+const CRASH_KEY: &str = "black_friday_last_crash";
+const SEEN_HOW_TO_PLAY_KEY: &str = "black_friday_seen_how_to_play";
+const PROBE_KEY: &str = "black_friday_probe";
+
+const IDB_NAME: &str = "black_friday";
+const IDB_STORE: &str = "kv";
+
+// String values by key. Writes are best effort: a full or unavailable store
+// shouldn't stop the game.
+pub(crate) trait KeyValueStore {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&self, key: &str, value: &str);
+    fn name(&self) -> &'static str; // For the operator menu
+}
+
+pub(crate) struct LocalStorage {
+    storage: web_sys::Storage,
+}
+
+impl LocalStorage {
+    // None when the browser has localStorage turned off
+    pub(crate) fn open() -> Option<Self> {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        Some(LocalStorage { storage })
+    }
+}
+
+impl KeyValueStore for LocalStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.storage.get_item(key).ok()?
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        if let Err(err) = self.storage.set_item(key, value) {
+            log(
+                LogLevel::Warn,
+                "storage",
+                &format!("couldn't save {key}: {err:?}"),
+            );
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "localStorage"
+    }
+}
+
+// Forgets everything on reload. Used when nothing better is available and by
+// anything running the game without a browser.
+#[derive(Default)]
+pub(crate) struct MemoryStore {
+    values: RefCell<HashMap<String, String>>,
+}
+
+impl KeyValueStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.values.borrow().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        self.values
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    fn name(&self) -> &'static str {
+        "memory only"
+    }
+}
+
+// A stand-in for testing the save paths, by the tests below and from the
+// debug console. It keeps values in memory, records every write, and can be
+// told to fail reads, so everything looks missing, or writes, which are
+// dropped the way a full or revoked store would drop them.
+#[cfg(any(test, feature = "debug-console"))]
+#[derive(Default)]
+pub(crate) struct MockStore {
+    values: MemoryStore,
+    writes: RefCell<Vec<MockWrite>>,
+    pub(crate) fail_reads: Cell<bool>,
+    pub(crate) fail_writes: Cell<bool>,
+}
+
+#[cfg(any(test, feature = "debug-console"))]
+#[derive(Clone)]
+pub(crate) struct MockWrite {
+    pub(crate) key: String,
+    pub(crate) bytes: usize,
+    pub(crate) failed: bool,
+}
+
+#[cfg(any(test, feature = "debug-console"))]
+impl MockStore {
+    // Every write so far, oldest first, failed ones included
+    pub(crate) fn writes(&self) -> Vec<MockWrite> {
+        self.writes.borrow().clone()
+    }
+}
+
+#[cfg(any(test, feature = "debug-console"))]
+impl KeyValueStore for MockStore {
+    fn get(&self, key: &str) -> Option<String> {
+        if self.fail_reads.get() {
+            return None;
+        }
+        self.values.get(key)
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        let failed = self.fail_writes.get();
+        self.writes.borrow_mut().push(MockWrite {
+            key: key.to_string(),
+            bytes: value.len(),
+            failed,
+        });
+        if failed {
+            log(
+                LogLevel::Warn,
+                "storage",
+                &format!("couldn't save {key}: mock store failing writes"),
+            );
+            return;
+        }
+        self.values.set(key, value);
+    }
+
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+}
+
+// IndexedDB, for webviews that turn localStorage off but keep IndexedDB.
+// Everything is read into memory when the database opens, so reads stay
+// synchronous; writes update the copy and are sent on without waiting.
+pub(crate) struct IndexedDbStore {
+    db: web_sys::IdbDatabase,
+    cache: MemoryStore,
+}
+
+impl IndexedDbStore {
+    pub(crate) async fn open() -> Result<Self, JsValue> {
+        let factory = web_sys::window()
+            .unwrap()
+            .indexed_db()?
+            .ok_or("IndexedDB unavailable")?;
+        let request = factory.open_with_u32(IDB_NAME, 1)?;
+        let upgrading = request.clone();
+        let upgrade = Closure::once(move || {
+            if let Ok(db) = upgrading.result() {
+                let db: web_sys::IdbDatabase = db.unchecked_into();
+                let _ = db.create_object_store(IDB_STORE);
+            }
+        });
+        request.set_onupgradeneeded(Some(upgrade.as_ref().unchecked_ref()));
+        let db: web_sys::IdbDatabase = finished(&request).await?.unchecked_into();
+        request.set_onupgradeneeded(None);
+
+        let store = db
+            .transaction_with_str(IDB_STORE)?
+            .object_store(IDB_STORE)?;
+        let keys = js_sys::Array::from(&finished(&store.get_all_keys()?).await?);
+        let values = js_sys::Array::from(&finished(&store.get_all()?).await?);
+        let cache = MemoryStore::default();
+        for (key, value) in keys.iter().zip(values.iter()) {
+            if let (Some(key), Some(value)) = (key.as_string(), value.as_string()) {
+                cache.set(&key, &value);
+            }
+        }
+        Ok(IndexedDbStore { db, cache })
+    }
+}
+
+impl KeyValueStore for IndexedDbStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.cache.get(key)
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        self.cache.set(key, value);
+        let Ok(transaction) = self
+            .db
+            .transaction_with_str_and_mode(IDB_STORE, web_sys::IdbTransactionMode::Readwrite)
+        else {
+            return;
+        };
+        let put = transaction.object_store(IDB_STORE).and_then(|store| {
+            store.put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))
+        });
+        if let Err(err) = put {
+            log(
+                LogLevel::Warn,
+                "storage",
+                &format!("couldn't save {key}: {err:?}"),
+            );
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "IndexedDB"
+    }
+}
+
+// Waits for an IndexedDB request and returns its result.
+async fn finished(request: &web_sys::IdbRequest) -> Result<JsValue, JsValue> {
+    let done = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    wasm_bindgen_futures::JsFuture::from(done).await?;
+    request.result()
+}
+
+#[derive(Clone)]
+pub(crate) struct LeaderboardEntry {
+    pub(crate) score: i32,
+    pub(crate) mode: PlayerMode,
+    pub(crate) name: String,
+    pub(crate) time_survived_ms: f64,
+    pub(crate) mutated: bool,
+    pub(crate) latest: bool, // From the last run played here, so it's highlighted
+    pub(crate) new_game_plus: u32, // The NEW GAME+ loop it was scored in, if any
+}
+
+// A leaderboard entry as it's stored. Mode is 0 for 1P, 1 for 2P and 2 for
+// a team.
+#[derive(Serialize, Deserialize)]
+struct SavedEntry {
+    score: i32,
+    mode: u8,
+    #[serde(default = "default_name")]
+    name: String,
+    // Entries saved before the run timer existed have no time
+    #[serde(default)]
+    time: f64,
+    #[serde(default)]
+    mutated: bool,
+    #[serde(default)]
+    plus: u32, // NEW GAME+ loop
+}
+
+fn default_name() -> String {
+    "AAA".to_string()
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum LeaderboardCategory {
+    Score,
+    Survival,
+    Team, // Team mode runs, which stay off the other boards
+    #[cfg(feature = "cabinet-sync")]
+    Cabinet, // Best scores across every game on the cabinet
+}
+
+// In the order the game over screen steps through them
+const LEADERBOARD_CATEGORIES: &[LeaderboardCategory] = &[
+    LeaderboardCategory::Score,
+    LeaderboardCategory::Survival,
+    LeaderboardCategory::Team,
+    #[cfg(feature = "cabinet-sync")]
+    LeaderboardCategory::Cabinet,
+];
+
+impl LeaderboardCategory {
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            LeaderboardCategory::Score => "TOP SCORES",
+            LeaderboardCategory::Survival => "LONGEST SURVIVAL",
+            LeaderboardCategory::Team => "TEAM SCORES",
+            #[cfg(feature = "cabinet-sync")]
+            LeaderboardCategory::Cabinet => "CABINET CHAMPIONS",
+        }
+    }
+
+    pub(crate) fn next(self) -> Self {
+        self.step(1)
+    }
+
+    pub(crate) fn previous(self) -> Self {
+        self.step(LEADERBOARD_CATEGORIES.len() - 1)
+    }
+
+    fn step(self, by: usize) -> Self {
+        let count = LEADERBOARD_CATEGORIES.len();
+        let index = LEADERBOARD_CATEGORIES
+            .iter()
+            .position(|&category| category == self)
+            .unwrap_or(0);
+        LEADERBOARD_CATEGORIES[(index + by) % count]
+    }
+}
+
+impl GameState {
+    pub(crate) fn load_leaderboard(&mut self) {
+        let Some(data) = self.store.get(LEADERBOARD_KEY) else {
+            return;
+        };
+        let entries = match serde_json::from_str::<Vec<serde_json::Value>>(&data) {
+            Ok(entries) => entries,
+            Err(err) => {
+                log(
+                    LogLevel::Warn,
+                    "storage",
+                    &format!("leaderboard unreadable: {err}"),
+                );
+                return;
+            }
+        };
+        // A damaged entry is dropped rather than losing the whole board
+        self.leaderboard = entries
+            .into_iter()
+            .filter_map(|entry| serde_json::from_value::<SavedEntry>(entry).ok())
+            .map(|saved| LeaderboardEntry {
+                score: saved.score,
+                mode: match saved.mode {
+                    0 => PlayerMode::Single,
+                    2 => PlayerMode::Team,
+                    _ => PlayerMode::Two,
+                },
+                name: saved.name,
+                time_survived_ms: saved.time,
+                mutated: saved.mutated,
+                latest: false,
+                new_game_plus: saved.plus,
+            })
+            .collect();
+    }
+
+    fn save_leaderboard(&self) {
+        let saved: Vec<SavedEntry> = self
+            .leaderboard
+            .iter()
+            .map(|entry| SavedEntry {
+                score: entry.score,
+                mode: match entry.mode {
+                    PlayerMode::Single => 0,
+                    PlayerMode::Two => 1,
+                    PlayerMode::Team => 2,
+                },
+                name: entry.name.clone(),
+                time: entry.time_survived_ms,
+                mutated: entry.mutated,
+                plus: entry.new_game_plus,
+            })
+            .collect();
+        if let Ok(json) = serde_json::to_string(&saved) {
+            self.store.set(LEADERBOARD_KEY, &json);
+        }
+    }
+
+    // Called when an achievement or a personal best changes. Saved straight
+    // away, unless the tick may yet be rolled back; then it waits for
+    // save_progress.
+    pub(crate) fn progress_changed(&mut self) {
+        self.unsaved_progress = true;
+        if !self.hold_saves {
+            self.save_progress();
+        }
+    }
+
+    pub(crate) fn save_progress(&mut self) {
+        if std::mem::take(&mut self.unsaved_progress) {
+            self.achievements.save(&*self.store);
+            self.personal_best.save(&*self.store);
+        }
+    }
+
+    // Switches to another store, e.g. once IndexedDB has opened, and picks up
+    // whatever was saved there.
+    pub(crate) fn set_store(&mut self, store: Rc<dyn KeyValueStore>) {
+        self.store = store;
+        self.settings = Settings::load(&*self.store);
+        self.unlockables = Unlockables::load(&*self.store);
+        self.personal_best = PersonalBest::load(&*self.store);
+        self.seen_how_to_play = seen_how_to_play(&*self.store);
+        self.load_leaderboard();
+        self.offer_resume();
+    }
+
+    pub(crate) fn add_to_leaderboard(
+        &mut self,
+        result: &RunResult,
+        mode: PlayerMode,
+        name: String,
+    ) {
+        let entry = LeaderboardEntry {
+            score: result.score,
+            mode,
+            name,
+            time_survived_ms: result.time_survived_ms,
+            mutated: self.mutators.any()
+                || self.settings.adaptive_difficulty
+                || self.settings.big_pieces
+                || self.lanes_active()
+                || result.handicap != Handicap::None,
+            latest: true,
+            new_game_plus: self.new_game_plus,
+        };
+        #[cfg(feature = "cabinet-sync")]
+        self.cabinet.unpublished.push(cabinet::PendingScore {
+            entry: entry.clone(),
+            seed: self.seed,
+            input_hash: self.input_hash(),
+            replay: ReplayBundle::record(self),
+        });
+        self.record_session_best(&entry);
+        self.leaderboard.push(entry);
+        // Sort descending by score
+        self.leaderboard
+            .sort_by_key(|entry| std::cmp::Reverse(entry.score));
+
+        // Keep the top entries of each board; an entry can be on more than one
+        let placed: Vec<usize> = LEADERBOARD_CATEGORIES
+            .iter()
+            .flat_map(|&category| self.ranked_indices(category))
+            .collect();
+        self.leaderboard = std::mem::take(&mut self.leaderboard)
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| placed.contains(i))
+            .map(|(_, entry)| entry)
+            .collect();
+        self.save_leaderboard();
+    }
+
+    // From the operator menu
+    pub(crate) fn wipe_leaderboard(&mut self) {
+        self.leaderboard.clear();
+        self.session_best = None;
+        self.save_leaderboard();
+        log(
+            LogLevel::Info,
+            "storage",
+            "leaderboard wiped by the operator",
+        );
+    }
+
+    pub(crate) fn ranked_leaderboard(
+        &self,
+        category: LeaderboardCategory,
+    ) -> Vec<&LeaderboardEntry> {
+        self.ranked_indices(category)
+            .into_iter()
+            .map(|i| &self.leaderboard[i])
+            .collect()
+    }
+
+    // Positions in the score-sorted leaderboard of the entries on a board,
+    // best first
+    fn ranked_indices(&self, category: LeaderboardCategory) -> Vec<usize> {
+        // The cabinet board lives on the cabinet service, not here
+        #[cfg(feature = "cabinet-sync")]
+        if category == LeaderboardCategory::Cabinet {
+            return Vec::new();
+        }
+        let team_board = category == LeaderboardCategory::Team;
+        let mut ranked: Vec<usize> = (0..self.leaderboard.len())
+            .filter(|&i| (self.leaderboard[i].mode == PlayerMode::Team) == team_board)
+            .collect();
+        if category == LeaderboardCategory::Survival {
+            ranked.sort_by(|&a, &b| {
+                self.leaderboard[b]
+                    .time_survived_ms
+                    .total_cmp(&self.leaderboard[a].time_survived_ms)
+            });
+        }
+        ranked.truncate(self.config.leaderboard_size);
+        ranked
+    }
+}
+
+impl Unlockables {
+    pub(crate) fn load(store: &dyn KeyValueStore) -> Self {
+        let ids: Vec<String> = store
+            .get(UNLOCKS_KEY)
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Unlockables {
+            unlocked: UNLOCKS
+                .iter()
+                .copied()
+                .filter(|unlock| ids.iter().any(|id| id == unlock.id()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn save(&self, store: &dyn KeyValueStore) {
+        let ids: Vec<&str> = self.unlocked.iter().map(|unlock| unlock.id()).collect();
+        if let Ok(json) = serde_json::to_string(&ids) {
+            store.set(UNLOCKS_KEY, &json);
+        }
+    }
+}
+
+impl Achievements {
+    pub(crate) fn load(store: &dyn KeyValueStore) -> Self {
+        let ids: Vec<String> = store
+            .get(ACHIEVEMENTS_KEY)
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Achievements {
+            earned: ACHIEVEMENTS
+                .iter()
+                .copied()
+                .filter(|achievement| ids.iter().any(|id| id == achievement.id()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn save(&self, store: &dyn KeyValueStore) {
+        let ids: Vec<&str> = self
+            .earned
+            .iter()
+            .map(|achievement| achievement.id())
+            .collect();
+        if let Ok(json) = serde_json::to_string(&ids) {
+            store.set(ACHIEVEMENTS_KEY, &json);
+        }
+    }
+}
+
+impl PersonalBest {
+    pub(crate) fn load(store: &dyn KeyValueStore) -> Self {
+        store
+            .get(PERSONAL_BEST_KEY)
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self, store: &dyn KeyValueStore) {
+        if let Ok(json) = serde_json::to_string(self) {
+            store.set(PERSONAL_BEST_KEY, &json);
+        }
+    }
+}
+
+impl SuspendedRun {
+    pub(crate) fn load(store: &dyn KeyValueStore) -> Option<Self> {
+        serde_json::from_str(&store.get(SUSPENDED_RUN_KEY)?).ok()
+    }
+
+    // None clears it
+    pub(crate) fn save(store: &dyn KeyValueStore, run: Option<&Self>) {
+        let json = run.and_then(|run| serde_json::to_string(run).ok());
+        store.set(SUSPENDED_RUN_KEY, json.as_deref().unwrap_or_default());
+    }
+}
+
+// Writes something and reads it back, to check saving works at all
+pub(crate) fn store_works(store: &dyn KeyValueStore) -> bool {
+    let probe = format!("{}", rand::random::<u32>());
+    store.set(PROBE_KEY, &probe);
+    store.get(PROBE_KEY).as_deref() == Some(probe.as_str())
+}
+
+// Whether the how-to-play screen has come up by itself on this machine yet
+pub(crate) fn seen_how_to_play(store: &dyn KeyValueStore) -> bool {
+    store.get(SEEN_HOW_TO_PLAY_KEY).as_deref() == Some("true")
+}
+
+pub(crate) fn mark_how_to_play_seen(store: &dyn KeyValueStore) {
+    store.set(SEEN_HOW_TO_PLAY_KEY, "true");
+}
+
+impl CrashReport {
+    pub(crate) fn save(&self, store: &dyn KeyValueStore) {
+        if let Ok(json) = serde_json::to_string(self) {
+            store.set(CRASH_KEY, &json);
+        }
+    }
+}
+
+impl Settings {
+    pub(crate) fn load(store: &dyn KeyValueStore) -> Self {
+        store
+            .get(SETTINGS_KEY)
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self, store: &dyn KeyValueStore) {
+        if let Ok(json) = serde_json::to_string(self) {
+            store.set(SETTINGS_KEY, &json);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(score: i32) -> RunResult {
+        RunResult {
+            player_index: 0,
+            score,
+            time_survived_ms: score as f64 * 100.0,
+            stats: RunStats::default(),
+            handicap: Handicap::None,
+        }
+    }
+
+    fn game(store: &Rc<MockStore>) -> GameState {
+        GameState::new(GameConfig::default(), store.clone())
+    }
+
+    fn board(state: &GameState) -> Vec<(String, i32)> {
+        state
+            .leaderboard
+            .iter()
+            .map(|entry| (entry.name.clone(), entry.score))
+            .collect()
+    }
+
+    #[test]
+    fn leaderboard_survives_a_reload() {
+        let store = Rc::new(MockStore::default());
+        let mut state = game(&store);
+        state.add_to_leaderboard(&run(500), PlayerMode::Single, "ABC".to_string());
+        state.add_to_leaderboard(&run(900), PlayerMode::Single, "XYZ".to_string());
+
+        let expected = vec![("XYZ".to_string(), 900), ("ABC".to_string(), 500)];
+        assert_eq!(board(&game(&store)), expected);
+        let writes = store.writes();
+        let last = writes.last().unwrap();
+        assert_eq!(last.key, LEADERBOARD_KEY);
+        assert!(!last.failed);
+    }
+
+    #[test]
+    fn settings_and_unlocks_survive_a_reload() {
+        let store = Rc::new(MockStore::default());
+        let settings = Settings {
+            crt_effect: true,
+            landing_shadows: false,
+            ..Settings::default()
+        };
+        settings.save(&*store);
+        let unlocks = Unlockables {
+            unlocked: vec![Unlock::Rainbow, Unlock::PracticeLives],
+        };
+        unlocks.save(&*store);
+
+        let loaded = Settings::load(&*store);
+        assert!(loaded.crt_effect);
+        assert!(!loaded.landing_shadows);
+        assert!(Unlockables::load(&*store).unlocked == unlocks.unlocked);
+    }
+
+    #[test]
+    fn failed_reads_start_from_defaults() {
+        let store = Rc::new(MockStore::default());
+        game(&store).add_to_leaderboard(&run(700), PlayerMode::Two, "BOB".to_string());
+        Settings {
+            crt_effect: true,
+            ..Settings::default()
+        }
+        .save(&*store);
+        Unlockables {
+            unlocked: vec![Unlock::BigHeads],
+        }
+        .save(&*store);
+
+        store.fail_reads.set(true);
+        assert!(game(&store).leaderboard.is_empty());
+        assert!(!Settings::load(&*store).crt_effect);
+        assert!(Unlockables::load(&*store).unlocked.is_empty());
+        assert!(!store_works(&*store));
+
+        // Nothing was lost, it just couldn't be read
+        store.fail_reads.set(false);
+        assert_eq!(board(&game(&store)), vec![("BOB".to_string(), 700)]);
+        assert!(Settings::load(&*store).crt_effect);
+    }
+
+    #[test]
+    fn failed_writes_keep_playing_but_save_nothing() {
+        let store = Rc::new(MockStore::default());
+        store.fail_writes.set(true);
+        let mut state = game(&store);
+        state.add_to_leaderboard(&run(300), PlayerMode::Single, "ANN".to_string());
+        Settings {
+            crt_effect: true,
+            ..Settings::default()
+        }
+        .save(&*store);
+        Unlockables {
+            unlocked: vec![Unlock::Rainbow],
+        }
+        .save(&*store);
+
+        // This session still has the score
+        assert_eq!(board(&state), vec![("ANN".to_string(), 300)]);
+        let writes = store.writes();
+        let keys: Vec<&str> = writes.iter().map(|write| write.key.as_str()).collect();
+        assert_eq!(keys, [LEADERBOARD_KEY, SETTINGS_KEY, UNLOCKS_KEY]);
+        assert!(writes.iter().all(|write| write.failed && write.bytes > 0));
+        assert!(!store_works(&*store));
+
+        store.fail_writes.set(false);
+        assert!(game(&store).leaderboard.is_empty());
+        assert!(!Settings::load(&*store).crt_effect);
+        assert!(Unlockables::load(&*store).unlocked.is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+// Suspend and resume: a run still going when the page unloads is saved, and
+// the next load asks whether to pick it back up, so an accidental refresh on
+// the cabinet doesn't throw away a great run.
+//
+// What's saved is the run's replay so far: the seed, what it started with and
+// every tick's inputs. Resuming plays that back through the simulation, which
+// lands on exactly the state the run was in, players, objects, difficulty and
+// rng included, without any of it having to be serialized itself. Tournament
+// runs, the attract demo and online play aren't saved.
+//
+// beforeunload can't wait for a frame, and the App is only reachable from
+// inside one, so the frame loop rebuilds the save after every frame's ticks
+// and the listener just writes out whatever it has. Run-length encoding keeps
+// the rebuild cheap.
+//
+// A resumed run plays with the settings it was saved with; the player's own
+// come back once it's over.
+
+use super::*;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SuspendedRun {
+    #[serde(flatten)]
+    run: ReplayBundle,
+    bot: Option<BotLevel>, // How good P2 was, in solo versus
+}
+
+pub(crate) const RESUME_ITEMS: [&str; 2] = ["RESUME RUN", "NEW GAME"];
+
+impl GameState {
+    fn can_suspend(&self) -> bool {
+        matches!(self.phase, GamePhase::Playing) && !self.in_demo() && self.tournament.is_none()
+    }
+
+    fn suspended_run(&self) -> SuspendedRun {
+        SuspendedRun {
+            run: ReplayBundle::record(self),
+            bot: self.bot.as_ref().map(|bot| bot.level),
+        }
+    }
+
+    // Asks about a saved run, if there is one and nothing else has started
+    pub(crate) fn offer_resume(&mut self) {
+        if matches!(self.phase, GamePhase::ModeSelect { .. })
+            && SuspendedRun::load(&*self.store).is_some()
+        {
+            self.phase = GamePhase::ResumePrompt { selection: 0 };
+        }
+    }
+
+    pub(crate) fn handle_resume_prompt(&mut self, commands: &[Command]) -> Result<(), String> {
+        let GamePhase::ResumePrompt { selection } = &mut self.phase else {
+            return Ok(());
+        };
+        move_selection(
+            selection,
+            RESUME_ITEMS.len(),
+            menu_prev(commands),
+            menu_next(commands),
+        );
+        if !commands.contains(&Command::Confirm) {
+            return Ok(());
+        }
+        let resume = *selection == 0;
+        // Either way it's only offered once
+        let saved = SuspendedRun::load(&*self.store);
+        SuspendedRun::save(&*self.store, None);
+        self.phase = GamePhase::main_menu();
+        match saved {
+            Some(saved) if resume => self.resume(saved),
+            _ => Ok(()),
+        }
+    }
+
+    fn resume(&mut self, saved: SuspendedRun) -> Result<(), String> {
+        let own_settings = self.settings;
+        let inputs = saved.run.start(self)?;
+        self.settings_after_run = Some(own_settings);
+        for (tick, &held) in inputs.iter().enumerate() {
+            if !matches!(self.phase, GamePhase::Playing) {
+                self.back_to_menu();
+                return Err(format!(
+                    "the run ended after {tick} of {} ticks",
+                    inputs.len()
+                ));
+            }
+            self.replay_tick(held);
+        }
+        if let Some(level) = saved.bot {
+            self.bot_level = level;
+            self.bot = Some(AiController::new(1, level));
+        }
+        // Catching up shouldn't leave its popups and zooms all over the screen
+        self.popups.clear();
+        self.whooshes.clear();
+        self.camera = Camera::default();
+        self.pace_marker = None;
+        self.ticker = Ticker::default();
+        self.ticker.push("RUN RESUMED".to_string());
+        Ok(())
+    }
+}
+
+// What the beforeunload listener will do: nothing, or write this save (None
+// clears it) to this store
+type PendingSave = Option<(Rc<dyn KeyValueStore>, Option<SuspendedRun>)>;
+
+pub(crate) struct RunSaver {
+    pending: Rc<RefCell<PendingSave>>,
+}
+
+impl RunSaver {
+    pub(crate) fn listen() -> Result<Self, JsValue> {
+        let pending: Rc<RefCell<PendingSave>> = Rc::new(RefCell::new(None));
+        let unloading = pending.clone();
+        let handler = Closure::wrap(Box::new(move || {
+            if let Some((store, run)) = unloading.borrow_mut().take() {
+                SuspendedRun::save(&*store, run.as_ref());
+            }
+        }) as Box<dyn FnMut()>);
+        web_sys::window()
+            .unwrap()
+            .add_event_listener_with_callback("beforeunload", handler.as_ref().unchecked_ref())?;
+        handler.forget();
+        Ok(RunSaver { pending })
+    }
+
+    // Called once a frame, after the frame's ticks
+    pub(crate) fn refresh(&self, state: &GameState) {
+        let mut pending = self.pending.borrow_mut();
+        // A save still waiting on its prompt is left alone
+        if matches!(
+            state.phase,
+            GamePhase::Loading { .. } | GamePhase::Splash { .. } | GamePhase::ResumePrompt { .. }
+        ) {
+            *pending = None;
+            return;
+        }
+        if !state.can_suspend() {
+            *pending = Some((state.store.clone(), None));
+            return;
+        }
+        *pending = Some((state.store.clone(), Some(state.suspended_run())));
+    }
+}
@@ -0,0 +1,68 @@
+// Team mode: two players on one side. Each still has their own health, but
+// every point goes to one team score and there's a single combo meter
+// between them. Running into the same hazard together is a pile-up, which
+// hurts both and costs the team points. The run ends as one result, named
+// once, on its own TEAM board.
+
+use super::*;
+
+pub(crate) const PILEUP_PENALTY: i32 = 25; // Points each player in a pile-up loses
+
+impl GameState {
+    // Everything earned so far, including by a teammate who's out
+    pub(crate) fn team_score(&self) -> i32 {
+        let living: i32 = self.players.iter().map(|slot| slot.score).sum();
+        let out: i32 = self.final_scores.iter().map(|result| result.score).sum();
+        living + out
+    }
+
+    // Whether a hazard touching more than one player is a pile-up. Bonus
+    // round stragglers are harmless, together or not.
+    pub(crate) fn is_pileup(&self, obj: &FallingObject, touching: usize) -> bool {
+        self.mode == PlayerMode::Team
+            && obj.obj_type == ObjectType::BadItem
+            && touching > 1
+            && !matches!(self.playing_phase, PlayingPhase::BonusRound { .. })
+    }
+
+    // Folds both players' results into the team's, for name entry and the
+    // game over screen
+    pub(crate) fn combine_team_results(&mut self) {
+        if self.mode != PlayerMode::Team || self.final_scores.is_empty() {
+            return;
+        }
+        let mut stats = RunStats::default();
+        for result in &self.final_scores {
+            stats.deals_caught += result.stats.deals_caught;
+            stats.deals_missed += result.stats.deals_missed;
+            stats.hits_taken += result.stats.hits_taken;
+            stats.hits_avoided += result.stats.hits_avoided;
+            stats.perfect_catches += result.stats.perfect_catches;
+        }
+        let team = RunResult {
+            player_index: 0,
+            score: self.team_score(),
+            time_survived_ms: self
+                .final_scores
+                .iter()
+                .map(|result| result.time_survived_ms)
+                .fold(0.0, f64::max),
+            stats,
+            handicap: Handicap::None,
+        };
+        self.final_scores = vec![team];
+    }
+}
+
+// The popup and ticker line for a pile-up of `players` players
+pub(crate) fn pileup_effects(obj: &FallingObject, players: usize) -> [WorldEffect; 2] {
+    [
+        WorldEffect::Popup {
+            x: obj.x + obj.width / 2.0,
+            y: obj.y,
+            text: format!("PILE-UP -{}", PILEUP_PENALTY * players as i32),
+            color: "#ff4444",
+        },
+        WorldEffect::Announce("Pile-up! Watch where you're going".to_string()),
+    ]
+}
@@ -0,0 +1,45 @@
+// HUD text that's drawn every frame but only formatted again when what it
+// shows changes. Each line keeps the values it was last built from next to
+// the string. Drawing only gets a &GameState, so the strings sit behind a
+// RefCell.
+
+use super::*;
+
+pub(crate) struct CachedText<K> {
+    built: RefCell<Option<(K, String)>>,
+}
+
+impl<K> Default for CachedText<K> {
+    fn default() -> Self {
+        CachedText {
+            built: RefCell::new(None),
+        }
+    }
+}
+
+impl<K: PartialEq> CachedText<K> {
+    // The text for `key`, formatted by `build` only if the key has changed
+    pub(crate) fn get(&self, key: K, build: impl FnOnce(&K) -> String) -> Ref<'_, str> {
+        {
+            let mut built = self.built.borrow_mut();
+            if built.as_ref().is_none_or(|(last, _)| *last != key) {
+                let text = build(&key);
+                *built = Some((key, text));
+            }
+        }
+        Ref::map(self.built.borrow(), |built| {
+            built.as_ref().map_or("", |(_, text)| text.as_str())
+        })
+    }
+}
+
+// Every HUD line, by player index where there's one per player
+#[derive(Default)]
+pub(crate) struct HudText {
+    pub(crate) team: CachedText<(i32, u32)>, // Score and combo
+    pub(crate) scores: [CachedText<(PlayerMode, bool, i32)>; 2], // Mode, whether it's the bot's, score
+    pub(crate) hearts: [CachedText<i32>; 2],                     // Only past MAX_HEART_ICONS
+    pub(crate) catch_rates: [CachedText<u32>; 2],                // Whole percent
+    pub(crate) clock: CachedText<u32>,                           // Whole seconds
+    pub(crate) wave: CachedText<u32>,
+}
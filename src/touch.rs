@@ -0,0 +1,182 @@
+// Player 1 on a touch screen: left, right and A buttons drawn over the
+// playfield, so a phone or tablet can play without the host page having to
+// lay out controls of its own. Every finger is tracked on its own, so walking
+// and pressing A at the same time works, and sliding a finger off a button
+// lets go of it.
+//
+// The buttons stay hidden until the screen is first touched, so they're never
+// in the way on a cabinet with a stick.
+
+use super::*;
+
+const BUTTON_MARGIN: f64 = 6.0;
+const BUTTON_SLOP: f64 = 8.0; // How far outside a button a touch still counts
+
+#[derive(Copy, Clone, PartialEq)]
+enum TouchButton {
+    Left,
+    Right,
+    A,
+}
+
+impl TouchButton {
+    fn label(self) -> &'static str {
+        match self {
+            TouchButton::Left => "\u{25c0}",
+            TouchButton::Right => "\u{25b6}",
+            TouchButton::A => "A",
+        }
+    }
+}
+
+// Where a button sits, as the square it's drawn in
+struct ButtonArea {
+    button: TouchButton,
+    x: f64,
+    y: f64,
+    size: f64,
+}
+
+impl ButtonArea {
+    fn contains(&self, (x, y): Point) -> bool {
+        x >= self.x - BUTTON_SLOP
+            && x <= self.x + self.size + BUTTON_SLOP
+            && y >= self.y - BUTTON_SLOP
+            && y <= self.y + self.size + BUTTON_SLOP
+    }
+}
+
+// Walking on the bottom left, A on the bottom right. A screen on its side has
+// room under the floor for bigger buttons.
+fn button_areas(world: WorldConfig) -> [ButtonArea; 3] {
+    let size = if world.width > world.height {
+        40.0
+    } else {
+        56.0
+    };
+    let y = world.height - size - BUTTON_MARGIN;
+    [
+        ButtonArea {
+            button: TouchButton::Left,
+            x: BUTTON_MARGIN,
+            y,
+            size,
+        },
+        ButtonArea {
+            button: TouchButton::Right,
+            x: BUTTON_MARGIN * 2.0 + size,
+            y,
+            size,
+        },
+        ButtonArea {
+            button: TouchButton::A,
+            x: world.width - size - BUTTON_MARGIN,
+            y,
+            size,
+        },
+    ]
+}
+
+#[derive(Clone)]
+pub(crate) struct TouchControls {
+    // Active touches by identifier, on the playfield in logical units
+    touches: Rc<RefCell<HashMap<i32, Point>>>,
+    touched: Rc<Cell<bool>>, // Whether anyone has touched the screen yet
+    viewport: Rc<Cell<Viewport>>,
+}
+
+impl TouchControls {
+    pub(crate) fn listen(
+        canvas: &HtmlCanvasElement,
+        viewport: Rc<Cell<Viewport>>,
+    ) -> Result<Self, JsValue> {
+        let controls = TouchControls {
+            touches: Rc::new(RefCell::new(HashMap::new())),
+            touched: Rc::new(Cell::new(false)),
+            viewport,
+        };
+
+        for event_name in ["touchstart", "touchmove", "touchend", "touchcancel"] {
+            let controls = controls.clone();
+            let target = canvas.clone();
+            let held = event_name == "touchstart" || event_name == "touchmove";
+            let handler = Closure::wrap(Box::new(move |event: TouchEvent| {
+                let rect = target.get_bounding_client_rect();
+                let mut touches = controls.touches.borrow_mut();
+                let changed = event.changed_touches();
+                for i in 0..changed.length() {
+                    let Some(touch) = changed.get(i) else {
+                        continue;
+                    };
+                    if held {
+                        let point = controls.viewport.get().logical_point(
+                            touch.client_x() as f64 - rect.left(),
+                            touch.client_y() as f64 - rect.top(),
+                        );
+                        touches.insert(touch.identifier(), point);
+                    } else {
+                        touches.remove(&touch.identifier());
+                    }
+                }
+                controls.touched.set(true);
+                // Keep the browser from scrolling or synthesizing mouse clicks
+                event.prevent_default();
+            }) as Box<dyn FnMut(_)>);
+            canvas
+                .add_event_listener_with_callback(event_name, handler.as_ref().unchecked_ref())?;
+            handler.forget();
+        }
+
+        Ok(controls)
+    }
+
+    fn held(&self) -> Vec<TouchButton> {
+        let areas = button_areas(self.viewport.get().world());
+        let touches = self.touches.borrow();
+        areas
+            .iter()
+            .filter(|area| touches.values().any(|&point| area.contains(point)))
+            .map(|area| area.button)
+            .collect()
+    }
+
+    // Drawn over everything else, in logical units
+    pub(crate) fn draw(&self, ctx: &dyn Renderer, world: WorldConfig) {
+        if !self.touched.get() {
+            return;
+        }
+        let held = self.held();
+        for area in button_areas(world) {
+            let radius = area.size / 2.0;
+            let (cx, cy) = (area.x + radius, area.y + radius);
+            let pressed = held.contains(&area.button);
+            ctx.set_alpha(if pressed { 0.45 } else { 0.2 });
+            ctx.circle(cx, cy, radius, "#fff");
+            ctx.set_alpha(0.6);
+            ctx.ring(cx, cy, radius, "#fff", 1.5);
+            ctx.text(
+                area.button.label(),
+                cx,
+                cy + radius * 0.25,
+                (radius * 0.7).round(),
+                "#fff",
+                Align::Center,
+            );
+        }
+        ctx.set_alpha(1.0);
+    }
+}
+
+impl InputSource for TouchControls {
+    fn poll(&self) -> InputSnapshot {
+        let mut snapshot = InputSnapshot::default();
+        for button in self.held() {
+            match button {
+                TouchButton::Left => snapshot.player1_left = true,
+                TouchButton::Right => snapshot.player1_right = true,
+                TouchButton::A => snapshot.player1_a = true,
+            }
+        }
+        snapshot
+    }
+}
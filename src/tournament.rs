@@ -0,0 +1,315 @@
+// Best-of-N series. Two players can go head to head in versus rounds, or two
+// to four can take turns on P1's controls, each playing a solo run per
+// round. The highest score takes a round, a tie gives it to nobody, and the
+// first to win a majority of the N rounds is champion. Between runs the
+// series screen shows every round so far and the last run's summary.
+//
+// The menu's alternating 2P mode is a one-round series of turns that plays
+// like a regular game: each player enters a name when their run ends, the
+// next turn starts straight after, and the comparison ends on the usual game
+// over screen.
+
+use super::*;
+
+const MAX_ENTRANTS: usize = 4;
+const SERIES_LENGTHS: [u32; 4] = [1, 3, 5, 7];
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum TournamentFormat {
+    Versus, // Both players at once, each round one 2P game
+    Turns,  // One solo run per entrant per round, all on P1's controls
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum TournamentItem {
+    Format,
+    Entrants,
+    Rounds,
+    Start,
+    Back,
+}
+
+pub(crate) const TOURNAMENT_ITEMS: [TournamentItem; 5] = [
+    TournamentItem::Format,
+    TournamentItem::Entrants,
+    TournamentItem::Rounds,
+    TournamentItem::Start,
+    TournamentItem::Back,
+];
+
+// What the setup screen picks
+#[derive(Copy, Clone)]
+pub(crate) struct TournamentSetup {
+    pub(crate) format: TournamentFormat,
+    pub(crate) entrants: usize,
+    pub(crate) rounds: u32, // Best of
+}
+
+impl Default for TournamentSetup {
+    fn default() -> Self {
+        TournamentSetup {
+            format: TournamentFormat::Versus,
+            entrants: 2,
+            rounds: 3,
+        }
+    }
+}
+
+impl TournamentSetup {
+    pub(crate) fn label(&self, item: TournamentItem) -> String {
+        match item {
+            TournamentItem::Format => match self.format {
+                TournamentFormat::Versus => "Format: Versus".to_string(),
+                TournamentFormat::Turns => "Format: Take turns".to_string(),
+            },
+            TournamentItem::Entrants => format!("Players: {}", self.entrants),
+            TournamentItem::Rounds => format!("Best of {}", self.rounds),
+            TournamentItem::Start => "Start".to_string(),
+            TournamentItem::Back => "Back".to_string(),
+        }
+    }
+}
+
+pub(crate) struct Tournament {
+    pub(crate) setup: TournamentSetup,
+    pub(crate) rounds: Vec<Vec<Option<RunResult>>>, // Per round, per entrant
+    pub(crate) turn: usize,                         // Entrant up next when taking turns
+    pub(crate) round_seed: u64,                     // Every turn in a round shops the same aisles
+    pub(crate) ranked: bool,                        // Alternating 2P: scores go on the leaderboard
+}
+
+impl Tournament {
+    fn round_complete(round: &[Option<RunResult>]) -> bool {
+        round.iter().all(Option::is_some)
+    }
+
+    // Who took the round, if it's over and nobody tied for the top score
+    pub(crate) fn round_winner(round: &[Option<RunResult>]) -> Option<usize> {
+        if !Tournament::round_complete(round) {
+            return None;
+        }
+        let scores: Vec<i32> = round.iter().flatten().map(|result| result.score).collect();
+        sole_leader(&scores)
+    }
+
+    pub(crate) fn wins(&self) -> Vec<u32> {
+        let mut wins = vec![0; self.setup.entrants];
+        for round in &self.rounds {
+            if let Some(winner) = Tournament::round_winner(round) {
+                wins[winner] += 1;
+            }
+        }
+        wins
+    }
+
+    // A majority of the rounds wins outright. Once all of them are played,
+    // ties included, the sole leader in wins does too; otherwise play goes on
+    // a round at a time.
+    pub(crate) fn champion(&self) -> Option<usize> {
+        let wins = self.wins();
+        let needed = self.setup.rounds / 2 + 1;
+        if let Some(winner) = wins.iter().position(|&count| count >= needed) {
+            return Some(winner);
+        }
+        let played = self
+            .rounds
+            .iter()
+            .filter(|round| Tournament::round_complete(round))
+            .count() as u32;
+        if played < self.setup.rounds {
+            return None;
+        }
+        sole_leader(&wins)
+    }
+
+    // An alternating game is over after its one round, tie or not
+    pub(crate) fn over(&self) -> bool {
+        self.champion().is_some()
+            || (self.ranked
+                && self
+                    .rounds
+                    .last()
+                    .is_some_and(|round| Tournament::round_complete(round)))
+    }
+}
+
+// Index of the one highest value, or None if it's shared
+fn sole_leader<T: Ord + Copy>(values: &[T]) -> Option<usize> {
+    let best = *values.iter().max()?;
+    let mut leaders = values
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| **value == best);
+    let (leader, _) = leaders.next()?;
+    leaders.next().is_none().then_some(leader)
+}
+
+impl GameState {
+    pub(crate) fn open_tournament(&mut self) {
+        self.phase = GamePhase::TournamentSetup {
+            selection: 0,
+            setup: TournamentSetup::default(),
+        };
+    }
+
+    pub(crate) fn handle_tournament_setup(&mut self, commands: &[Command]) {
+        let GamePhase::TournamentSetup { selection, setup } = &mut self.phase else {
+            return;
+        };
+        move_selection(
+            selection,
+            TOURNAMENT_ITEMS.len(),
+            menu_prev(commands),
+            menu_next(commands),
+        );
+        if !commands.contains(&Command::Confirm) {
+            return;
+        }
+        match TOURNAMENT_ITEMS[*selection] {
+            TournamentItem::Format => {
+                setup.format = match setup.format {
+                    TournamentFormat::Versus => TournamentFormat::Turns,
+                    TournamentFormat::Turns => TournamentFormat::Versus,
+                };
+                // The cabinet has two sets of controls
+                if setup.format == TournamentFormat::Versus {
+                    setup.entrants = 2;
+                }
+            }
+            TournamentItem::Entrants => {
+                if setup.format == TournamentFormat::Turns {
+                    setup.entrants = setup.entrants % MAX_ENTRANTS + 1;
+                    setup.entrants = setup.entrants.max(2);
+                }
+            }
+            TournamentItem::Rounds => {
+                let next = SERIES_LENGTHS
+                    .iter()
+                    .position(|&rounds| rounds == setup.rounds)
+                    .map_or(0, |i| (i + 1) % SERIES_LENGTHS.len());
+                setup.rounds = SERIES_LENGTHS[next];
+            }
+            TournamentItem::Start => {
+                self.tournament = Some(Tournament {
+                    setup: *setup,
+                    rounds: Vec::new(),
+                    turn: 0,
+                    round_seed: 0,
+                    ranked: false,
+                });
+                self.start_tournament_run();
+            }
+            TournamentItem::Back => self.phase = GamePhase::main_menu(),
+        }
+    }
+
+    // Two players on one set of controls, P1 first
+    pub(crate) fn start_alternating(&mut self) {
+        self.tournament = Some(Tournament {
+            setup: TournamentSetup {
+                format: TournamentFormat::Turns,
+                entrants: 2,
+                rounds: 1,
+            },
+            rounds: Vec::new(),
+            turn: 0,
+            round_seed: 0,
+            ranked: true,
+        });
+        self.start_tournament_run();
+    }
+
+    fn start_tournament_run(&mut self) {
+        let Some(tournament) = &mut self.tournament else {
+            return;
+        };
+        if tournament
+            .rounds
+            .last()
+            .is_none_or(|round| Tournament::round_complete(round))
+        {
+            tournament
+                .rounds
+                .push(vec![None; tournament.setup.entrants]);
+            tournament.round_seed = rand::random();
+        }
+        let round = tournament.rounds.len();
+        let turn = tournament.turn;
+        match tournament.setup.format {
+            TournamentFormat::Versus => {
+                self.start_new_game(PlayerMode::Two);
+                self.ticker.push(format!("Round {round} - fight!"));
+            }
+            TournamentFormat::Turns => {
+                let seed = tournament.round_seed;
+                self.start_seeded_game(PlayerMode::Single, seed);
+                self.ticker
+                    .push(format!("Round {round} - P{} is shopping", turn + 1));
+            }
+        }
+    }
+
+    // A turn is played as P1; the summary and name entry should show whose
+    // turn it was
+    pub(crate) fn credit_turn(&mut self) {
+        let Some(tournament) = &self.tournament else {
+            return;
+        };
+        if tournament.setup.format == TournamentFormat::Turns {
+            for result in &mut self.final_scores {
+                result.player_index = tournament.turn;
+            }
+        }
+    }
+
+    // Records the run that just ended and moves on to the series screen, or
+    // straight to the next turn of an alternating game. Only alternating
+    // scores also go on the leaderboard.
+    pub(crate) fn finish_tournament_run(&mut self) {
+        let Some(tournament) = &mut self.tournament else {
+            return;
+        };
+        let Some(round) = tournament.rounds.last_mut() else {
+            return;
+        };
+        match tournament.setup.format {
+            TournamentFormat::Versus => {
+                for result in &self.final_scores {
+                    round[result.player_index] = Some(result.clone());
+                }
+            }
+            TournamentFormat::Turns => {
+                round[tournament.turn] = self.final_scores.first().cloned();
+                tournament.turn = (tournament.turn + 1) % tournament.setup.entrants;
+            }
+        }
+        if tournament.ranked {
+            if !Tournament::round_complete(round) {
+                self.start_tournament_run();
+                return;
+            }
+            // Both runs, for the comparison and the game over screen after it
+            self.final_scores = round.iter().flatten().cloned().collect();
+        }
+        self.phase = GamePhase::Series;
+    }
+
+    pub(crate) fn handle_series(&mut self, commands: &[Command]) {
+        if !commands.contains(&Command::Confirm) {
+            return;
+        }
+        let Some(tournament) = &self.tournament else {
+            return;
+        };
+        match (tournament.over(), tournament.ranked) {
+            (true, true) => {
+                self.tournament = None;
+                self.phase = GamePhase::GameOver {
+                    tab: LeaderboardCategory::Score,
+                };
+            }
+            (true, false) => self.back_to_menu(),
+            (false, _) => self.start_tournament_run(),
+        }
+    }
+}
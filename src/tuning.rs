@@ -0,0 +1,190 @@
+// Live tuning panel, built with `--features tuning-panel`. F9 shows it; while
+// it's open I/K pick a value, J/L nudge it, Backspace restores the default and
+// Enter logs the whole set to the browser console so values that play well
+// can be copied back into the constants.
+
+use super::*;
+
+struct Row {
+    label: &'static str,
+    step: f64,
+    min: f64,
+    value: fn(&mut Tunables) -> &mut f64,
+}
+
+const ROWS: [Row; 7] = [
+    Row {
+        label: "player speed",
+        step: 0.25,
+        min: 0.25,
+        value: |t| &mut t.player_speed,
+    },
+    Row {
+        label: "player size",
+        step: 0.1,
+        min: 0.2,
+        value: |t| &mut t.player_scale,
+    },
+    Row {
+        label: "fall speed",
+        step: 0.1,
+        min: 0.1,
+        value: |t| &mut t.fall_speed,
+    },
+    Row {
+        label: "spawn interval",
+        step: 5.0,
+        min: 5.0,
+        value: |t| &mut t.spawn_interval,
+    },
+    Row {
+        label: "min interval",
+        step: 1.0,
+        min: 1.0,
+        value: |t| &mut t.min_spawn_interval,
+    },
+    Row {
+        label: "difficulty/wave",
+        step: 0.05,
+        min: 0.0,
+        value: |t| &mut t.difficulty_per_wave,
+    },
+    Row {
+        label: "deal weight",
+        step: 0.1,
+        min: 0.0,
+        value: |t| &mut t.deal_weight,
+    },
+];
+
+#[derive(Copy, Clone)]
+enum TuningCommand {
+    Select(i32),
+    Nudge(f64), // Steps, signed
+    Reset,
+    Log,
+}
+
+#[derive(Default)]
+pub(crate) struct TuningPanel {
+    open: bool,
+    selection: usize, // Index into ROWS
+    commands: Vec<TuningCommand>,
+}
+
+pub(crate) fn setup_listeners() -> Result<Rc<RefCell<TuningPanel>>, JsValue> {
+    let panel = Rc::new(RefCell::new(TuningPanel::default()));
+    let window = web_sys::window().unwrap();
+
+    let state = panel.clone();
+    let keydown = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        let mut panel = state.borrow_mut();
+        let code = event.code();
+        if code == "F9" {
+            panel.open = !panel.open;
+            event.prevent_default();
+            return;
+        }
+        if !panel.open {
+            return;
+        }
+        let command = match code.as_str() {
+            "KeyI" => TuningCommand::Select(-1),
+            "KeyK" => TuningCommand::Select(1),
+            "KeyJ" => TuningCommand::Nudge(-1.0),
+            "KeyL" => TuningCommand::Nudge(1.0),
+            "Backspace" => TuningCommand::Reset,
+            "Enter" => TuningCommand::Log,
+            _ => return,
+        };
+        panel.commands.push(command);
+        event.prevent_default();
+    }) as Box<dyn FnMut(_)>);
+    window.add_event_listener_with_callback("keydown", keydown.as_ref().unchecked_ref())?;
+    keydown.forget();
+
+    Ok(panel)
+}
+
+pub(crate) fn run_queued(panel: &RefCell<TuningPanel>, state: &mut GameState) {
+    let mut panel = panel.borrow_mut();
+    for command in std::mem::take(&mut panel.commands) {
+        let row = &ROWS[panel.selection];
+        match command {
+            TuningCommand::Select(step) => {
+                move_selection(&mut panel.selection, ROWS.len(), step < 0, step > 0);
+                continue;
+            }
+            TuningCommand::Nudge(steps) => {
+                let value = (row.value)(&mut state.tunables);
+                *value = (*value + steps * row.step).max(row.min);
+            }
+            TuningCommand::Reset => {
+                let default = *(row.value)(&mut Tunables::default());
+                *(row.value)(&mut state.tunables) = default;
+            }
+            TuningCommand::Log => {
+                log(LogLevel::Info, "tuning", &describe(&state.tunables));
+                continue;
+            }
+        }
+        resize_players(state);
+    }
+}
+
+// Players already on the floor pick up a size change straight away
+fn resize_players(state: &mut GameState) {
+    let scale = state.player_size_scale();
+    for slot in &mut state.players {
+        let player = &mut slot.player;
+        let center = player.x + player.width / 2.0;
+        player.width = PLAYER_WIDTH * scale;
+        player.height = PLAYER_HEIGHT * scale;
+        player.x = center - player.width / 2.0;
+    }
+    state.keep_players_in_view();
+}
+
+fn describe(tunables: &Tunables) -> String {
+    let mut tunables = tunables.clone();
+    let values: Vec<String> = ROWS
+        .iter()
+        .map(|row| format!("{} = {:.2}", row.label, (row.value)(&mut tunables)))
+        .collect();
+    format!("tunables: {}", values.join(", "))
+}
+
+pub(crate) fn draw(ctx: &dyn Renderer, panel: &TuningPanel, state: &GameState) {
+    if !panel.open {
+        return;
+    }
+    let mut tunables = state.tunables.clone();
+    let mut defaults = Tunables::default();
+    let left = state.world.width - 150.0;
+    let top = 30.0;
+
+    let height = 18.0 + ROWS.len() as f64 * 10.0;
+    ctx.rect(left, top, 146.0, height, "rgba(0, 0, 0, 0.75)");
+    let hint = "I/K pick  J/L adjust";
+    ctx.text(hint, left + 4.0, top + 10.0, 8.0, "#888", Align::Left);
+
+    for (i, row) in ROWS.iter().enumerate() {
+        let value = *(row.value)(&mut tunables);
+        let changed = value != *(row.value)(&mut defaults);
+        let color = if i == panel.selection {
+            "#0ff"
+        } else if changed {
+            "#ff0"
+        } else {
+            "#ccc"
+        };
+        ctx.text(
+            &format!("{:<16}{value:>6.2}", row.label),
+            left + 4.0,
+            top + 22.0 + i as f64 * 10.0,
+            8.0,
+            color,
+            Align::Left,
+        );
+    }
+}
@@ -0,0 +1,519 @@
+// Menus and the screens around a run: mode select, settings, mutators,
+// cheat codes entered on the menu, and name entry.
+
+use super::*;
+
+const UNLOCK_FLASH_FRAMES: u32 = 180;
+pub(crate) const NAME_LENGTH: usize = 3;
+
+// What each letter of a name can be, in the order up/down steps through them
+const NAME_CHARS: [char; 38] = [
+    'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S',
+    'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ' ',
+    '\u{2665}',
+];
+
+fn blank_name() -> Vec<char> {
+    vec![NAME_CHARS[0]; NAME_LENGTH]
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum MenuItem {
+    Play(PlayerMode),
+    VersusBot, // 1P against the computer
+    Alternate, // 2P taking turns on P1's controls
+    Tournament,
+    Mutators,
+    Settings,
+    HowToPlay,
+    Credits,
+    #[cfg(feature = "editor")]
+    Editor,
+}
+
+pub(crate) const MENU_ITEMS: &[MenuItem] = &[
+    MenuItem::Play(PlayerMode::Single),
+    MenuItem::Play(PlayerMode::Two),
+    MenuItem::VersusBot,
+    MenuItem::Play(PlayerMode::Team),
+    MenuItem::Alternate,
+    MenuItem::Tournament,
+    MenuItem::Mutators,
+    MenuItem::Settings,
+    MenuItem::HowToPlay,
+    MenuItem::Credits,
+    #[cfg(feature = "editor")]
+    MenuItem::Editor,
+];
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum MutatorItem {
+    DoubleSpeed,
+    NoComboReset,
+    TinyPlayers,
+    DodgeMode,
+    SplitFloor,
+    BigHeads,
+    Rainbow,
+    PracticeLives,
+    Back,
+}
+
+impl MutatorItem {
+    // Which cheat code has to be entered before this item is listed
+    fn unlock(self) -> Option<Unlock> {
+        match self {
+            MutatorItem::BigHeads => Some(Unlock::BigHeads),
+            MutatorItem::Rainbow => Some(Unlock::Rainbow),
+            MutatorItem::PracticeLives => Some(Unlock::PracticeLives),
+            _ => None,
+        }
+    }
+}
+
+const MUTATOR_ITEMS: [MutatorItem; 9] = [
+    MutatorItem::DoubleSpeed,
+    MutatorItem::NoComboReset,
+    MutatorItem::TinyPlayers,
+    MutatorItem::DodgeMode,
+    MutatorItem::SplitFloor,
+    MutatorItem::BigHeads,
+    MutatorItem::Rainbow,
+    MutatorItem::PracticeLives,
+    MutatorItem::Back,
+];
+
+// Goofy modes hidden behind menu cheat codes. Once entered they stay
+// unlocked and show up as extra toggles on the mutators screen.
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum Unlock {
+    BigHeads,
+    Rainbow,
+    PracticeLives,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum CheatInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    A,
+}
+
+pub(crate) const UNLOCKS: [Unlock; 3] = [Unlock::BigHeads, Unlock::Rainbow, Unlock::PracticeLives];
+
+impl Unlock {
+    pub(crate) fn id(self) -> &'static str {
+        match self {
+            Unlock::BigHeads => "bigHeads",
+            Unlock::Rainbow => "rainbow",
+            Unlock::PracticeLives => "practiceLives",
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Unlock::BigHeads => "BIG HEADS",
+            Unlock::Rainbow => "RAINBOW",
+            Unlock::PracticeLives => "30 LIVES PRACTICE",
+        }
+    }
+
+    fn code(self) -> &'static [CheatInput] {
+        use CheatInput::*;
+        match self {
+            Unlock::BigHeads => &[Up, Down, Up, Down, Up, Down, A],
+            Unlock::Rainbow => &[Left, Right, Left, Right, Left, Right, A],
+            // The classic, minus the B the cabinet doesn't have
+            Unlock::PracticeLives => &[Up, Up, Down, Down, Left, Right, Left, Right, A],
+        }
+    }
+}
+
+// Watches the menu's cursor and confirm presses and reports when the most
+// recent ones spell out a code.
+#[derive(Default)]
+pub(crate) struct CheatDetector {
+    recent: VecDeque<CheatInput>,
+}
+
+impl CheatDetector {
+    fn observe(&mut self, commands: &[Command]) -> Option<Unlock> {
+        let longest = UNLOCKS.iter().map(|u| u.code().len()).max().unwrap_or(0);
+        let mut found = None;
+        for command in commands {
+            let input = match command {
+                Command::CursorUp => CheatInput::Up,
+                Command::CursorDown => CheatInput::Down,
+                Command::CursorLeft => CheatInput::Left,
+                Command::CursorRight => CheatInput::Right,
+                Command::Confirm => CheatInput::A,
+                _ => continue,
+            };
+            self.recent.push_back(input);
+            if self.recent.len() > longest {
+                self.recent.pop_front();
+            }
+            for unlock in UNLOCKS {
+                let code = unlock.code();
+                if self.recent.len() >= code.len()
+                    && self
+                        .recent
+                        .iter()
+                        .skip(self.recent.len() - code.len())
+                        .eq(code)
+                {
+                    self.recent.clear();
+                    found = Some(unlock);
+                }
+            }
+        }
+        found
+    }
+}
+
+// Unlocked cheat modes, persisted on their own so clearing the leaderboard
+// doesn't take them away.
+#[derive(Default)]
+pub(crate) struct Unlockables {
+    pub(crate) unlocked: Vec<Unlock>,
+}
+
+impl Unlockables {
+    fn has(&self, unlock: Unlock) -> bool {
+        self.unlocked.contains(&unlock)
+    }
+
+    fn unlock(&mut self, unlock: Unlock, store: &dyn KeyValueStore) {
+        if !self.has(unlock) {
+            self.unlocked.push(unlock);
+            self.save(store);
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum SettingsItem {
+    LandingShadows,
+    AdaptiveDifficulty,
+    BigPieces,
+    ReduceMotion,
+    SpawnLanes,
+    TournamentEmotes,
+    PixelSnap,
+    CrtEffect,
+    Back,
+}
+
+pub(crate) const SETTINGS_ITEMS: [SettingsItem; 9] = [
+    SettingsItem::LandingShadows,
+    SettingsItem::AdaptiveDifficulty,
+    SettingsItem::BigPieces,
+    SettingsItem::ReduceMotion,
+    SettingsItem::SpawnLanes,
+    SettingsItem::TournamentEmotes,
+    SettingsItem::PixelSnap,
+    SettingsItem::CrtEffect,
+    SettingsItem::Back,
+];
+
+// Player-facing options, persisted separately from the leaderboard.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct Settings {
+    pub(crate) landing_shadows: bool,
+    pub(crate) adaptive_difficulty: bool, // Runs with this on are flagged like mutated runs
+    pub(crate) big_pieces: bool, // Bigger players and objects, for small or far-off screens
+    pub(crate) reduce_motion: bool, // No camera zooms
+    pub(crate) spawn_lanes: bool, // 2P drops take turns between halves; flagged like mutated runs
+    pub(crate) tournament_emotes: bool, // Off keeps emotes out of tournament runs
+    pub(crate) pixel_snap: bool, // Whole pixels and no image smoothing, instead of drawing between ticks
+    pub(crate) crt_effect: bool, // Scanlines, curvature and a vignette over every frame
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            landing_shadows: true,
+            adaptive_difficulty: false,
+            big_pieces: false,
+            reduce_motion: false,
+            spawn_lanes: false,
+            tournament_emotes: true,
+            pixel_snap: false,
+            crt_effect: false,
+        }
+    }
+}
+
+impl Settings {
+    pub(crate) fn label(&self, item: SettingsItem) -> String {
+        let on_off = |flag: bool| if flag { "ON" } else { "OFF" };
+        match item {
+            SettingsItem::LandingShadows => {
+                format!("Landing shadows: {}", on_off(self.landing_shadows))
+            }
+            SettingsItem::AdaptiveDifficulty => {
+                format!("Adaptive difficulty: {}", on_off(self.adaptive_difficulty))
+            }
+            SettingsItem::BigPieces => format!("Big pieces: {}", on_off(self.big_pieces)),
+            SettingsItem::ReduceMotion => {
+                format!("Reduce motion: {}", on_off(self.reduce_motion))
+            }
+            SettingsItem::SpawnLanes => format!("2P spawn lanes: {}", on_off(self.spawn_lanes)),
+            SettingsItem::TournamentEmotes => {
+                format!("Emotes in tournaments: {}", on_off(self.tournament_emotes))
+            }
+            SettingsItem::PixelSnap => {
+                let look = if self.pixel_snap { "CRISP" } else { "SMOOTH" };
+                format!("Pixels: {look}")
+            }
+            SettingsItem::CrtEffect => format!("CRT effect: {}", on_off(self.crt_effect)),
+            SettingsItem::Back => "Back".to_string(),
+        }
+    }
+}
+
+impl GamePhase {
+    pub(crate) fn main_menu() -> Self {
+        GamePhase::ModeSelect {
+            selection: 0,
+            cheats: CheatDetector::default(),
+            unlock_flash: None,
+            idle_frames: 0,
+            service_held_ms: 0.0,
+            backdrop: MenuBackdrop::default(),
+        }
+    }
+}
+
+impl GameState {
+    pub(crate) fn back_to_menu(&mut self) {
+        self.reset_runtime();
+        self.players.clear();
+        self.tournament = None;
+        self.bot = None;
+        self.new_game_plus = 0;
+        self.phase = GamePhase::main_menu();
+        self.load_leaderboard(); // Refresh leaderboard when returning to menu
+    }
+
+    pub(crate) fn open_settings(&mut self) {
+        self.phase = GamePhase::Settings { selection: 0 };
+    }
+
+    pub(crate) fn handle_settings(&mut self, commands: &[Command]) {
+        let GamePhase::Settings { selection } = &mut self.phase else {
+            return;
+        };
+        move_selection(
+            selection,
+            SETTINGS_ITEMS.len(),
+            menu_prev(commands),
+            menu_next(commands),
+        );
+        if commands.contains(&Command::Confirm) {
+            match SETTINGS_ITEMS[*selection] {
+                SettingsItem::LandingShadows => {
+                    self.settings.landing_shadows = !self.settings.landing_shadows;
+                }
+                SettingsItem::AdaptiveDifficulty => {
+                    self.settings.adaptive_difficulty = !self.settings.adaptive_difficulty;
+                }
+                SettingsItem::BigPieces => {
+                    self.settings.big_pieces = !self.settings.big_pieces;
+                }
+                SettingsItem::ReduceMotion => {
+                    self.settings.reduce_motion = !self.settings.reduce_motion;
+                }
+                SettingsItem::SpawnLanes => {
+                    self.settings.spawn_lanes = !self.settings.spawn_lanes;
+                }
+                SettingsItem::TournamentEmotes => {
+                    self.settings.tournament_emotes = !self.settings.tournament_emotes;
+                }
+                SettingsItem::PixelSnap => {
+                    self.settings.pixel_snap = !self.settings.pixel_snap;
+                }
+                SettingsItem::CrtEffect => {
+                    self.settings.crt_effect = !self.settings.crt_effect;
+                }
+                SettingsItem::Back => {
+                    self.settings.save(&*self.store);
+                    self.phase = GamePhase::main_menu();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn open_mutators(&mut self) {
+        self.phase = GamePhase::Mutators { selection: 0 };
+    }
+
+    // Mutator rows in menu order, leaving out cheat modes not yet unlocked
+    pub(crate) fn mutator_items(&self) -> Vec<MutatorItem> {
+        MUTATOR_ITEMS
+            .iter()
+            .copied()
+            .filter(|item| item.unlock().is_none_or(|u| self.unlockables.has(u)))
+            .collect()
+    }
+
+    pub(crate) fn handle_mutators(&mut self, commands: &[Command]) {
+        let items = self.mutator_items();
+        let GamePhase::Mutators { selection } = &mut self.phase else {
+            return;
+        };
+        move_selection(
+            selection,
+            items.len(),
+            menu_prev(commands),
+            menu_next(commands),
+        );
+        if commands.contains(&Command::Confirm) {
+            let mutators = &mut self.mutators;
+            match items[*selection] {
+                MutatorItem::DoubleSpeed => mutators.double_speed = !mutators.double_speed,
+                MutatorItem::NoComboReset => mutators.no_combo_reset = !mutators.no_combo_reset,
+                MutatorItem::TinyPlayers => mutators.tiny_players = !mutators.tiny_players,
+                MutatorItem::DodgeMode => mutators.dodge_mode = !mutators.dodge_mode,
+                MutatorItem::SplitFloor => mutators.split_floor = !mutators.split_floor,
+                MutatorItem::BigHeads => mutators.big_heads = !mutators.big_heads,
+                MutatorItem::Rainbow => mutators.rainbow = !mutators.rainbow,
+                MutatorItem::PracticeLives => mutators.practice_lives = !mutators.practice_lives,
+                MutatorItem::Back => self.phase = GamePhase::main_menu(),
+            }
+        }
+    }
+
+    // Feeds menu input to the cheat detector. Returns true when a code was
+    // just completed, so its final A press doesn't also pick a menu item.
+    pub(crate) fn check_cheat_codes(&mut self, commands: &[Command]) -> bool {
+        let GamePhase::ModeSelect {
+            cheats,
+            unlock_flash,
+            ..
+        } = &mut self.phase
+        else {
+            return false;
+        };
+        if let Some((_, frames)) = unlock_flash {
+            *frames = frames.saturating_sub(1);
+            if *frames == 0 {
+                *unlock_flash = None;
+            }
+        }
+        match cheats.observe(commands) {
+            Some(unlock) => {
+                *unlock_flash = Some((unlock, UNLOCK_FLASH_FRAMES));
+                self.unlockables.unlock(unlock, &*self.store);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn start_name_entry(&mut self) {
+        // The demo's runs aren't anyone's to keep
+        if self.in_demo() {
+            self.back_to_menu();
+            return;
+        }
+        // Tournament runs are scored by the series instead. Alternating
+        // turns are named like any run and move on once they are.
+        if let Some(tournament) = &self.tournament {
+            let ranked = tournament.ranked;
+            self.credit_turn();
+            if !ranked {
+                self.finish_tournament_run();
+                return;
+            }
+        }
+        self.combine_team_results();
+        // Collect all scores that need names; the bot's doesn't
+        let queue: Vec<RunResult> = self
+            .final_scores
+            .iter()
+            .filter(|result| !self.is_bot(result.player_index))
+            .cloned()
+            .collect();
+        if queue.is_empty() {
+            // No scores to save, go straight to game over
+            self.show_game_over();
+            return;
+        }
+        self.phase = GamePhase::NameEntry {
+            name: blank_name(),
+            cursor: 0,
+            queue,
+        };
+    }
+
+    pub(crate) fn handle_name_entry(&mut self, commands: &[Command]) {
+        let GamePhase::NameEntry {
+            name,
+            cursor,
+            queue,
+        } = &mut self.phase
+        else {
+            return;
+        };
+
+        name.resize(NAME_LENGTH, NAME_CHARS[0]);
+        let issued = |command| commands.contains(&command);
+
+        // Up and down step the letter under the cursor through NAME_CHARS
+        let letter = &mut name[*cursor];
+        let index = NAME_CHARS.iter().position(|c| c == letter).unwrap_or(0);
+        if issued(Command::CursorUp) {
+            *letter = NAME_CHARS[(index + NAME_CHARS.len() - 1) % NAME_CHARS.len()];
+        }
+        if issued(Command::CursorDown) {
+            *letter = NAME_CHARS[(index + 1) % NAME_CHARS.len()];
+        }
+
+        // Handle position changes (left/right)
+        if issued(Command::CursorLeft) {
+            *cursor = cursor.saturating_sub(1);
+        }
+        if issued(Command::CursorRight) {
+            *cursor = (*cursor + 1).min(NAME_LENGTH - 1);
+        }
+
+        // Confirm name
+        if issued(Command::Confirm) && !queue.is_empty() {
+            let result = queue.remove(0);
+            let entered: String = std::mem::replace(name, blank_name()).into_iter().collect();
+            *cursor = 0;
+            let finished = queue.is_empty();
+            // The QR code follows the best score named this game
+            let best_yet = self
+                .share_code
+                .as_ref()
+                .is_none_or(|share| result.score > share.score);
+            if let Some(url) = self.config.share_url.as_ref().filter(|_| best_yet)
+                && let Some(share) = ShareCode::new(url, &result, self.seed, &entered)
+            {
+                self.share_code = Some(share);
+            }
+
+            self.add_to_leaderboard(&result, self.mode, entered);
+            if finished && self.tournament.is_some() {
+                self.finish_tournament_run();
+            } else if finished {
+                self.show_game_over();
+            }
+        }
+    }
+}
+
+// Steps a list cursor by one, stopping at either end.
+pub(crate) fn move_selection(selection: &mut usize, len: usize, prev: bool, next: bool) {
+    if prev && *selection > 0 {
+        *selection -= 1;
+    }
+    if next && *selection + 1 < len {
+        *selection += 1;
+    }
+}
@@ -0,0 +1,158 @@
+// Fitting the playfield to the page. The game simulates and draws in logical
+// units, the size of the GameState's world, and never sees a pixel: the canvas
+// fills the window at the screen's full resolution, and each frame's drawing
+// goes through a view transform that scales the playfield up as far as it
+// fits whole and centers it, with black bars over the rest. Resizing the
+// window, turning the screen or a high DPI display only change that
+// transform, so nothing about how the game plays depends on them.
+//
+// Pointer input arrives in CSS pixels over the whole canvas and goes back
+// through the same fit to land in logical units.
+
+use super::*;
+
+#[derive(Copy, Clone)]
+pub(crate) struct Viewport {
+    world: WorldConfig,
+    scale: f64,    // CSS pixels per logical unit
+    offset_x: f64, // Width of the left bar, in CSS pixels
+    offset_y: f64, // Height of the top bar, in CSS pixels
+    pixel_ratio: f64,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport {
+            world: WorldConfig::default(),
+            scale: 1.0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            pixel_ratio: 1.0,
+        }
+    }
+}
+
+impl Viewport {
+    // The biggest whole-playfield fit in a canvas `width` x `height` CSS pixels
+    pub(crate) fn fit(world: WorldConfig, width: f64, height: f64, pixel_ratio: f64) -> Self {
+        let scale = (width / world.width).min(height / world.height);
+        Viewport {
+            world,
+            scale,
+            offset_x: (width - world.width * scale) / 2.0,
+            offset_y: (height - world.height * scale) / 2.0,
+            pixel_ratio,
+        }
+    }
+
+    pub(crate) fn world(&self) -> WorldConfig {
+        self.world
+    }
+
+    pub(crate) fn pixels_per_unit(&self) -> f64 {
+        self.scale * self.pixel_ratio
+    }
+
+    // The playfield's box in canvas pixels: x, y, width and height
+    pub(crate) fn playfield_pixels(&self) -> (f64, f64, f64, f64) {
+        let scale = self.pixels_per_unit();
+        (
+            (self.offset_x * self.pixel_ratio).round(),
+            (self.offset_y * self.pixel_ratio).round(),
+            (self.world.width * scale).round(),
+            (self.world.height * scale).round(),
+        )
+    }
+
+    // Where a point on the canvas, in CSS pixels, falls on the playfield
+    pub(crate) fn logical_point(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            (x - self.offset_x) / self.scale,
+            (y - self.offset_y) / self.scale,
+        )
+    }
+
+    // Sizes the canvas to its box on the page at full resolution
+    fn resize(&self, canvas: &HtmlCanvasElement) {
+        canvas.set_width((canvas.client_width() as f64 * self.pixel_ratio).round() as u32);
+        canvas.set_height((canvas.client_height() as f64 * self.pixel_ratio).round() as u32);
+    }
+
+    // Blacks out the bars and leaves the context drawing in logical units,
+    // clipped to the playfield, until end_frame
+    pub(crate) fn begin_frame(&self, context: &CanvasRenderingContext2d) {
+        context.save();
+        context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0).unwrap();
+        if let Some(canvas) = context.canvas() {
+            context.set_fill_style_str("#000");
+            context.fill_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
+        }
+
+        let scale = self.scale * self.pixel_ratio;
+        context
+            .set_transform(
+                scale,
+                0.0,
+                0.0,
+                scale,
+                self.offset_x * self.pixel_ratio,
+                self.offset_y * self.pixel_ratio,
+            )
+            .unwrap();
+        context.begin_path();
+        context.rect(0.0, 0.0, self.world.width, self.world.height);
+        context.clip();
+    }
+
+    // Puts the context back how begin_frame found it, ready for the next fit
+    pub(crate) fn end_frame(&self, context: &CanvasRenderingContext2d) {
+        context.restore();
+    }
+}
+
+// Refits the playfield to the canvas as it's laid out right now, and keeps
+// `viewport` up to date for drawing and the pointer listeners. A
+// `render_scale` under 1 gives the canvas fewer pixels than the screen has.
+pub(crate) fn fit_canvas(
+    canvas: &HtmlCanvasElement,
+    viewport: &Cell<Viewport>,
+    world: WorldConfig,
+    render_scale: f64,
+) {
+    let pixel_ratio = web_sys::window().unwrap().device_pixel_ratio() * render_scale;
+    let fitted = Viewport::fit(
+        world,
+        canvas.client_width() as f64,
+        canvas.client_height() as f64,
+        pixel_ratio,
+    );
+    fitted.resize(canvas);
+    viewport.set(fitted);
+}
+
+// Posts AppMessage::Resized whenever the page's layout might have moved the
+// canvas: a window resize, or a phone or cabinet screen turning
+pub(crate) fn listen_for_resize(inbox: &Rc<RefCell<Vec<AppMessage>>>) -> Result<(), JsValue> {
+    let window = web_sys::window().unwrap();
+    for event_name in ["resize", "orientationchange"] {
+        let inbox = inbox.clone();
+        let handler = Closure::wrap(Box::new(move || {
+            inbox.borrow_mut().push(AppMessage::Resized);
+        }) as Box<dyn FnMut()>);
+        window.add_event_listener_with_callback(event_name, handler.as_ref().unchecked_ref())?;
+        handler.forget();
+    }
+    Ok(())
+}
+
+impl GameState {
+    // Puts anyone a layout change left past a wall back inside it
+    pub(crate) fn keep_players_in_view(&mut self) {
+        let world = self.world;
+        for slot in &mut self.players {
+            let player = &mut slot.player;
+            player.x = player.x.clamp(0.0, world.width - player.width);
+            player.y = world.floor_y() - player.height;
+        }
+    }
+}